@@ -0,0 +1,127 @@
+/*
+Fruits and the priority they are served in.
+
+A Priority is a list of criteria, compared in order: the first criterion
+decides, the next one breaks its ties and so on.
+
+fig       figs before other fruits (the original rule)
+ripeness  riper fruits first
+expiry    the fruit that expires first comes first, no date last
+rank      the rank the user gave, 1 first, unranked last
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+
+/// A fruit of the salad
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fruit {
+    pub name: String,
+    /// 0 (unripe) to 10 (fully ripe)
+    pub ripeness: u8,
+    pub expires: Option<NaiveDate>,
+    /// Assigned by the user, 1 is the most wanted
+    pub rank: Option<u32>,
+}
+
+impl Fruit {
+    /// A fruit without ripeness, expiry date or rank
+    pub fn new(name: &str) -> Self {
+        Fruit {
+            name: name.to_string(),
+            ripeness: 0,
+            expires: None,
+            rank: None,
+        }
+    }
+
+    pub fn is_fig(&self) -> bool {
+        self.name.eq_ignore_ascii_case("fig")
+    }
+}
+
+impl fmt::Display for Fruit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// One rule of a Priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    Fig,
+    Ripeness,
+    Expiry,
+    Rank,
+}
+
+impl Criterion {
+    /// Greater is served first
+    pub fn key(&self, fruit: &Fruit) -> i64 {
+        match self {
+            Criterion::Fig => fruit.is_fig() as i64,
+            Criterion::Ripeness => fruit.ripeness as i64,
+            Criterion::Expiry => fruit
+                .expires
+                .map_or(i64::MIN, |date| -(date.num_days_from_ce() as i64)),
+            Criterion::Rank => fruit.rank.map_or(i64::MIN, |rank| -(rank as i64)),
+        }
+    }
+}
+
+impl FromStr for Criterion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fig" | "figs" => Ok(Criterion::Fig),
+            "ripeness" | "ripe" => Ok(Criterion::Ripeness),
+            "expiry" | "expires" => Ok(Criterion::Expiry),
+            "rank" => Ok(Criterion::Rank),
+            other => Err(format!(
+                "unknown priority '{}'. Choose from: fig, ripeness, expiry, rank",
+                other
+            )),
+        }
+    }
+}
+
+/// How fruits are ordered in the heap, see the module comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Priority(pub Vec<Criterion>);
+
+impl Priority {
+    /// The heap priority of a fruit, compared criterion by criterion
+    pub fn key(&self, fruit: &Fruit) -> Vec<i64> {
+        self.0
+            .iter()
+            .map(|criterion| criterion.key(fruit))
+            .collect()
+    }
+}
+
+impl Default for Priority {
+    /// Figs first, like the original salad
+    fn default() -> Self {
+        Priority(vec![Criterion::Fig])
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    /// Comma separated criteria, e.g. "ripeness,expiry"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let criteria = s
+            .split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if criteria.is_empty() {
+            return Err("no priority given".to_string());
+        }
+        Ok(Priority(criteria))
+    }
+}
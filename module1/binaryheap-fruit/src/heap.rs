@@ -0,0 +1,210 @@
+/*
+An indexed max-heap.
+
+Every push returns a Handle that stays valid until the item is popped or
+removed. The heap keeps the position of each handle, so changing the
+priority of an item or removing it sifts only along one path of the tree,
+O(log n), instead of rebuilding the heap.
+
+Items with equal priority come out in the order they were pushed.
+*/
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Refers to one item of an IndexedHeap. Handles are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Handle(usize);
+
+#[derive(Debug, Clone)]
+struct Entry<T, P> {
+    item: T,
+    priority: P,
+    // Index in IndexedHeap::heap
+    pos: usize,
+}
+
+/// A max-heap of items with a separate priority, see the module comment.
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<T, P> {
+    heap: Vec<Handle>,
+    entries: HashMap<Handle, Entry<T, P>>,
+    next: usize,
+}
+
+impl<T, P: Ord> Default for IndexedHeap<T, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, P: Ord> IndexedHeap<T, P> {
+    pub fn new() -> Self {
+        IndexedHeap {
+            heap: Vec::new(),
+            entries: HashMap::new(),
+            next: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.entries.contains_key(&handle)
+    }
+
+    pub fn push(&mut self, item: T, priority: P) -> Handle {
+        let handle = Handle(self.next);
+        self.next += 1;
+        let pos = self.heap.len();
+        self.heap.push(handle);
+        self.entries.insert(
+            handle,
+            Entry {
+                item,
+                priority,
+                pos,
+            },
+        );
+        self.sift_up(pos);
+        handle
+    }
+
+    /// The item with the highest priority
+    pub fn peek(&self) -> Option<(&T, &P)> {
+        self.heap.first().map(|handle| self.item(*handle))
+    }
+
+    pub fn pop(&mut self) -> Option<(T, P)> {
+        let top = *self.heap.first()?;
+        self.remove(top)
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<(&T, &P)> {
+        self.entries
+            .get(&handle)
+            .map(|entry| (&entry.item, &entry.priority))
+    }
+
+    /// The item to change in place. Its priority stays the same, use
+    /// update_priority for that.
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.entries.get_mut(&handle).map(|entry| &mut entry.item)
+    }
+
+    /// Give an item a new priority and return the old one, O(log n)
+    pub fn update_priority(&mut self, handle: Handle, priority: P) -> Option<P> {
+        let entry = self.entries.get_mut(&handle)?;
+        let old = std::mem::replace(&mut entry.priority, priority);
+        let pos = entry.pos;
+        self.sift_up(pos);
+        self.sift_down(self.entries[&handle].pos);
+        Some(old)
+    }
+
+    /// Take an item out of the heap, O(log n)
+    pub fn remove(&mut self, handle: Handle) -> Option<(T, P)> {
+        let pos = self.entries.get(&handle)?.pos;
+        let last = self.heap.len() - 1;
+        self.swap(pos, last);
+        self.heap.pop();
+        if pos < last {
+            self.sift_up(pos);
+            self.sift_down(pos);
+        }
+        let entry = self.entries.remove(&handle)?;
+        Some((entry.item, entry.priority))
+    }
+
+    /// The first handle, in heap order, whose item matches
+    pub fn find(&self, mut matches: impl FnMut(&T) -> bool) -> Option<Handle> {
+        self.heap
+            .iter()
+            .copied()
+            .find(|handle| matches(&self.entries[handle].item))
+    }
+
+    /// Every handle whose item matches, in heap order
+    pub fn find_all(&self, mut matches: impl FnMut(&T) -> bool) -> Vec<Handle> {
+        self.heap
+            .iter()
+            .copied()
+            .filter(|handle| matches(&self.entries[handle].item))
+            .collect()
+    }
+
+    /// Items in heap (not sorted) order
+    pub fn iter(&self) -> impl Iterator<Item = (Handle, &T, &P)> {
+        self.heap.iter().map(|handle| {
+            let (item, priority) = self.item(*handle);
+            (*handle, item, priority)
+        })
+    }
+
+    /// Items from the highest priority down, the order pop would give. The
+    /// heap stays as it is.
+    pub fn sorted(&self) -> Vec<(&T, &P)> {
+        let mut handles = self.heap.clone();
+        handles.sort_by(|a, b| self.compare(*b, *a));
+        handles
+            .into_iter()
+            .map(|handle| self.item(handle))
+            .collect()
+    }
+
+    fn item(&self, handle: Handle) -> (&T, &P) {
+        let entry = &self.entries[&handle];
+        (&entry.item, &entry.priority)
+    }
+
+    // Greater comes out first: higher priority, then the older handle
+    fn compare(&self, a: Handle, b: Handle) -> Ordering {
+        self.entries[&a]
+            .priority
+            .cmp(&self.entries[&b].priority)
+            .then_with(|| b.cmp(&a))
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        for pos in [i, j] {
+            let handle = self.heap[pos];
+            self.entries.get_mut(&handle).expect("handle in heap").pos = pos;
+        }
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if self.compare(self.heap[pos], self.heap[parent]) != Ordering::Greater {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut largest = pos;
+            for child in [2 * pos + 1, 2 * pos + 2] {
+                if child < self.heap.len()
+                    && self.compare(self.heap[child], self.heap[largest]) == Ordering::Greater
+                {
+                    largest = child;
+                }
+            }
+            if largest == pos {
+                break;
+            }
+            self.swap(pos, largest);
+            pos = largest;
+        }
+    }
+}
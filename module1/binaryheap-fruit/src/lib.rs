@@ -0,0 +1,259 @@
+/*
+A fruit salad served from a priority queue.
+
+The fruits go into an IndexedHeap keyed by a configurable Priority, figs
+first by default. Because the heap knows where every fruit is, a fruit can
+be removed or re-prioritised in O(log n), and sorted() lists the salad in
+serving order without emptying the heap.
+*/
+
+pub mod fruit;
+pub mod heap;
+
+pub use fruit::{Criterion, Fruit, Priority};
+pub use heap::{Handle, IndexedHeap};
+
+use chrono::{Days, NaiveDate};
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+/// Fruits keyed by their Priority
+pub type FruitHeap = IndexedHeap<Fruit, Vec<i64>>;
+
+/// Random fruits until two figs are drawn. Each fruit gets a random
+/// ripeness and an expiry date within two weeks of `today`.
+pub fn generate_fruit_salad<R: Rng + ?Sized>(
+    rng: &mut R,
+    priority: &Priority,
+    today: NaiveDate,
+) -> FruitHeap {
+    let fruits = vec![
+        "Apple", "Orange", "Pear", "Peach", "Banana", "Fig", "Fig", "Fig", "Fig",
+    ];
+
+    let mut names = Vec::new();
+    let mut figs_count = 0;
+    while figs_count < 2 {
+        let fruit = fruits.choose(rng).unwrap();
+        if *fruit == "Fig" {
+            figs_count += 1;
+        }
+        names.push(*fruit);
+    }
+
+    let mut fruit_salad = FruitHeap::new();
+    for name in names {
+        let fruit = Fruit {
+            ripeness: rng.gen_range(0..=10),
+            expires: today.checked_add_days(Days::new(rng.gen_range(1..=14))),
+            ..Fruit::new(name)
+        };
+        let key = priority.key(&fruit);
+        fruit_salad.push(fruit, key);
+    }
+
+    fruit_salad
+}
+
+/// Remove every fruit with this name, case-insensitive. Returns how many
+/// were removed.
+pub fn remove_fruit(heap: &mut FruitHeap, target: &str) -> usize {
+    let handles = heap.find_all(|fruit| fruit.name.eq_ignore_ascii_case(target.trim()));
+    for handle in &handles {
+        heap.remove(*handle);
+    }
+    handles.len()
+}
+
+/// Change every fruit with this name and move it to its new place in the
+/// heap. Returns how many were changed.
+pub fn update_fruit(
+    heap: &mut FruitHeap,
+    priority: &Priority,
+    target: &str,
+    change: impl Fn(&mut Fruit),
+) -> usize {
+    let handles = heap.find_all(|fruit| fruit.name.eq_ignore_ascii_case(target.trim()));
+    for handle in &handles {
+        let fruit = heap.get_mut(*handle).expect("handle was just found");
+        change(fruit);
+        let key = priority.key(fruit);
+        heap.update_priority(*handle, key);
+    }
+    handles.len()
+}
+
+/// The fruit names from the highest priority down
+pub fn names(heap: &FruitHeap) -> Vec<String> {
+    heap.sorted()
+        .into_iter()
+        .map(|(fruit, _)| fruit.name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    fn salad(seed: u64, priority: &Priority) -> FruitHeap {
+        generate_fruit_salad(&mut StdRng::seed_from_u64(seed), priority, today())
+    }
+
+    fn heap_of(fruits: Vec<Fruit>, priority: &Priority) -> FruitHeap {
+        let mut heap = FruitHeap::new();
+        for fruit in fruits {
+            let key = priority.key(&fruit);
+            heap.push(fruit, key);
+        }
+        heap
+    }
+
+    #[test]
+    fn test_same_seed_same_salad() {
+        let mut fruits = names(&salad(2, &Priority::default()));
+        assert_eq!(&fruits[..2], ["Fig", "Fig"]);
+        fruits.sort();
+        assert_eq!(
+            fruits,
+            ["Apple", "Banana", "Banana", "Fig", "Fig", "Peach", "Pear"]
+        );
+        let priority = "ripeness".parse().unwrap();
+        assert_eq!(salad(7, &priority).sorted(), salad(7, &priority).sorted());
+    }
+
+    #[test]
+    fn test_two_figs_on_top() {
+        for seed in 0..20 {
+            let mut salad = salad(seed, &Priority::default());
+            assert!(salad.pop().unwrap().0.is_fig());
+            assert!(salad.pop().unwrap().0.is_fig());
+            assert!(!salad.iter().any(|(_, fruit, _)| fruit.is_fig()));
+        }
+    }
+
+    #[test]
+    fn test_remove_fruit_with_seed() {
+        let mut salad = salad(2, &Priority::default());
+        assert_eq!(remove_fruit(&mut salad, "banana"), 2);
+        assert_eq!(remove_fruit(&mut salad, "kiwi"), 0);
+        let mut remaining = names(&salad);
+        remaining.sort();
+        assert_eq!(remaining, ["Apple", "Fig", "Fig", "Peach", "Pear"]);
+    }
+
+    #[test]
+    fn test_parse_priority() {
+        assert_eq!(
+            "Ripeness, expiry".parse::<Priority>(),
+            Ok(Priority(vec![Criterion::Ripeness, Criterion::Expiry]))
+        );
+        assert!("".parse::<Priority>().is_err());
+        assert_eq!(
+            "size".parse::<Priority>().unwrap_err(),
+            "unknown priority 'size'. Choose from: fig, ripeness, expiry, rank"
+        );
+    }
+
+    #[test]
+    fn test_priority_criteria() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d);
+        let fruits = vec![
+            Fruit {
+                ripeness: 3,
+                expires: day(9),
+                rank: Some(2),
+                ..Fruit::new("Apple")
+            },
+            Fruit {
+                ripeness: 9,
+                expires: day(5),
+                rank: None,
+                ..Fruit::new("Pear")
+            },
+            Fruit {
+                ripeness: 9,
+                expires: None,
+                rank: Some(1),
+                ..Fruit::new("Fig")
+            },
+        ];
+        let order = |priority: &str| {
+            let priority = priority.parse().unwrap();
+            names(&heap_of(fruits.clone(), &priority))
+        };
+        assert_eq!(order("fig"), ["Fig", "Apple", "Pear"]);
+        assert_eq!(order("expiry"), ["Pear", "Apple", "Fig"]);
+        assert_eq!(order("rank"), ["Fig", "Apple", "Pear"]);
+        assert_eq!(order("ripeness"), ["Pear", "Fig", "Apple"]);
+        assert_eq!(order("ripeness,expiry"), ["Pear", "Fig", "Apple"]);
+        assert_eq!(order("ripeness,rank"), ["Fig", "Pear", "Apple"]);
+    }
+
+    #[test]
+    fn test_update_fruit_moves_it() {
+        let priority: Priority = "rank".parse().unwrap();
+        let mut heap = heap_of(
+            vec![Fruit::new("Apple"), Fruit::new("Pear"), Fruit::new("Peach")],
+            &priority,
+        );
+        assert_eq!(
+            update_fruit(&mut heap, &priority, "peach", |f| f.rank = Some(1)),
+            1
+        );
+        assert_eq!(
+            update_fruit(&mut heap, &priority, "pear", |f| f.rank = Some(2)),
+            1
+        );
+        assert_eq!(names(&heap), ["Peach", "Pear", "Apple"]);
+        assert_eq!(heap.peek().unwrap().0.rank, Some(1));
+    }
+
+    #[test]
+    fn test_indexed_heap_operations() {
+        let mut heap = IndexedHeap::new();
+        let handles: Vec<Handle> = [5, 1, 8, 3, 9, 2, 7]
+            .into_iter()
+            .map(|p| heap.push(format!("p{}", p), p))
+            .collect();
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.peek(), Some((&"p9".to_string(), &9)));
+
+        assert_eq!(heap.remove(handles[4]), Some(("p9".to_string(), 9)));
+        assert_eq!(heap.remove(handles[4]), None);
+        assert!(!heap.contains(handles[4]));
+
+        assert_eq!(heap.update_priority(handles[1], 10), Some(1));
+        assert_eq!(heap.update_priority(handles[2], 0), Some(8));
+
+        // sorted() leaves the heap alone
+        let sorted: Vec<i32> = heap.sorted().into_iter().map(|(_, p)| *p).collect();
+        assert_eq!(sorted, [10, 7, 5, 3, 2, 0]);
+        assert_eq!(heap.len(), 6);
+
+        let mut popped = Vec::new();
+        while let Some((_, p)) = heap.pop() {
+            popped.push(p);
+        }
+        assert_eq!(popped, sorted);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_equal_priorities_keep_push_order() {
+        let mut heap = IndexedHeap::new();
+        for name in ["a", "b", "c", "d"] {
+            heap.push(name, 0);
+        }
+        let b = heap.find(|item| *item == "b").unwrap();
+        heap.remove(b);
+        let order: Vec<&str> = heap.sorted().into_iter().map(|(item, _)| *item).collect();
+        assert_eq!(order, ["a", "c", "d"]);
+        assert_eq!(heap.pop(), Some(("a", 0)));
+    }
+}
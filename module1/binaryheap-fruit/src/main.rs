@@ -1,8 +1,9 @@
+use binaryheap_fruit::{Priority, generate_fruit_salad, names, remove_fruit, update_fruit};
+use chrono::Local;
 use clap::Parser;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::cmp::Ord;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::collections::{HashMap, HashSet};
 
 /// Command-line arguments
 #[derive(Parser)]
@@ -12,101 +13,72 @@ struct Args {
     /// Fruit to remove (e.g., Fig or Apple)
     #[arg(short, long)]
     remove: Option<String>,
-}
-
-#[derive(Eq, PartialEq, Debug, Clone, Hash)]
-enum Fruit {
-    Fig,
-    Other(String),
-}
-
-// We define Figs as the highest priority by implementing Ord
-impl Ord for Fruit {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match (self, other) {
-            (Fruit::Fig, Fruit::Fig) => std::cmp::Ordering::Equal,
-            (Fruit::Fig, Fruit::Other(_)) => std::cmp::Ordering::Greater,
-            (Fruit::Other(_), Fruit::Fig) => std::cmp::Ordering::Less,
-            (Fruit::Other(_), Fruit::Other(_)) => std::cmp::Ordering::Equal,
-        }
-    }
-}
-
-impl PartialOrd for Fruit {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-fn generate_fruit_salad() -> BinaryHeap<Fruit> {
-    let mut rng = thread_rng();
-    let fruits = vec![
-        "Apple", "Orange", "Pear", "Peach", "Banana", "Fig", "Fig", "Fig", "Fig",
-    ];
-    let mut fruit_salad = BinaryHeap::new();
 
-    let mut figs_count = 0;
-    while figs_count < 2 {
-        let fruit = fruits.choose(&mut rng).unwrap();
-        if *fruit == "Fig" {
-            figs_count += 1;
-            fruit_salad.push(Fruit::Fig);
-        } else {
-            fruit_salad.push(Fruit::Other(fruit.to_string()));
-        }
-    }
+    /// Seed for the random fruits, the same seed gives the same salad
+    #[arg(short, long)]
+    seed: Option<u64>,
 
-    fruit_salad
-}
+    /// Serving order: comma separated fig, ripeness, expiry and rank, the
+    /// first one deciding
+    #[arg(short, long, default_value = "fig")]
+    priority: Priority,
 
-fn remove_fruit(heap: BinaryHeap<Fruit>, target: &str) -> BinaryHeap<Fruit> {
-    let filtered = heap
-        .into_iter()
-        .filter(|fruit| match fruit {
-            Fruit::Fig => target.to_lowercase() != "fig",
-            Fruit::Other(name) => name.to_lowercase() != target.to_lowercase(),
-        })
-        .collect::<Vec<_>>();
+    /// Rank fruits by name, the first one given gets rank 1. Can be repeated
+    #[arg(long)]
+    rank: Vec<String>,
 
-    BinaryHeap::from(filtered)
+    /// Make a fruit fully ripe, can be repeated
+    #[arg(long)]
+    ripen: Vec<String>,
 }
 
 fn main() {
     let args = Args::parse();
-    let fruit_salad = generate_fruit_salad();
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let today = Local::now().date_naive();
+    let mut fruit_salad = generate_fruit_salad(&mut rng, &args.priority, today);
+
+    for (i, name) in args.rank.iter().enumerate() {
+        let rank = i as u32 + 1;
+        update_fruit(&mut fruit_salad, &args.priority, name, |fruit| {
+            fruit.rank = Some(rank)
+        });
+    }
+    for name in &args.ripen {
+        update_fruit(&mut fruit_salad, &args.priority, name, |fruit| {
+            fruit.ripeness = 10
+        });
+    }
 
-    let fruit_salad = if let Some(fruit_to_remove) = args.remove {
+    if let Some(fruit_to_remove) = args.remove {
         println!("Removing fruit: {}", fruit_to_remove);
-        remove_fruit(fruit_salad, &fruit_to_remove)
-    } else {
-        fruit_salad
-    };
+        remove_fruit(&mut fruit_salad, &fruit_to_remove);
+    }
 
     println!("Random Fruit Salad With Two Servings of Figs:");
-    for fruit in fruit_salad.clone().into_sorted_vec() {
-        match fruit {
-            Fruit::Fig => println!("Fig"),
-            Fruit::Other(fruit_name) => println!("{}", fruit_name),
-        }
+    for (fruit, _) in fruit_salad.sorted() {
+        let expires = fruit
+            .expires
+            .map_or("-".to_string(), |date| date.to_string());
+        let rank = fruit.rank.map_or("-".to_string(), |rank| rank.to_string());
+        println!(
+            "{:<8} ripeness {:>2}, expires {}, rank {}",
+            fruit.name, fruit.ripeness, expires, rank
+        );
     }
 
     let mut fruit_counts = HashMap::new();
-    for fruit in fruit_salad.clone() {
-        let name = match fruit {
-            Fruit::Fig => "Fig".to_string(),
-            Fruit::Other(name) => name.clone(),
-        };
-        *fruit_counts.entry(name).or_insert(0) += 1;
+    for (_, fruit, _) in fruit_salad.iter() {
+        *fruit_counts.entry(fruit.name.clone()).or_insert(0) += 1;
     }
 
     let mut unique_names = Vec::new();
     let mut seen = HashSet::new();
 
-    for fruit in fruit_salad.clone().into_sorted_vec().iter().rev() {
-        let name = match fruit {
-            Fruit::Fig => "Fig".to_string(),
-            Fruit::Other(name) => name.clone(),
-        };
+    for name in names(&fruit_salad).into_iter().rev() {
         if seen.insert(name.clone()) {
             unique_names.push(name); // preserve reverse order of first-seen unique fruits
         }
@@ -117,6 +89,10 @@ fn main() {
         println!("{}", name);
     }
 
+    // Sorted by name, HashMap order changes from run to run
+    let mut fruit_counts: Vec<(String, i32)> = fruit_counts.into_iter().collect();
+    fruit_counts.sort();
+
     println!("\nFruits with count");
     for (fruit, count) in fruit_counts {
         println!("{} {}", fruit, count);
@@ -0,0 +1,195 @@
+/*
+Counting how often each value occurs, for any value that can be hashed
+and ordered: numbers, words, CSV cells.
+
+The values come out most frequent first; values with the same count are
+in their own order, so the result is the same on every run.
+*/
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use serde::Serialize;
+
+/// The counts of a list of values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frequencies<T: Hash + Eq + Ord> {
+    counts: HashMap<T, u64>,
+    total: u64,
+}
+
+/// One value of a frequency table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row<'a, T> {
+    pub value: &'a T,
+    pub count: u64,
+    /// Share of all values, 0 to 100
+    pub percent: f64,
+    /// Share of this value and all more frequent ones, 0 to 100
+    pub cumulative: f64,
+}
+
+/// Statistics of the whole distribution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary<'a, T> {
+    pub total: u64,
+    pub distinct: usize,
+    /// The most frequent values, more than one on a tie
+    pub mode: Vec<&'a T>,
+    /// Shannon entropy in bits
+    pub entropy: f64,
+    /// How many of the most frequent values make up PARETO_SHARE of all values
+    pub pareto: usize,
+}
+
+/// The share of all values the Pareto summary looks for
+pub const PARETO_SHARE: f64 = 0.8;
+
+impl<T: Hash + Eq + Ord> Default for Frequencies<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq + Ord> Frequencies<T> {
+    pub fn new() -> Self {
+        Frequencies {
+            counts: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        *self.counts.entry(value).or_insert(0) += 1;
+        self.total += 1;
+    }
+
+    /// How often a value occurs
+    pub fn get(&self, value: &T) -> u64 {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+
+    /// Number of values counted
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Number of distinct values
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Values and counts, most frequent first
+    pub fn sorted(&self) -> Vec<(&T, u64)> {
+        let mut sorted: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(value, count)| (value, *count))
+            .collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        sorted
+    }
+
+    /// Values and counts, most frequent first, taking the values
+    pub fn into_sorted(self) -> Vec<(T, u64)> {
+        let mut sorted: Vec<_> = self.counts.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        sorted
+    }
+
+    /// The frequency table with percentages and the cumulative
+    /// distribution, most frequent first
+    pub fn rows(&self) -> Vec<Row<'_, T>> {
+        let mut running = 0;
+        self.sorted()
+            .into_iter()
+            .map(|(value, count)| {
+                running += count;
+                Row {
+                    value,
+                    count,
+                    percent: self.percent(count),
+                    cumulative: self.percent(running),
+                }
+            })
+            .collect()
+    }
+
+    /// The most frequent values, in order
+    pub fn mode(&self) -> Vec<&T> {
+        let sorted = self.sorted();
+        let Some(&(_, max)) = sorted.first() else {
+            return Vec::new();
+        };
+        sorted
+            .into_iter()
+            .take_while(|(_, count)| *count == max)
+            .map(|(value, _)| value)
+            .collect()
+    }
+
+    /// Shannon entropy in bits: 0 when every value is the same, log2(n)
+    /// when n values are equally frequent
+    pub fn entropy(&self) -> f64 {
+        let total = self.total as f64;
+        self.counts
+            .values()
+            .map(|count| {
+                let p = *count as f64 / total;
+                p * (1.0 / p).log2()
+            })
+            .sum()
+    }
+
+    /// How many of the most frequent values it takes to make up `share`
+    /// (0 to 1) of all values
+    pub fn pareto(&self, share: f64) -> usize {
+        let target = share * self.total as f64;
+        let mut running = 0;
+        for (taken, (_, count)) in self.sorted().into_iter().enumerate() {
+            if running as f64 >= target {
+                return taken;
+            }
+            running += count;
+        }
+        self.len()
+    }
+
+    pub fn summary(&self) -> Summary<'_, T> {
+        Summary {
+            total: self.total,
+            distinct: self.len(),
+            mode: self.mode(),
+            entropy: self.entropy(),
+            pareto: self.pareto(PARETO_SHARE),
+        }
+    }
+
+    fn percent(&self, count: u64) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            100.0 * count as f64 / self.total as f64
+        }
+    }
+}
+
+impl<T: Hash + Eq + Ord> FromIterator<T> for Frequencies<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut frequencies = Frequencies::new();
+        frequencies.extend(values);
+        frequencies
+    }
+}
+
+impl<T: Hash + Eq + Ord> Extend<T> for Frequencies<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, values: I) {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
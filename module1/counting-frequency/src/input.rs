@@ -0,0 +1,140 @@
+/*
+Where the values come from: the command line, stdin, text files or a
+column of a CSV file. Text is split on whitespace, CSV cells are taken as
+they are with the surrounding spaces trimmed; empty cells are skipped.
+*/
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(PathBuf, io::Error),
+    Csv(PathBuf, csv::Error),
+    /// The CSV file has no column with this name or index
+    NoColumn(PathBuf, String),
+    /// A value is not of the type being counted, with the type's name and
+    /// the FromStr error
+    Parse {
+        value: String,
+        expected: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            InputError::Csv(path, err) => write!(f, "{}: {err}", path.display()),
+            InputError::NoColumn(path, column) => {
+                write!(f, "{}: no column {column:?}", path.display())
+            }
+            InputError::Parse {
+                value,
+                expected,
+                reason,
+            } => write!(f, "{value:?} is not a valid {expected}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// One place to read values from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Values(Vec<String>),
+    Stdin,
+    File(PathBuf),
+    /// A column of a CSV file with a header row, by name or 0-based index
+    Csv {
+        path: PathBuf,
+        column: String,
+    },
+}
+
+impl Source {
+    pub fn read(&self) -> Result<Vec<String>, InputError> {
+        match self {
+            Source::Values(values) => Ok(values.clone()),
+            Source::Stdin => read_words(io::stdin().lock(), Path::new("<stdin>")),
+            Source::File(path) => {
+                let file = File::open(path).map_err(|err| InputError::Io(path.clone(), err))?;
+                read_words(BufReader::new(file), path)
+            }
+            Source::Csv { path, column } => {
+                let file = File::open(path).map_err(|err| InputError::Io(path.clone(), err))?;
+                read_column(file, path, column)
+            }
+        }
+    }
+}
+
+/// The values of all sources, in order
+pub fn read_all(sources: &[Source]) -> Result<Vec<String>, InputError> {
+    let mut values = Vec::new();
+    for source in sources {
+        values.extend(source.read()?);
+    }
+    Ok(values)
+}
+
+/// Values as numbers, or another type with FromStr
+pub fn parse_all<T>(values: Vec<String>) -> Result<Vec<T>, InputError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    values
+        .into_iter()
+        .map(|value| {
+            value.parse().map_err(|err: T::Err| InputError::Parse {
+                reason: err.to_string(),
+                expected: std::any::type_name::<T>(),
+                value,
+            })
+        })
+        .collect()
+}
+
+fn read_words<R: BufRead>(reader: R, path: &Path) -> Result<Vec<String>, InputError> {
+    let mut words = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|err| InputError::Io(path.to_path_buf(), err))?;
+        words.extend(line.split_whitespace().map(str::to_string));
+    }
+    Ok(words)
+}
+
+pub fn read_column<R: Read>(
+    reader: R,
+    path: &Path,
+    column: &str,
+) -> Result<Vec<String>, InputError> {
+    let csv_error = |err| InputError::Csv(path.to_path_buf(), err);
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader.headers().map_err(csv_error)?;
+    // A header name wins over an index, in case a column is called "2"
+    let index = headers
+        .iter()
+        .position(|header| header.trim() == column)
+        .or_else(|| column.parse().ok().filter(|index| *index < headers.len()))
+        .ok_or_else(|| InputError::NoColumn(path.to_path_buf(), column.to_string()))?;
+
+    let mut values = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        if let Some(value) = record
+            .get(index)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            values.push(value.to_string());
+        }
+    }
+    Ok(values)
+}
@@ -0,0 +1,138 @@
+/*
+A frequency counter for any value that can be hashed and ordered.
+
+Values are read from the command line, stdin, text files or CSV columns
+and counted in a Frequencies table. The table gives the values most
+frequent first with their percentages and cumulative distribution, and a
+summary with the mode, the entropy and how many values make up 80% of all
+of them. It prints as text with an ASCII histogram, as JSON or as CSV.
+*/
+
+pub mod frequency;
+pub mod input;
+pub mod output;
+
+use std::hash::Hash;
+
+pub use frequency::{Frequencies, PARETO_SHARE, Row, Summary};
+pub use input::{InputError, Source, parse_all, read_all};
+pub use output::{Format, View, render};
+
+/// The frequency of each value, most frequent first. Works the same for
+/// integers, words or anything else that can be hashed and ordered.
+pub fn logic<T: Hash + Eq + Ord>(values: Vec<T>) -> Vec<(T, u64)> {
+    values.into_iter().collect::<Frequencies<T>>().into_sorted()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn words(text: &str) -> Frequencies<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_logic() {
+        let numbers = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 3];
+        let result = logic(numbers);
+        assert_eq!(result[..3], [(1, 2), (3, 2), (2, 1)]);
+        assert_eq!(result.len(), 9);
+
+        let result = logic(vec!["b", "a", "b"]);
+        assert_eq!(result, [("b", 2), ("a", 1)]);
+    }
+
+    #[test]
+    fn test_rows() {
+        let frequencies = words("a b a c a b");
+        let rows = frequencies.rows();
+        assert_eq!(rows.len(), 3);
+        assert_eq!((rows[0].value.as_str(), rows[0].count), ("a", 3));
+        assert_eq!(rows[0].percent, 50.0);
+        assert_eq!(rows[1].cumulative, 100.0 * 5.0 / 6.0);
+        assert_eq!(rows[2].cumulative, 100.0);
+    }
+
+    #[test]
+    fn test_summary() {
+        let frequencies = words("a b c d");
+        let summary = frequencies.summary();
+        assert_eq!(summary.mode.len(), 4);
+        assert_eq!(summary.entropy, 2.0);
+        assert_eq!(summary.pareto, 4);
+
+        // 8 of 10 values are "x" or "y"
+        let frequencies = words("x x x x x y y y z w");
+        let summary = frequencies.summary();
+        assert_eq!(summary.mode, [&"x".to_string()]);
+        assert_eq!(summary.pareto, 2);
+        assert_eq!(words("same same").entropy(), 0.0);
+
+        let empty = Frequencies::<i64>::new();
+        assert!(empty.mode().is_empty());
+        assert_eq!(empty.pareto(PARETO_SHARE), 0);
+    }
+
+    #[test]
+    fn test_csv_column() {
+        let csv = "name, color\napple,red\nlime, green\ncherry,red\nkiwi,\n";
+        let path = Path::new("fruits.csv");
+        let colors = input::read_column(csv.as_bytes(), path, "color").unwrap();
+        assert_eq!(colors, ["red", "green", "red"]);
+        let names = input::read_column(csv.as_bytes(), path, "0").unwrap();
+        assert_eq!(names.len(), 4);
+        assert!(matches!(
+            input::read_column(csv.as_bytes(), path, "size"),
+            Err(InputError::NoColumn(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        let numbers: Vec<i64> = parse_all(vec!["3".into(), "-1".into()]).unwrap();
+        assert_eq!(numbers, [3, -1]);
+        let error = parse_all::<i64>(vec!["3".into(), "three".into()]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "\"three\" is not a valid i64: invalid digit found in string"
+        );
+        let error = parse_all::<f64>(vec!["1.5e".into()]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "\"1.5e\" is not a valid f64: invalid float literal"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let frequencies: Frequencies<i64> = [2, 1, 2, 2].into_iter().collect();
+        let view = View {
+            top: None,
+            width: 6,
+        };
+
+        let text = render(&frequencies, Format::Text, view);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "2            3   75.00%      75.00%  ######");
+        assert_eq!(lines[2], "1            1   25.00%     100.00%  ##");
+        assert!(text.contains("total 4, distinct 2, mode 2"));
+
+        let csv = render(
+            &frequencies,
+            Format::Csv,
+            View {
+                top: Some(1),
+                width: 6,
+            },
+        );
+        assert_eq!(csv, "value,count,percent,cumulative\n2,3,75.00,75.00\n");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&frequencies, Format::Json, view)).unwrap();
+        assert_eq!(json["summary"]["mode"][0], 2);
+        assert_eq!(json["summary"]["pareto"], 2);
+        assert_eq!(json["rows"][1]["cumulative"], 100.0);
+    }
+}
@@ -1,110 +1,131 @@
 /*
 This example code counts the frequency of each number in the vector.
  */
-use std::collections::HashMap;
 
 // 1. Use clap  modify the program to accept input from the
 // user and then calculate the frequency of each integer.
 // 2.  Extend this concept to count the frequency of words in a sentence.
-
-use clap::{Arg, Command};
-
-fn main_with_clap() -> Vec<(String, u32)> {
-    let matches = Command::new("counting-frequency")
-        .version("1.0")
-        .author("Author Name")
-        .about("Counts the frequency of each integer/word in a list")
-        .arg(
-            Arg::new("numbers or words")
-                .help("List of integers or words to count frequency")
-                .required(false)
-                .num_args(1..),
-        )
-        .get_matches();
-
-    let numbers: Vec<String> = if let Some(nums) = matches.get_many::<String>("numbers or words") {
-        nums.map(|s| s.to_string()).collect()
-    } else {
-        vec![ ]
-    };
-
-    let _result: Vec<(String, u32)> = logic_word(numbers);
-    _result
-}
-
-fn logic_word(numbers_or_words: Vec<String>) -> Vec<(String, u32)> {
-    let mut frequencies = HashMap::new();
-
-    for item in numbers_or_words {
-        let frequency = frequencies.entry(item).or_insert(0);
-        *frequency += 1;
-    }
-
-    let mut result = Vec::new();
-
-    for (item, frequency) in frequencies {
-        result.push((item, frequency));
-    }
-
-    result
+// 3. order the result by frequency = value not key
+
+use std::fmt::Display;
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::process;
+
+use clap::Parser;
+use counting_frequency::{Format, Frequencies, Source, View, logic, parse_all, read_all, render};
+use serde::Serialize;
+
+#[derive(Parser)]
+#[command(
+    name = "counting-frequency",
+    version = "1.0",
+    about = "Counts the frequency of each integer/word in a list"
+)]
+struct Cli {
+    /// List of integers or words to count frequency
+    values: Vec<String>,
+
+    /// Read whitespace separated values from stdin
+    #[arg(long)]
+    stdin: bool,
+
+    /// Read whitespace separated values from a file, can be repeated
+    #[arg(short, long = "file", value_name = "FILE")]
+    files: Vec<PathBuf>,
+
+    /// Read a column of a CSV file with a header row, can be repeated
+    #[arg(long, value_name = "FILE", requires = "column")]
+    csv: Vec<PathBuf>,
+
+    /// The CSV column, by header name or 0-based index
+    #[arg(long)]
+    column: Option<String>,
+
+    /// Count the values as integers, ordered as numbers on ties
+    #[arg(short, long)]
+    numbers: bool,
+
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+
+    /// Only show the most frequent values
+    #[arg(long)]
+    top: Option<usize>,
+
+    /// Width of the longest histogram bar
+    #[arg(long, default_value_t = 40)]
+    width: usize,
+
+    /// Write to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
 }
 
-fn logic(numbers: Vec<i32>) -> Vec<(i32, u32)> {
-    let mut frequencies = HashMap::new();
-
-    for num in numbers {
-        let frequency = frequencies.entry(num).or_insert(0);
-        *frequency += 1;
+impl Cli {
+    fn sources(&self) -> Vec<Source> {
+        let mut sources = Vec::new();
+        if !self.values.is_empty() {
+            sources.push(Source::Values(self.values.clone()));
+        }
+        if self.stdin {
+            sources.push(Source::Stdin);
+        }
+        sources.extend(self.files.iter().cloned().map(Source::File));
+        let column = self.column.clone().unwrap_or_default();
+        sources.extend(self.csv.iter().map(|path| Source::Csv {
+            path: path.clone(),
+            column: column.clone(),
+        }));
+        sources
     }
 
-    let mut result = Vec::new();
-
-    for (num, frequency) in frequencies {
-        result.push((num, frequency));
+    fn view(&self) -> View {
+        View {
+            top: self.top,
+            width: self.width,
+        }
     }
-
-    result
 }
 
-// 3. order Vec(i32, u32) by frequency = value not key
-fn order_by_frequency_string(vec: Vec<(String, u32)>) -> Vec<(String, u32)> {
-    let mut vec = vec;
-    vec.sort_by(|a, b| b.1.cmp(&a.1));
-    vec
+fn report<T>(values: Vec<T>, cli: &Cli) -> String
+where
+    T: Hash + Eq + Ord + Display + Serialize,
+{
+    let frequencies: Frequencies<T> = values.into_iter().collect();
+    render(&frequencies, cli.format, cli.view())
 }
 
-// 3. order Vec(String, u32) by frequency = value not key
-fn order_by_frequency_int(vec: Vec<(i32, u32)>) -> Vec<(i32, u32)> {
-    let mut vec = vec;
-    vec.sort_by(|a, b| b.1.cmp(&a.1));
-    vec
+fn run(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let values = read_all(&cli.sources())?;
+    let text = if cli.numbers {
+        report(parse_all::<i64>(values)?, cli)
+    } else {
+        report(values, cli)
+    };
+    match &cli.output {
+        Some(path) => std::fs::write(path, text)?,
+        None => print!("{text}"),
+    }
+    Ok(())
 }
 
 fn main() {
-    let numbers = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 3];
-    let result = logic(numbers);
-    //print the results in a human readable format that explains what the result is.
-    println!(
-        "The frequency of each number in the vector is: {:?}",
-        result
-    );
-    
-    // 3. order
-    println!(
-        "Ordered The frequency of each number in the vector is: {:?}",
-        order_by_frequency_int(result)
-    );
-
-    let _cli_result = main_with_clap();
-    // Print the results from the CLI
-    println!(
-        "The frequency of each number/word from CLI input is: {:?}",
-        _cli_result
-    );
-
-    // 3. ordered from CLI
-    println!(
-        "Ordered frequency of each number/word from CLI input is: {:?}",
-        order_by_frequency_string(_cli_result)
-    );
+    let cli = Cli::parse();
+    if cli.sources().is_empty() {
+        // No input given: the example from the lab
+        let numbers = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 3];
+        let result = logic(numbers);
+        //print the results in a human readable format that explains what the result is.
+        println!(
+            "Ordered frequency of each number in the vector is: {:?}",
+            result
+        );
+        println!("Pass integers or words, --stdin, --file or --csv to count your own values.");
+        return;
+    }
+    if let Err(err) = run(&cli) {
+        eprintln!("Error: {err}");
+        process::exit(1);
+    }
 }
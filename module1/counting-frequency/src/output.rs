@@ -0,0 +1,165 @@
+/*
+Frequency tables as text with an ASCII histogram, as JSON or as CSV.
+
+The text and JSON output end with the summary: total, distinct values,
+mode, entropy and the Pareto count. CSV has one row per value only.
+*/
+
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::frequency::{Frequencies, PARETO_SHARE, Row, Summary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+/// What to show of a frequency table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct View {
+    /// Only the most frequent values, all of them when None
+    pub top: Option<usize>,
+    /// Characters of the longest histogram bar
+    pub width: usize,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            top: None,
+            width: 40,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a, T> {
+    summary: Summary<'a, T>,
+    rows: Vec<Row<'a, T>>,
+}
+
+pub fn render<T>(frequencies: &Frequencies<T>, format: Format, view: View) -> String
+where
+    T: Hash + Eq + Ord + Display + Serialize,
+{
+    let mut rows = frequencies.rows();
+    if let Some(top) = view.top {
+        rows.truncate(top);
+    }
+    match format {
+        Format::Text => to_text(frequencies, &rows, view.width),
+        Format::Json => to_json(frequencies, rows),
+        Format::Csv => to_csv(&rows),
+    }
+}
+
+fn to_text<T>(frequencies: &Frequencies<T>, rows: &[Row<T>], width: usize) -> String
+where
+    T: Hash + Eq + Ord + Display,
+{
+    let values: Vec<String> = rows.iter().map(|row| row.value.to_string()).collect();
+    let value_width = values
+        .iter()
+        .map(|value| value.chars().count())
+        .max()
+        .unwrap_or(0);
+    let value_width = value_width.max("value".len());
+    let max = rows.first().map_or(0, |row| row.count);
+
+    let mut text = String::new();
+    writeln!(
+        text,
+        "{:<value_width$}  {:>7}  {:>7}  {:>10}  histogram",
+        "value", "count", "percent", "cumulative"
+    )
+    .unwrap();
+    for (row, value) in rows.iter().zip(&values) {
+        writeln!(
+            text,
+            "{value:<value_width$}  {:>7}  {:>6.2}%  {:>9.2}%  {}",
+            row.count,
+            row.percent,
+            row.cumulative,
+            bar(row.count, max, width)
+        )
+        .unwrap();
+    }
+
+    let summary = frequencies.summary();
+    let mode: Vec<String> = summary.mode.iter().map(|value| value.to_string()).collect();
+    writeln!(text).unwrap();
+    writeln!(
+        text,
+        "total {}, distinct {}, mode {}",
+        summary.total,
+        summary.distinct,
+        if mode.is_empty() {
+            "-".to_string()
+        } else {
+            mode.join(", ")
+        }
+    )
+    .unwrap();
+    writeln!(
+        text,
+        "entropy {:.3} bits (at most {:.3})",
+        summary.entropy,
+        (summary.distinct.max(1) as f64).log2()
+    )
+    .unwrap();
+    writeln!(
+        text,
+        "{}% of all values come from {} of {} distinct values",
+        PARETO_SHARE * 100.0,
+        summary.pareto,
+        summary.distinct
+    )
+    .unwrap();
+    text
+}
+
+// Bars are scaled to the most frequent value; any count gets at least one #
+fn bar(count: u64, max: u64, width: usize) -> String {
+    if count == 0 || max == 0 {
+        return String::new();
+    }
+    let len = (count as f64 / max as f64 * width as f64).round() as usize;
+    "#".repeat(len.max(1))
+}
+
+fn to_json<T>(frequencies: &Frequencies<T>, rows: Vec<Row<T>>) -> String
+where
+    T: Hash + Eq + Ord + Serialize,
+{
+    let report = Report {
+        summary: frequencies.summary(),
+        rows,
+    };
+    serde_json::to_string_pretty(&report).expect("a report serializes to JSON") + "\n"
+}
+
+fn to_csv<T: Display>(rows: &[Row<T>]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["value", "count", "percent", "cumulative"])
+        .expect("writing to a Vec");
+    for row in rows {
+        writer
+            .write_record([
+                row.value.to_string(),
+                row.count.to_string(),
+                format!("{:.2}", row.percent),
+                format!("{:.2}", row.cumulative),
+            ])
+            .expect("writing to a Vec");
+    }
+    let bytes = writer.into_inner().expect("flushing a Vec");
+    String::from_utf8(bytes).expect("CSV of strings is UTF-8")
+}
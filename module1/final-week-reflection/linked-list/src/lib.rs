@@ -0,0 +1,50 @@
+/*
+A doubly-linked list and the word count list built on it.
+
+LinkedList<T> owns its nodes and has a cursor to insert, remove and
+splice anywhere in O(1). A list of WordCount kept sorted by count with
+insert_by_count gives the most common words of a text from the front.
+*/
+
+pub mod list;
+
+pub use list::{CursorMut, IntoIter, Iter, IterMut, LinkedList};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordCount {
+    pub word: String,
+    pub count: usize,
+}
+
+impl LinkedList<WordCount> {
+    /// Insert a word keeping the list sorted by count, most common first.
+    /// Words with the same count are in alphabetical order. Each insert
+    /// walks the list, so sort many words first and push_back them.
+    pub fn insert_by_count(&mut self, word: String, count: usize) {
+        self.insert_sorted_by(WordCount { word, count }, |a, b| {
+            b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_by_count() {
+        let mut list = LinkedList::new();
+        for (word, count) in [
+            ("pear", 2),
+            ("apple", 5),
+            ("fig", 2),
+            ("kiwi", 7),
+            ("date", 1),
+        ] {
+            list.insert_by_count(word.to_string(), count);
+        }
+        let words: Vec<_> = list.iter().map(|word| word.word.as_str()).collect();
+        assert_eq!(words, ["kiwi", "apple", "fig", "pear", "date"]);
+        assert_eq!(list.back().map(|word| word.count), Some(1));
+    }
+}
@@ -0,0 +1,763 @@
+/*
+An owned doubly-linked list.
+
+Every node is a heap allocation that the list owns through raw pointers,
+so a node can point both ways. All the pointer work happens in three
+places: link_between puts a chain of nodes between two neighbours,
+unlink takes one node out, and the iterators walk the pointers. Everything
+else, the cursor included, is built on those.
+
+A CursorMut points at one node, or at the "ghost" position between the
+back and the front, and can insert, remove and splice in O(1) there.
+Dropping a list pops its nodes one by one, so a long list does not
+overflow the stack the way a recursive Box<Node> chain does.
+*/
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+// Linked nodes taken out of a list, or a new node, first to last
+struct Chain<T> {
+    first: NonNull<Node<T>>,
+    last: NonNull<Node<T>>,
+    count: usize,
+}
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    // The list owns its nodes, for drop check and variance
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// The list owns its values like a Vec does
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: head points to a node owned by the list
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in front, and &mut self makes the access unique
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: tail points to a node owned by the list
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in back, and &mut self makes the access unique
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        // SAFETY: the new node is not in any list, head is a node of this one
+        unsafe { self.link_between(None, self.head, new_node(value)) }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        // SAFETY: the new node is not in any list, tail is a node of this one
+        unsafe { self.link_between(self.tail, None, new_node(value)) }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: head is a node of this list
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: tail is a node of this list
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// Move all items of `other` to the back of this list, O(1)
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let mut cursor = self.cursor_back_mut();
+        cursor.splice_after(std::mem::take(other));
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    /// A cursor at the front item, or at the ghost position when empty
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: if self.head.is_some() { Some(0) } else { None },
+            list: self,
+        }
+    }
+
+    /// A cursor at the back item, or at the ghost position when empty
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.checked_sub(1),
+            list: self,
+        }
+    }
+
+    /// Insert `value` before the first item that `compare` puts after it.
+    /// Keeps a list sorted by `compare` sorted; equal items stay in the
+    /// order they were inserted. O(n).
+    pub fn insert_sorted_by(&mut self, value: T, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        let mut cursor = self.cursor_front_mut();
+        while let Some(current) = cursor.current() {
+            if compare(&value, current) == Ordering::Less {
+                break;
+            }
+            cursor.move_next();
+        }
+        // At the ghost position this pushes to the back
+        cursor.insert_before(value);
+    }
+
+    pub fn insert_sorted(&mut self, value: T)
+    where
+        T: Ord,
+    {
+        self.insert_sorted_by(value, T::cmp);
+    }
+
+    /// Put a chain of nodes between prev and next, which must be
+    /// neighbours in this list (None for the ends).
+    ///
+    /// SAFETY: the chain must be linked from first to last, owned by no
+    /// list, and prev and next must be nodes of this list or None.
+    unsafe fn link_between(&mut self, prev: Link<T>, next: Link<T>, chain: Chain<T>) {
+        let Chain { first, last, count } = chain;
+        unsafe {
+            (*first.as_ptr()).prev = prev;
+            (*last.as_ptr()).next = next;
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(first),
+                None => self.head = Some(first),
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(last),
+                None => self.tail = Some(last),
+            }
+        }
+        self.len += count;
+    }
+
+    /// Take a node out of the list and return its value.
+    ///
+    /// SAFETY: node must be a node of this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        // SAFETY: the node was made by new_node and is unlinked from here on
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        unsafe {
+            match node.prev {
+                Some(prev) => (*prev.as_ptr()).next = node.next,
+                None => self.head = node.next,
+            }
+            match node.next {
+                Some(next) => (*next.as_ptr()).prev = node.prev,
+                None => self.tail = node.prev,
+            }
+        }
+        self.len -= 1;
+        node.value
+    }
+
+    /// The nodes of the list, leaving it empty
+    fn take_chain(&mut self) -> Option<Chain<T>> {
+        Some(Chain {
+            first: self.head.take()?,
+            last: self.tail.take()?,
+            count: std::mem::take(&mut self.len),
+        })
+    }
+}
+
+fn new_node<T>(value: T) -> Chain<T> {
+    let node = NonNull::from(Box::leak(Box::new(Node {
+        value,
+        prev: None,
+        next: None,
+    })));
+    Chain {
+        first: node,
+        last: node,
+        count: 1,
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // One node at a time, no recursion
+        self.clear();
+    }
+}
+
+/// A position in a LinkedList to edit it at: an item or the ghost
+/// position between the back and the front. Moving past either end goes
+/// to the ghost position, moving on from there wraps around.
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    current: Link<T>,
+    // None at the ghost position
+    index: Option<usize>,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// The index of the current item, None at the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: current is a node of the borrowed list
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            // SAFETY: current is a node of the borrowed list
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        // SAFETY: next is a node of the borrowed list
+        next.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            // SAFETY: current is a node of the borrowed list
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: prev is a node of the borrowed list
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => {
+                // SAFETY: current is a node of the borrowed list
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index = self.current.and_then(|_| self.index.map(|index| index + 1));
+            }
+            None => {
+                self.current = self.list.head;
+                self.index = self.current.map(|_| 0);
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => {
+                // SAFETY: current is a node of the borrowed list
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = self.current.and_then(|_| self.index.map(|index| index - 1));
+            }
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.checked_sub(1);
+            }
+        }
+    }
+
+    /// Insert an item after the current one; at the ghost position that
+    /// is the front of the list
+    pub fn insert_after(&mut self, value: T) {
+        let mut list = LinkedList::new();
+        list.push_back(value);
+        self.splice_after(list);
+    }
+
+    /// Insert an item before the current one; at the ghost position that
+    /// is the back of the list
+    pub fn insert_before(&mut self, value: T) {
+        let mut list = LinkedList::new();
+        list.push_back(value);
+        self.splice_before(list);
+    }
+
+    /// Move all items of `other` in after the current item, O(1)
+    pub fn splice_after(&mut self, mut other: LinkedList<T>) {
+        let Some(chain) = other.take_chain() else {
+            return;
+        };
+        let next = match self.current {
+            // SAFETY: current is a node of the borrowed list
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        // SAFETY: other gave up the chain, current and next are neighbours
+        unsafe { self.list.link_between(self.current, next, chain) }
+    }
+
+    /// Move all items of `other` in before the current item, O(1)
+    pub fn splice_before(&mut self, mut other: LinkedList<T>) {
+        let Some(chain) = other.take_chain() else {
+            return;
+        };
+        let count = chain.count;
+        let prev = match self.current {
+            // SAFETY: current is a node of the borrowed list
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: other gave up the chain, prev and current are neighbours
+        unsafe { self.list.link_between(prev, self.current, chain) }
+        if let Some(index) = &mut self.index {
+            *index += count;
+        }
+    }
+
+    /// Remove the current item and move to the next one, or to the ghost
+    /// position after the back
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: current is a node of the borrowed list
+        self.current = unsafe { (*node.as_ptr()).next };
+        if self.current.is_none() {
+            self.index = None;
+        }
+        // SAFETY: as above
+        Some(unsafe { self.list.unlink(node) })
+    }
+}
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list is borrowed for 'a, len keeps us inside it
+            let node = unsafe { &*node.as_ptr() };
+            self.head = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            self.len -= 1;
+            // SAFETY: as in next
+            let node = unsafe { &*node.as_ptr() };
+            self.tail = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list is borrowed mutably for 'a and every node
+            // is handed out once, len keeps the ends from meeting twice
+            let node = unsafe { &mut *node.as_ptr() };
+            self.head = node.next;
+            &mut node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            self.len -= 1;
+            // SAFETY: as in next
+            let node = unsafe { &mut *node.as_ptr() };
+            self.tail = node.prev;
+            &mut node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn list(values: &[i32]) -> LinkedList<i32> {
+        values.iter().copied().collect()
+    }
+
+    fn to_vec(list: &LinkedList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    // Counts its drops, to check that every value is dropped exactly once
+    struct Tracked(Rc<Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut list = LinkedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!((list.front(), list.back()), (Some(&1), Some(&3)));
+        *list.front_mut().unwrap() = 10;
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+        assert_eq!(list.back(), None);
+    }
+
+    #[test]
+    fn test_iterators_both_ways() {
+        let mut list = list(&[1, 2, 3, 4]);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [4, 3, 2, 1]);
+        let mut iter = list.iter();
+        assert_eq!((iter.next(), iter.next_back()), (Some(&1), Some(&4)));
+        assert_eq!((iter.next_back(), iter.next()), (Some(&3), Some(&2)));
+        assert_eq!((iter.next(), iter.next_back()), (None, None));
+
+        for value in &mut list {
+            *value *= 10;
+        }
+        let mut iter = list.iter_mut();
+        *iter.next_back().unwrap() += 1;
+        assert_eq!(iter.len(), 3);
+        assert_eq!(to_vec(&list), [10, 20, 30, 41]);
+
+        let mut owned = list.clone().into_iter();
+        assert_eq!((owned.next_back(), owned.next()), (Some(41), Some(10)));
+        assert_eq!(owned.collect::<Vec<_>>(), [20, 30]);
+        assert_eq!(format!("{list:?}"), "[10, 20, 30, 41]");
+    }
+
+    #[test]
+    fn test_cursor_moves_and_wraps() {
+        let mut list = list(&[1, 2, 3]);
+        let mut cursor = list.cursor_front_mut();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(0), Some(1))
+        );
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(cursor.peek_next().copied(), Some(1));
+        assert_eq!(cursor.peek_prev().copied(), Some(3));
+        cursor.move_prev();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(2), Some(3))
+        );
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(0), Some(1))
+        );
+
+        let mut empty = LinkedList::<i32>::new();
+        let mut cursor = empty.cursor_back_mut();
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+    }
+
+    #[test]
+    fn test_cursor_insert_and_remove() {
+        let mut list = list(&[1, 3]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_after(2);
+        cursor.insert_before(0);
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(1), Some(1))
+        );
+        assert_eq!(cursor.remove_current(), Some(1));
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(1), Some(2))
+        );
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        // At the ghost position: after is the front, before is the back
+        cursor.insert_after(-1);
+        cursor.insert_before(4);
+        assert_eq!(to_vec(&list), [-1, 0, 2, 4]);
+        assert_eq!(list.back(), Some(&4));
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn test_splice_and_append() {
+        let mut list = list(&[1, 5]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.splice_after(self::list(&[2, 3]));
+        cursor.move_next();
+        cursor.move_next();
+        cursor.splice_before(LinkedList::new());
+        cursor.splice_after(self::list(&[4]));
+        cursor.move_prev();
+        cursor.splice_before(self::list(&[-2, -1]));
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current().copied(), Some(2));
+        assert_eq!(to_vec(&list), [1, -2, -1, 2, 3, 4, 5]);
+
+        let mut other = self::list(&[6, 7]);
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.len(), 9);
+        assert_eq!(list.iter().next_back(), Some(&7));
+        let mut empty = LinkedList::new();
+        empty.append(&mut list);
+        assert_eq!(empty.len(), 9);
+        assert_eq!(empty.front(), Some(&1));
+    }
+
+    #[test]
+    fn test_insert_sorted() {
+        let mut list = LinkedList::new();
+        for value in [5, 1, 4, 1, 3] {
+            list.insert_sorted(value);
+        }
+        assert_eq!(to_vec(&list), [1, 1, 3, 4, 5]);
+
+        // Descending by the first field, ties in insertion order
+        let mut pairs = LinkedList::new();
+        for pair in [(2, 'a'), (3, 'b'), (2, 'c'), (1, 'd'), (3, 'e')] {
+            pairs.insert_sorted_by(pair, |a, b| b.0.cmp(&a.0));
+        }
+        let letters: String = pairs.iter().map(|pair| pair.1).collect();
+        assert_eq!(letters, "beacd");
+    }
+
+    #[test]
+    fn test_values_dropped_once() {
+        let drops = Rc::new(Cell::new(0));
+        let mut list: LinkedList<_> = (0..10).map(|_| Tracked(drops.clone())).collect();
+        drop(list.pop_front());
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        drop(cursor.remove_current());
+        assert_eq!(drops.get(), 2);
+        let mut iter = list.into_iter();
+        drop(iter.next_back());
+        drop(iter);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn test_drop_long_list() {
+        // Miri is slow, the point there is the pointer checks
+        let len = if cfg!(miri) { 1_000 } else { 1_000_000 };
+        let list: LinkedList<usize> = (0..len).collect();
+        assert_eq!(list.len(), len);
+        drop(list);
+    }
+}
@@ -2,50 +2,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, BufRead};
 
-#[derive(Debug)]
-struct Node {
-    word: String,
-    count: usize,
-    next: Option<Box<Node>>,
-}
-
-impl Node {
-    fn new(word: String, count: usize) -> Self {
-        Node {
-            word,
-            count,
-            next: None,
-        }
-    }
-}
-
-struct LinkedList {
-    head: Option<Box<Node>>,
-}
-
-impl LinkedList {
-    fn new() -> Self {
-        LinkedList { head: None }
-    }
-
-    fn insert(&mut self, word: String, count: usize) {
-        let mut new_node = Box::new(Node::new(word, count));
-        new_node.next = self.head.take();
-        self.head = Some(new_node);
-    }
-
-    fn to_vec(&self) -> Vec<(String, usize)> {
-        let mut result = Vec::new();
-        let mut current = &self.head;
-        
-        while let Some(node) = current {
-            result.push((node.word.clone(), node.count));
-            current = &node.next;
-        }
-        
-        result
-    }
-}
+use linked_list::{LinkedList, WordCount};
 
 fn main() -> io::Result<()> {
     // Read and count words
@@ -65,26 +22,26 @@ fn main() -> io::Result<()> {
         }
     }
 
-    // Create linked list
+    // 1) Sort by occurrences (descending) once, then build the linked list
+    //    in that order
+    let mut words: Vec<_> = word_counts.into_iter().collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
     let mut list = LinkedList::new();
-    for (word, count) in word_counts {
-        list.insert(word, count);
+    for (word, count) in words {
+        list.push_back(WordCount { word, count });
     }
-    // 1) Sort by occurrences (descending)
-    let mut words = list.to_vec();
-    words.sort_by(|a, b| b.1.cmp(&a.1));
 
-    // 2) Take the 10 most occurred
-    let mut top_10: Vec<_> = words.into_iter().take(10).collect();
+    // 2) Take the 10 most occurred from the front
+    let mut top_10: Vec<_> = list.into_iter().take(10).collect();
 
     // 3) Sort those 10 alphabetically
-    top_10.sort_by(|a, b| a.0.cmp(&b.0));
+    top_10.sort_by(|a, b| a.word.cmp(&b.word));
 
     // Print short list with occurrences
     println!("Top 10 most common words (alphabetically sorted):");
-    for (i, (word, count)) in top_10.iter().enumerate() {
-        println!("{}. {} - {} occurrences", i + 1, word, count);
+    for (i, word) in top_10.iter().enumerate() {
+        println!("{}. {} - {} occurrences", i + 1, word.word, word.count);
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,312 @@
+/*
+The fruit catalog and its file formats.
+
+The format follows the file extension:
+
+TOML  [[fruit]] tables, see fruits.toml
+JSON  an array of fruit objects with the same fields
+CSV   name,seasons,calories,allergens,price,stock with the seasons and
+      allergens separated by ';'
+
+In every format a fruit without seasons is in season all year and one
+without stock has one serving, as with Fruit::new.
+
+Fruits keep the order of the file, so the same file always gives the same
+catalog.
+*/
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::fruit::{Fruit, Season, all_seasons, one_serving};
+use crate::salad::{Constraints, Salad, SaladError};
+
+// The catalog built into the crate
+const BUILTIN: &str = include_str!("../fruits.toml");
+
+/// Error loading or saving a catalog
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(io::Error),
+    Toml(String),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    /// A file extension other than toml, json or csv
+    UnknownFormat(String),
+    /// A CSV field that does not parse, with its line
+    Invalid {
+        line: u64,
+        reason: String,
+    },
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogError::Io(e) => write!(f, "{}", e),
+            CatalogError::Toml(e) => write!(f, "invalid TOML catalog: {}", e),
+            CatalogError::Json(e) => write!(f, "invalid JSON catalog: {}", e),
+            CatalogError::Csv(e) => write!(f, "invalid CSV catalog: {}", e),
+            CatalogError::UnknownFormat(path) => write!(
+                f,
+                "unknown catalog format '{}'. Use a .toml, .json or .csv file",
+                path
+            ),
+            CatalogError::Invalid { line, reason } => {
+                write!(f, "invalid catalog entry on line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+impl From<io::Error> for CatalogError {
+    fn from(e: io::Error) -> Self {
+        CatalogError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for CatalogError {
+    fn from(e: serde_json::Error) -> Self {
+        CatalogError::Json(e)
+    }
+}
+
+impl From<csv::Error> for CatalogError {
+    fn from(e: csv::Error) -> Self {
+        CatalogError::Csv(e)
+    }
+}
+
+// TOML needs a table at the top
+#[derive(Serialize, Deserialize)]
+struct TomlCatalog {
+    #[serde(default)]
+    fruit: Vec<Fruit>,
+}
+
+// One CSV row, lists joined with ';'. Empty or missing cells take the
+// same defaults as a TOML or JSON entry without the field.
+#[derive(Serialize, Deserialize)]
+struct CsvFruit {
+    name: String,
+    #[serde(default)]
+    seasons: String,
+    #[serde(default)]
+    calories: Option<u32>,
+    #[serde(default)]
+    allergens: String,
+    #[serde(default)]
+    price: Option<f64>,
+    #[serde(default)]
+    stock: Option<u32>,
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// Fruits available for salads, in a fixed order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Catalog {
+    fruits: Vec<Fruit>,
+}
+
+impl Catalog {
+    pub fn new(fruits: Vec<Fruit>) -> Self {
+        Catalog { fruits }
+    }
+
+    /// The catalog shipped with the crate (fruits.toml)
+    pub fn builtin() -> Self {
+        Self::from_toml(BUILTIN).expect("built-in catalog is valid")
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, CatalogError> {
+        let catalog: TomlCatalog =
+            toml::from_str(text).map_err(|e| CatalogError::Toml(e.to_string()))?;
+        Ok(Catalog::new(catalog.fruit))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(&TomlCatalog {
+            fruit: self.fruits.clone(),
+        })
+        .expect("catalog serializes to TOML")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, CatalogError> {
+        Ok(Catalog::new(serde_json::from_str(text)?))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.fruits).expect("catalog serializes to JSON")
+    }
+
+    pub fn from_csv<R: io::Read>(reader: R) -> Result<Self, CatalogError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let mut fruits = Vec::new();
+        for row in reader.deserialize() {
+            let row: CsvFruit = row?;
+            let mut seasons = split_list(&row.seasons)
+                .map(|s| s.parse::<Season>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|reason| CatalogError::Invalid {
+                    // Header is line 1
+                    line: fruits.len() as u64 + 2,
+                    reason,
+                })?;
+            if seasons.is_empty() {
+                seasons = all_seasons();
+            }
+            fruits.push(Fruit {
+                name: row.name,
+                seasons,
+                calories: row.calories.unwrap_or_default(),
+                allergens: split_list(&row.allergens).map(String::from).collect(),
+                price: row.price.unwrap_or_default(),
+                stock: row.stock.unwrap_or_else(one_serving),
+            });
+        }
+        Ok(Catalog::new(fruits))
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for fruit in &self.fruits {
+            let seasons: Vec<&str> = fruit.seasons.iter().map(|s| s.name()).collect();
+            writer
+                .serialize(CsvFruit {
+                    name: fruit.name.clone(),
+                    seasons: seasons.join(";"),
+                    calories: Some(fruit.calories),
+                    allergens: fruit.allergens.join(";"),
+                    price: Some(fruit.price),
+                    stock: Some(fruit.stock),
+                })
+                .expect("fruit serializes to CSV");
+        }
+        String::from_utf8(writer.into_inner().expect("CSV flushes to memory"))
+            .expect("CSV is UTF-8")
+    }
+
+    /// Load a catalog, the format chosen by the file extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        let path = path.as_ref();
+        match extension(path)?.as_str() {
+            "toml" => Self::from_toml(&fs::read_to_string(path)?),
+            "json" => Self::from_json(&fs::read_to_string(path)?),
+            _ => Self::from_csv(fs::File::open(path)?),
+        }
+    }
+
+    /// Save a catalog, the format chosen by the file extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CatalogError> {
+        let path = path.as_ref();
+        let text = match extension(path)?.as_str() {
+            "toml" => self.to_toml(),
+            "json" => self.to_json() + "\n",
+            _ => self.to_csv(),
+        };
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn fruits(&self) -> &[Fruit] {
+        &self.fruits
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Fruit> {
+        self.fruits.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fruits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fruits.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.fruits.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    /// Case-insensitive lookup by name
+    pub fn get(&self, name: &str) -> Option<&Fruit> {
+        self.fruits
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The named fruits in the given order. Names missing from the catalog
+    /// become plain `Fruit::new` entries.
+    pub fn only(&self, names: &[String]) -> Catalog {
+        Catalog::new(
+            names
+                .iter()
+                .map(|name| {
+                    self.get(name)
+                        .cloned()
+                        .unwrap_or_else(|| Fruit::new(name.trim()))
+                })
+                .collect(),
+        )
+    }
+
+    /// The same catalog sorted by name
+    pub fn sorted_by_name(&self) -> Catalog {
+        let mut fruits = self.fruits.clone();
+        fruits.sort_by(|a, b| a.name.cmp(&b.name));
+        Catalog::new(fruits)
+    }
+
+    /// Build a salad picking fruits in catalog order
+    pub fn build_salad(&self, constraints: &Constraints) -> Result<Salad, SaladError> {
+        constraints.select(self.fruits.iter())
+    }
+
+    /// Build a salad picking fruits in random order
+    pub fn build_salad_with<R: Rng + ?Sized>(
+        &self,
+        constraints: &Constraints,
+        rng: &mut R,
+    ) -> Result<Salad, SaladError> {
+        let mut order: Vec<&Fruit> = self.fruits.iter().collect();
+        order.shuffle(rng);
+        constraints.select(order)
+    }
+}
+
+impl<'a> IntoIterator for &'a Catalog {
+    type Item = &'a Fruit;
+    type IntoIter = std::slice::Iter<'a, Fruit>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fruits.iter()
+    }
+}
+
+// Lowercase extension of a catalog path, toml/json/csv only
+fn extension(path: &Path) -> Result<String, CatalogError> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    match ext.as_str() {
+        "toml" | "json" | "csv" => Ok(ext),
+        _ => Err(CatalogError::UnknownFormat(path.display().to_string())),
+    }
+}
@@ -0,0 +1,125 @@
+/*
+A fruit of the catalog and the seasons it is harvested in.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [
+        Season::Spring,
+        Season::Summer,
+        Season::Autumn,
+        Season::Winter,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Autumn => "autumn",
+            Season::Winter => "winter",
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Season {
+    type Err = String;
+
+    /// Accepts "fall" for autumn, any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if s == "fall" {
+            return Ok(Season::Autumn);
+        }
+        Season::ALL
+            .into_iter()
+            .find(|season| season.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown season '{}'. Choose from: spring, summer, autumn, winter",
+                    s
+                )
+            })
+    }
+}
+
+/// A fruit with its nutrition, price and stock. Calories and price are per
+/// serving.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fruit {
+    pub name: String,
+    #[serde(default = "all_seasons")]
+    pub seasons: Vec<Season>,
+    #[serde(default)]
+    pub calories: u32,
+    #[serde(default)]
+    pub allergens: Vec<String>,
+    #[serde(default)]
+    pub price: f64,
+    #[serde(default = "one_serving")]
+    pub stock: u32,
+}
+
+// Defaults of a file entry without seasons or stock, the same as Fruit::new
+// and used by the CSV reader too
+pub(crate) fn all_seasons() -> Vec<Season> {
+    Season::ALL.to_vec()
+}
+
+pub(crate) fn one_serving() -> u32 {
+    1
+}
+
+impl Fruit {
+    /// A fruit known by name only: in season all year, no calories,
+    /// allergens or price, and one serving in stock
+    pub fn new(name: &str) -> Self {
+        Fruit {
+            name: name.to_string(),
+            seasons: all_seasons(),
+            calories: 0,
+            allergens: Vec::new(),
+            price: 0.0,
+            stock: one_serving(),
+        }
+    }
+
+    pub fn in_season(&self, season: Season) -> bool {
+        self.seasons.contains(&season)
+    }
+
+    /// Case-insensitive allergen check
+    pub fn has_allergen(&self, allergen: &str) -> bool {
+        self.allergens
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(allergen.trim()))
+    }
+
+    pub fn in_stock(&self) -> bool {
+        self.stock > 0
+    }
+}
+
+impl fmt::Display for Fruit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
@@ -0,0 +1,203 @@
+/*
+A shared fruit catalog for the fruit salad crates.
+
+Fruits carry seasons, calories, allergens, price and stock and are loaded
+from TOML, JSON or CSV files. Salads are built from a catalog under
+constraints (count, calorie limit, excluded allergens, season, budget).
+Without a random generator the fruits are picked in catalog order, so the
+same catalog and constraints always give the same salad.
+*/
+
+pub mod catalog;
+pub mod fruit;
+pub mod salad;
+
+pub use catalog::{Catalog, CatalogError};
+pub use fruit::{Fruit, Season};
+pub use salad::{Constraints, Salad, SaladError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_builtin_catalog() {
+        let catalog = Catalog::builtin();
+        assert_eq!(catalog.len(), 10);
+        assert_eq!(catalog.names()[0], "Arbutus");
+        let cherry = catalog.get("cherry").unwrap();
+        assert!(cherry.in_season(Season::Summer));
+        assert!(cherry.has_allergen("Stone Fruit"));
+        assert!(catalog.get("Durian").is_none());
+    }
+
+    #[test]
+    fn test_season_from_str() {
+        assert_eq!("Fall".parse::<Season>(), Ok(Season::Autumn));
+        assert_eq!(" winter ".parse::<Season>(), Ok(Season::Winter));
+        assert!("monsoon".parse::<Season>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip_formats() {
+        let catalog = Catalog::builtin();
+        assert_eq!(Catalog::from_toml(&catalog.to_toml()).unwrap(), catalog);
+        assert_eq!(Catalog::from_json(&catalog.to_json()).unwrap(), catalog);
+        assert_eq!(
+            Catalog::from_csv(catalog.to_csv().as_bytes()).unwrap(),
+            catalog
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = std::env::temp_dir().join(format!("fruit-catalog-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let catalog = Catalog::builtin();
+        for ext in ["toml", "json", "csv"] {
+            let path = dir.join(format!("fruits.{}", ext));
+            catalog.save(&path).unwrap();
+            assert_eq!(Catalog::load(&path).unwrap(), catalog);
+        }
+        assert!(matches!(
+            Catalog::load(dir.join("fruits.txt")),
+            Err(CatalogError::UnknownFormat(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_minimal_record_defaults() {
+        let from_toml = Catalog::from_toml("[[fruit]]\nname = \"Kiwi\"\n").unwrap();
+        let from_json = Catalog::from_json(r#"[{"name": "Kiwi"}]"#).unwrap();
+        let from_csv = Catalog::from_csv("name\nKiwi\n".as_bytes()).unwrap();
+        let empty_cells = "name,seasons,calories,allergens,price,stock\nKiwi,,,,,\n";
+        let from_csv_cells = Catalog::from_csv(empty_cells.as_bytes()).unwrap();
+        for catalog in [from_toml, from_json, from_csv, from_csv_cells] {
+            let kiwi = catalog.get("Kiwi").unwrap();
+            assert_eq!(*kiwi, Fruit::new("Kiwi"));
+            assert!(kiwi.in_stock());
+            assert!(kiwi.in_season(Season::Winter));
+        }
+    }
+
+    #[test]
+    fn test_csv_invalid_season() {
+        let csv = "name,seasons,calories,allergens,price,stock\n\
+                   Kiwi,winter,61,,0.4,3\n\
+                   Mango,monsoon,60,,1.2,2\n";
+        match Catalog::from_csv(csv.as_bytes()) {
+            Err(CatalogError::Invalid { line, .. }) => assert_eq!(line, 3),
+            other => panic!("expected invalid entry, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_salad_is_deterministic() {
+        let catalog = Catalog::builtin();
+        let salad = catalog.build_salad(&Constraints::new().count(3)).unwrap();
+        assert_eq!(
+            salad.names(),
+            ["Arbutus", "Loquat", "Strawberry Tree Berry"]
+        );
+        assert_eq!(salad.total_calories(), 212);
+
+        let constraints = Constraints::new().count(4);
+        let a = catalog
+            .build_salad_with(&constraints, &mut StdRng::seed_from_u64(7))
+            .unwrap();
+        let b = catalog
+            .build_salad_with(&constraints, &mut StdRng::seed_from_u64(7))
+            .unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_constraints() {
+        let catalog = Catalog::builtin();
+
+        let summer = catalog
+            .build_salad(&Constraints::new().in_season(Season::Summer))
+            .unwrap();
+        assert_eq!(summer.names(), ["Fig", "Cherry", "Pear", "Peach", "Apple"]);
+
+        let no_pollen = catalog
+            .build_salad(&Constraints::new().exclude_allergen("birch pollen"))
+            .unwrap();
+        assert!(no_pollen.iter().all(|f| !f.has_allergen("birch pollen")));
+        assert_eq!(no_pollen.len(), 7);
+
+        let light = catalog
+            .build_salad(&Constraints::new().max_calories(150))
+            .unwrap();
+        assert!(light.total_calories() <= 150);
+        // Arbutus (80) fits, Loquat (47) fits, the rest are too heavy
+        assert_eq!(light.names(), ["Arbutus", "Loquat"]);
+
+        let cheap = catalog
+            .build_salad(&Constraints::new().budget(2.0))
+            .unwrap();
+        assert!(cheap.total_price() <= 2.0);
+        assert_eq!(cheap.names(), ["Arbutus"]);
+    }
+
+    #[test]
+    fn test_out_of_stock_is_skipped() {
+        let mut fruits = vec![Fruit::new("Kiwi"), Fruit::new("Lime")];
+        fruits[0].stock = 0;
+        let catalog = Catalog::new(fruits);
+        let salad = catalog.build_salad(&Constraints::new()).unwrap();
+        assert_eq!(salad.names(), ["Lime"]);
+    }
+
+    #[test]
+    fn test_not_enough_fruits() {
+        let catalog = Catalog::builtin();
+        let err = catalog
+            .build_salad(&Constraints::new().count(11))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SaladError::NotEnoughFruits {
+                requested: 11,
+                available: 10,
+                allowed: 10
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Requested 11 fruits but only 10 are available."
+        );
+
+        let err = catalog
+            .build_salad(&Constraints::new().count(5).max_calories(100))
+            .unwrap_err();
+        assert!(err.to_string().contains("calorie and budget limits"));
+    }
+
+    #[test]
+    fn test_only_keeps_order_and_unknown_names() {
+        let catalog = Catalog::builtin();
+        let only = catalog.only(&["pear".to_string(), "Durian".to_string()]);
+        assert_eq!(only.names(), ["Pear", "Durian"]);
+        assert_eq!(only.get("pear").unwrap().calories, 57);
+        assert_eq!(only.get("durian").unwrap(), &Fruit::new("Durian"));
+    }
+
+    #[test]
+    fn test_salad_summary() {
+        let catalog = Catalog::builtin();
+        let mut salad = catalog
+            .only(&["Pear".to_string(), "Cherry".to_string()])
+            .build_salad(&Constraints::new())
+            .unwrap();
+        salad.sort_by_name();
+        assert_eq!(salad.allergens(), ["birch pollen", "stone fruit"]);
+        assert_eq!(
+            salad.to_string(),
+            "Cherry\nPear\n2 fruits, 120 kcal, 3.20 EUR, allergens: birch pollen, stone fruit"
+        );
+    }
+}
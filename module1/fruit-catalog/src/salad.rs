@@ -0,0 +1,236 @@
+/*
+Salads and the constraints they are built with.
+
+Constraints::select walks the fruits in the order it is given and keeps
+every fruit that passes the filters (in stock, in season, no excluded
+allergen) as long as the calorie and price limits still hold, until the
+salad has the requested number of fruits. A fruit that would break a limit
+is skipped and a later, lighter or cheaper one may still fit.
+*/
+
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::fruit::{Fruit, Season};
+
+/// Limits for building a salad. The default allows everything.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Constraints {
+    /// Number of fruits, 0 for as many as fit
+    pub count: usize,
+    /// Upper limit of the summed calories
+    pub max_calories: Option<u32>,
+    /// Allergens no fruit may contain, compared case-insensitively
+    pub exclude_allergens: Vec<String>,
+    /// Only fruits in season
+    pub season: Option<Season>,
+    /// Upper limit of the summed price
+    pub budget: Option<f64>,
+}
+
+impl Constraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn max_calories(mut self, calories: u32) -> Self {
+        self.max_calories = Some(calories);
+        self
+    }
+
+    pub fn exclude_allergen(mut self, allergen: &str) -> Self {
+        self.exclude_allergens.push(allergen.to_string());
+        self
+    }
+
+    pub fn in_season(mut self, season: Season) -> Self {
+        self.season = Some(season);
+        self
+    }
+
+    pub fn budget(mut self, budget: f64) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Whether a fruit may go into a salad at all, regardless of the limits
+    pub fn allows(&self, fruit: &Fruit) -> bool {
+        fruit.in_stock()
+            && self.season.is_none_or(|season| fruit.in_season(season))
+            && !self
+                .exclude_allergens
+                .iter()
+                .any(|allergen| fruit.has_allergen(allergen))
+    }
+
+    /// Pick fruits in the given order, see the module comment
+    pub fn select<'a>(
+        &self,
+        fruits: impl IntoIterator<Item = &'a Fruit>,
+    ) -> Result<Salad, SaladError> {
+        let mut salad = Salad::default();
+        let mut allowed = 0;
+        for fruit in fruits {
+            if self.count > 0 && salad.len() == self.count {
+                break;
+            }
+            if !self.allows(fruit) {
+                continue;
+            }
+            allowed += 1;
+            let calories = salad.total_calories() + fruit.calories;
+            let price = salad.total_price() + fruit.price;
+            if self.max_calories.is_some_and(|max| calories > max)
+                || self.budget.is_some_and(|budget| price > budget + 1e-9)
+            {
+                continue;
+            }
+            salad.fruits.push(fruit.clone());
+        }
+
+        if self.count > salad.len() {
+            return Err(SaladError::NotEnoughFruits {
+                requested: self.count,
+                available: salad.len(),
+                allowed,
+            });
+        }
+        Ok(salad)
+    }
+}
+
+/// Why a salad could not be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaladError {
+    /// Fewer fruits fit the constraints than requested. `allowed` counts
+    /// the fruits that passed the filters before the limits were applied.
+    NotEnoughFruits {
+        requested: usize,
+        available: usize,
+        allowed: usize,
+    },
+}
+
+impl fmt::Display for SaladError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaladError::NotEnoughFruits {
+                requested,
+                available,
+                allowed,
+            } if available == allowed => write!(
+                f,
+                "Requested {} fruits but only {} are available.",
+                requested, available
+            ),
+            SaladError::NotEnoughFruits {
+                requested,
+                available,
+                ..
+            } => write!(
+                f,
+                "Requested {} fruits but only {} fit the calorie and budget limits.",
+                requested, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaladError {}
+
+/// A fruit salad: the chosen fruits in serving order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Salad {
+    fruits: Vec<Fruit>,
+}
+
+impl Salad {
+    pub fn new(fruits: Vec<Fruit>) -> Self {
+        Salad { fruits }
+    }
+
+    pub fn fruits(&self) -> &[Fruit] {
+        &self.fruits
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.fruits.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Fruit> {
+        self.fruits.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fruits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fruits.is_empty()
+    }
+
+    pub fn total_calories(&self) -> u32 {
+        self.fruits.iter().map(|f| f.calories).sum()
+    }
+
+    pub fn total_price(&self) -> f64 {
+        self.fruits.iter().map(|f| f.price).sum()
+    }
+
+    /// Allergens of all fruits, sorted, without duplicates
+    pub fn allergens(&self) -> Vec<String> {
+        let mut allergens: Vec<String> = self
+            .fruits
+            .iter()
+            .flat_map(|f| f.allergens.iter().map(|a| a.to_lowercase()))
+            .collect();
+        allergens.sort();
+        allergens.dedup();
+        allergens
+    }
+
+    pub fn sort_by_name(&mut self) {
+        self.fruits.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.fruits.shuffle(rng);
+    }
+}
+
+impl fmt::Display for Salad {
+    /// One fruit per line followed by the totals
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for fruit in &self.fruits {
+            writeln!(f, "{}", fruit.name)?;
+        }
+        write!(
+            f,
+            "{} fruits, {} kcal, {:.2} EUR",
+            self.len(),
+            self.total_calories(),
+            self.total_price()
+        )?;
+        let allergens = self.allergens();
+        if !allergens.is_empty() {
+            write!(f, ", allergens: {}", allergens.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Salad {
+    type Item = &'a Fruit;
+    type IntoIter = std::slice::Iter<'a, Fruit>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fruits.iter()
+    }
+}
@@ -1,62 +1,95 @@
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use fruit_catalog::{Catalog, Constraints, Salad, SaladError};
+use rand::Rng;
 
-/// Creates a fruit salad by selecting a specified number of fruits from either a predefined list or a custom list.
+pub use fruit_catalog;
+
+/// Creates a fruit salad by selecting fruits from a catalog under the given constraints.
 ///
 /// # Parameters
-/// - `num_fruits`: The number of fruits to include in the salad. If set to 0, all available fruits are used.
-/// - `custom_fruits`: An optional slice of custom fruit names. If empty, a predefined list is used.
-/// - `alphabetical`: If true, the selected fruits are sorted alphabetically; otherwise, they are shuffled randomly.
+/// - `catalog`: The fruits to choose from, e.g. `Catalog::builtin()` or a catalog file.
+/// - `custom_fruits`: An optional slice of fruit names. If not empty, only these fruits are used,
+///   with their catalog attributes when the catalog knows them.
+/// - `constraints`: Number of fruits (0 = as many as fit), calorie limit, excluded allergens,
+///   season and budget.
+/// - `alphabetical`: If true, fruits are picked in alphabetical order, so the same catalog and
+///   constraints always give the same salad; otherwise, they are picked in random order.
+/// - `rng`: The random number generator for the order. A seeded generator always gives the
+///   same salad.
 ///
 /// # Returns
-/// Returns `Ok(Vec<String>)` containing the selected fruits, or `Err(String)` if the requested number exceeds available fruits.
-pub fn create_fruit_salad(
-    num_fruits: usize,
+/// Returns `Ok(Salad)` with the selected fruits, or `Err(SaladError)` if fewer fruits than
+/// requested fit the constraints.
+pub fn create_fruit_salad<R: Rng + ?Sized>(
+    catalog: &Catalog,
     custom_fruits: &[String],
+    constraints: &Constraints,
     alphabetical: bool,
-) -> Result<Vec<String>, String> {
-    // List of predefined fruits
-    let fruits = vec![
-        "Arbutus".to_string(),
-        "Loquat".to_string(),
-        "Strawberry Tree Berry".to_string(),
-        "Pomegranate".to_string(),
-        "Fig".to_string(),
-        "Cherry".to_string(),
-        "Orange".to_string(),
-        "Pear".to_string(),
-        "Peach".to_string(),
-        "Apple".to_string(),
-    ];
-
-    let fruits = if !custom_fruits.is_empty() {
-        custom_fruits.to_vec()
+    rng: &mut R,
+) -> Result<Salad, SaladError> {
+    let catalog = if !custom_fruits.is_empty() {
+        catalog.only(custom_fruits)
     } else {
-        fruits
+        catalog.clone()
     };
 
-    // Validate input
-    if num_fruits > fruits.len() {
-        return Err("Requested number of fruits exceeds available fruits.".to_string());
+    if alphabetical {
+        catalog.sorted_by_name().build_salad(constraints)
+    } else {
+        catalog.build_salad_with(constraints, rng)
     }
+}
 
-    let num_fruits = if num_fruits == 0 {
-        fruits.len()
-    } else {
-        num_fruits
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
-    // Sort or shuffle the fruits based on the 'alphabetical' flag.
-    let fruits = if alphabetical {
-        let mut sorted_fruits = fruits.clone();
-        sorted_fruits.sort();
-        sorted_fruits
-    } else {
-        let mut rng = thread_rng();
-        let mut fruits = fruits;
-        fruits.shuffle(&mut rng);
-        fruits
-    };
+    fn salad(seed: u64, count: usize) -> Vec<String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let constraints = Constraints::new().count(count);
+        create_fruit_salad(&Catalog::builtin(), &[], &constraints, false, &mut rng)
+            .unwrap()
+            .names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_salad() {
+        assert_eq!(salad(42, 4), ["Pear", "Pomegranate", "Apple", "Cherry"]);
+        assert_eq!(salad(42, 4), salad(42, 4));
+        assert_ne!(salad(42, 10), salad(7, 10));
+    }
 
-    Ok(fruits.into_iter().take(num_fruits).collect())
+    #[test]
+    fn test_alphabetical_ignores_seed() {
+        let constraints = Constraints::new().count(3);
+        for seed in [1, 2] {
+            let salad = create_fruit_salad(
+                &Catalog::builtin(),
+                &[],
+                &constraints,
+                true,
+                &mut StdRng::seed_from_u64(seed),
+            )
+            .unwrap();
+            assert_eq!(salad.names(), ["Apple", "Arbutus", "Cherry"]);
+        }
+    }
+
+    #[test]
+    fn test_custom_fruits_with_seed() {
+        let fruits = vec!["Kiwi".to_string(), "Lime".to_string(), "Fig".to_string()];
+        let salad = create_fruit_salad(
+            &Catalog::builtin(),
+            &fruits,
+            &Constraints::new(),
+            false,
+            &mut StdRng::seed_from_u64(3),
+        )
+        .unwrap();
+        assert_eq!(salad.names(), ["Lime", "Fig", "Kiwi"]);
+    }
 }
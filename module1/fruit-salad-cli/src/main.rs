@@ -1,5 +1,10 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 use fruit_salad_cli::create_fruit_salad;
+use fruit_salad_cli::fruit_catalog::{Catalog, Constraints, Season};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
 #[derive(Parser)]
 #[clap(
@@ -31,6 +36,30 @@ struct Opts {
         required = false
     )]
     alphabetical: bool,
+
+    /// Fruit catalog file (.toml, .json or .csv). Defaults to the built-in catalog.
+    #[clap(short, long, value_name = "FILE")]
+    catalog: Option<PathBuf>,
+
+    /// Maximum calories of the whole salad
+    #[clap(long, value_name = "KCAL")]
+    max_calories: Option<u32>,
+
+    /// Leave out fruits with this allergen, can be repeated
+    #[clap(short = 'x', long, value_name = "ALLERGEN")]
+    exclude_allergen: Vec<String>,
+
+    /// Only use fruits in season: spring, summer, autumn or winter
+    #[clap(short, long, value_name = "SEASON")]
+    season: Option<Season>,
+
+    /// Maximum price of the whole salad in euros
+    #[clap(short, long, value_name = "EUROS")]
+    budget: Option<f64>,
+
+    /// Seed for the random order. The same seed always gives the same salad.
+    #[clap(long, value_name = "SEED")]
+    seed: Option<u64>,
 }
 
 fn main() {
@@ -43,8 +72,35 @@ fn main() {
     //    they want in their salad as command-line arguments.
     //    --> if fruits were entered use those if not then use default fruits.
 
+    let catalog = match &opts.catalog {
+        Some(path) => Catalog::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }),
+        None => Catalog::builtin(),
+    };
+
+    let constraints = Constraints {
+        count: num_fruits,
+        max_calories: opts.max_calories,
+        exclude_allergens: opts.exclude_allergen,
+        season: opts.season,
+        budget: opts.budget,
+    };
+
+    let mut rng = match opts.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
     // Create the fruit salad
-    let fruit_salad = create_fruit_salad(num_fruits, &opts.fruits, opts.alphabetical);
+    let fruit_salad = create_fruit_salad(
+        &catalog,
+        &opts.fruits,
+        &constraints,
+        opts.alphabetical,
+        &mut rng,
+    );
 
     if fruit_salad.is_err() {
         eprintln!("Error: {}", fruit_salad.err().unwrap());
@@ -57,6 +113,16 @@ fn main() {
     // Print the fruit salad in human readable format with a count of fruits used
     println!(
         "Created Fruit salad with {} fruits: {:?}",
-        num_fruits, fruit_salad
+        num_fruits,
+        fruit_salad.names()
     );
+    println!(
+        "Calories: {} kcal, price: {:.2} EUR",
+        fruit_salad.total_calories(),
+        fruit_salad.total_price()
+    );
+    let allergens = fruit_salad.allergens();
+    if !allergens.is_empty() {
+        println!("Allergens: {}", allergens.join(", "));
+    }
 }
@@ -0,0 +1,67 @@
+/*
+The LinkedList salad as a bowl for the interactive shell.
+
+Inserting and removing in the middle split the list at the position and
+append the tail again, which only relinks nodes. Shuffling and sorting go
+through a Vec like the rest of the program.
+*/
+
+use std::collections::LinkedList;
+
+use fruit_salad_repl::Bowl;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+#[derive(Clone, Debug, Default)]
+pub struct LinkedListBowl(pub LinkedList<String>);
+
+impl Bowl for LinkedListBowl {
+    fn insert(&mut self, position: usize, fruit: String) {
+        let mut tail = self.0.split_off(position);
+        self.0.push_back(fruit);
+        self.0.append(&mut tail);
+    }
+
+    fn remove(&mut self, position: usize) -> String {
+        let mut tail = self.0.split_off(position);
+        let fruit = tail.pop_front().expect("position is inside the list");
+        self.0.append(&mut tail);
+        fruit
+    }
+
+    fn push_front(&mut self, fruit: String) {
+        self.0.push_front(fruit);
+    }
+
+    fn push_back(&mut self, fruit: String) {
+        self.0.push_back(fruit);
+    }
+
+    fn pop_front(&mut self) -> Option<String> {
+        self.0.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<String> {
+        self.0.pop_back()
+    }
+
+    fn shuffle(&mut self, rng: &mut StdRng) {
+        let mut fruit: Vec<_> = std::mem::take(&mut self.0).into_iter().collect();
+        fruit.shuffle(rng);
+        self.0 = fruit.into_iter().collect();
+    }
+
+    fn sort(&mut self) {
+        let mut fruit: Vec<_> = std::mem::take(&mut self.0).into_iter().collect();
+        fruit.sort();
+        self.0 = fruit.into_iter().collect();
+    }
+
+    fn fruits(&self) -> Vec<String> {
+        self.0.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
@@ -15,10 +15,14 @@ A great example of when to use a LinkedList is when you need to insert or remove
 from the middle of the list.
 */
 
+mod bowl;
+
+use bowl::LinkedListBowl;
 use clap::Parser; // clap is a command line argument parser for Rust
 use rand::seq::SliceRandom; // rand is a random number generation library in Rust
 use rand::thread_rng;
 use std::collections::LinkedList;
+use std::path::PathBuf;
 
 // 1. Add command line option to give fruit and its position
 #[derive(clap::Parser, Debug)]
@@ -47,10 +51,47 @@ struct Args {
         default_value_t = -1,
     )]
     remove_position: isize,
+
+    /// Edit the salad in an interactive shell
+    #[clap(long)]
+    repl: bool,
+
+    /// Run the shell commands of a file instead of reading them interactively
+    #[clap(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// Seed for the shuffle command of the shell
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+// 4. Interactive shell on the salad before shuffling, see fruit-salad-repl for the commands
+fn run_repl(args: &Args) {
+    let bowl = LinkedListBowl(
+        fruit_salad_repl::STARTING_SALAD
+            .iter()
+            .map(|f| f.to_string())
+            .collect(),
+    );
+    match fruit_salad_repl::run_session(bowl, args.script.as_deref(), args.seed) {
+        Ok((_, 0)) => {}
+        Ok((_, errors)) => {
+            eprintln!("{} commands failed", errors);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn main() {
     let _args = Args::parse();
+    if _args.repl || _args.script.is_some() {
+        run_repl(&_args);
+        return;
+    }
 
     let mut fruit: LinkedList<String> = LinkedList::new();
     fruit.push_back("Arbutus".to_string());
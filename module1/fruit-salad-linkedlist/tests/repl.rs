@@ -0,0 +1,50 @@
+use std::process::Command;
+
+// Run the shell on a script and return stdout and whether it succeeded
+fn run_script(name: &str, script: &str) -> (String, bool) {
+    let path = std::env::temp_dir().join(format!("linkedlist-{}-{}.txt", name, std::process::id()));
+    std::fs::write(&path, script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_fruit-salad-linkedlist"))
+        .arg("--script")
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.success(),
+    )
+}
+
+// The bowl splits the list at the position and appends the tail again,
+// so insert and remove at the front, inside and at the back
+#[test]
+fn test_script_splits_and_joins_the_list() {
+    let (out, ok) = run_script(
+        "split",
+        "add 6 Kiwi\nadd 0 Lime\nadd 4 Pear\nremove 8\nremove 0\nremove 3\nshow\n",
+    );
+    assert!(ok);
+    assert_eq!(
+        out,
+        "Added Kiwi at 6\n\
+         Added Lime at 0\n\
+         Added Pear at 4\n\
+         Removed Kiwi at 8\n\
+         Removed Lime at 0\n\
+         Removed Pear at 3\n\
+         Fruit Salad: Pomegranate, Arbutus, Loquat, Strawberry Tree Berry, Fig, Cherry\n"
+    );
+}
+
+#[test]
+fn test_script_undoes_a_split_and_fails_past_the_end() {
+    let (out, ok) = run_script("past-end", "add 3 Kiwi\nundo\nadd 7 Lime\nremove 6\nshow\n");
+    assert!(!ok);
+    assert!(out.contains("Undid 'add 3 Kiwi'\n"));
+    assert!(out.contains("Error: position 7 is out of bounds, the salad has 6 fruits\n"));
+    assert!(out.contains("Error: position 6 is out of bounds, the salad has 6 fruits\n"));
+    assert!(out.ends_with(
+        "Fruit Salad: Pomegranate, Arbutus, Loquat, Strawberry Tree Berry, Fig, Cherry\n"
+    ));
+}
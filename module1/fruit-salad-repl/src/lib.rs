@@ -0,0 +1,632 @@
+/*
+An interactive shell that edits a fruit salad in place.
+
+The salad lives in a Bowl, which each fruit salad crate implements on top
+of its own collection (LinkedList, VecDeque, Vec), so the commands run on
+the collection-specific operations. A Session parses the commands, keeps
+the history and snapshots of the bowl for undo and redo.
+
+Commands, positions start at 0:
+
+    add <position> <fruit>   insert a fruit
+    remove <position>        remove the fruit at a position
+    push front|back <fruit>  add a fruit at one end
+    pop front|back           remove the fruit at one end
+    shuffle                  random order
+    sort                     alphabetical order
+    undo / redo              step back or forward through the changes
+    show                     print the salad
+    history                  print the commands entered so far
+    save <file>              write the salad to a file
+    help                     list the commands
+    quit / exit              leave the shell
+
+The same commands can be read from a script file, one per line. Empty
+lines and lines starting with '#' are skipped.
+*/
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// The salad of the LinkedList and VecDeque programs before shuffling,
+/// which their shells start with
+pub const STARTING_SALAD: [&str; 6] = [
+    "Pomegranate",
+    "Arbutus",
+    "Loquat",
+    "Strawberry Tree Berry",
+    "Fig",
+    "Cherry",
+];
+
+/// A salad collection the shell can edit.
+///
+/// `insert` and `remove` are only called with positions inside the bowl,
+/// `insert` also with the position just past the end.
+pub trait Bowl: Clone {
+    fn insert(&mut self, position: usize, fruit: String);
+    fn remove(&mut self, position: usize) -> String;
+    fn push_front(&mut self, fruit: String);
+    fn push_back(&mut self, fruit: String);
+    fn pop_front(&mut self) -> Option<String>;
+    fn pop_back(&mut self) -> Option<String>;
+    fn shuffle(&mut self, rng: &mut StdRng);
+    fn sort(&mut self);
+    fn fruits(&self) -> Vec<String>;
+
+    fn len(&self) -> usize {
+        self.fruits().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the salad to a file, one fruit per line
+    fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut content = self.fruits().join("\n");
+        content.push('\n');
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Which end of the bowl
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Front,
+    Back,
+}
+
+/// A parsed shell command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Add(usize, String),
+    Remove(usize),
+    Push(End, String),
+    Pop(End),
+    Shuffle,
+    Sort,
+    Undo,
+    Redo,
+    Show,
+    History,
+    Save(String),
+    Help,
+    Quit,
+}
+
+/// Why a command failed. The shell reports it and carries on.
+#[derive(Debug)]
+pub enum ReplError {
+    UnknownCommand(String),
+    /// A command with missing or invalid arguments, with its usage
+    Usage(&'static str),
+    OutOfBounds {
+        position: usize,
+        len: usize,
+    },
+    Empty,
+    NothingToUndo,
+    NothingToRedo,
+    Save(String),
+}
+
+impl fmt::Display for ReplError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplError::UnknownCommand(command) => {
+                write!(
+                    f,
+                    "unknown command '{}', type 'help' for the commands",
+                    command
+                )
+            }
+            ReplError::Usage(usage) => write!(f, "usage: {}", usage),
+            ReplError::OutOfBounds { position, len } => write!(
+                f,
+                "position {} is out of bounds, the salad has {} fruits",
+                position, len
+            ),
+            ReplError::Empty => write!(f, "the salad is empty"),
+            ReplError::NothingToUndo => write!(f, "nothing to undo"),
+            ReplError::NothingToRedo => write!(f, "nothing to redo"),
+            ReplError::Save(e) => write!(f, "could not save: {}", e),
+        }
+    }
+}
+
+impl Error for ReplError {}
+
+const HELP: &str = "\
+add <position> <fruit>   insert a fruit, positions start at 0
+remove <position>        remove the fruit at a position
+push front|back <fruit>  add a fruit at one end
+pop front|back           remove the fruit at one end
+shuffle                  random order
+sort                     alphabetical order
+undo / redo              step back or forward through the changes
+show                     print the salad
+history                  print the commands entered so far
+save <file>              write the salad to a file
+help                     list the commands
+quit / exit              leave the shell";
+
+fn parse_end(word: Option<&str>, usage: &'static str) -> Result<End, ReplError> {
+    match word.map(str::to_lowercase).as_deref() {
+        Some("front") => Ok(End::Front),
+        Some("back") => Ok(End::Back),
+        _ => Err(ReplError::Usage(usage)),
+    }
+}
+
+fn parse_position(word: Option<&str>, usage: &'static str) -> Result<usize, ReplError> {
+    word.and_then(|w| w.parse().ok())
+        .ok_or(ReplError::Usage(usage))
+}
+
+// The rest of the line after `skip` words, as typed, for fruit names and
+// file paths with spaces
+fn rest(line: &str, skip: usize) -> String {
+    let mut rest = line.trim();
+    for _ in 0..skip {
+        rest = rest
+            .trim_start_matches(|c: char| !c.is_whitespace())
+            .trim_start();
+    }
+    rest.to_string()
+}
+
+impl std::str::FromStr for Command {
+    type Err = ReplError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default().to_lowercase();
+        let command = match command.as_str() {
+            "add" => {
+                const USAGE: &str = "add <position> <fruit>";
+                let position = parse_position(words.next(), USAGE)?;
+                let fruit = rest(line, 2);
+                if fruit.is_empty() {
+                    return Err(ReplError::Usage(USAGE));
+                }
+                Command::Add(position, fruit)
+            }
+            "remove" => Command::Remove(parse_position(words.next(), "remove <position>")?),
+            "push" => {
+                const USAGE: &str = "push front|back <fruit>";
+                let end = parse_end(words.next(), USAGE)?;
+                let fruit = rest(line, 2);
+                if fruit.is_empty() {
+                    return Err(ReplError::Usage(USAGE));
+                }
+                Command::Push(end, fruit)
+            }
+            "pop" => Command::Pop(parse_end(words.next(), "pop front|back")?),
+            "shuffle" => Command::Shuffle,
+            "sort" => Command::Sort,
+            "undo" => Command::Undo,
+            "redo" => Command::Redo,
+            "show" | "ls" => Command::Show,
+            "history" => Command::History,
+            "save" => {
+                let path = rest(line, 1);
+                if path.is_empty() {
+                    return Err(ReplError::Usage("save <file>"));
+                }
+                Command::Save(path)
+            }
+            "help" | "?" => Command::Help,
+            "quit" | "exit" => Command::Quit,
+            _ => return Err(ReplError::UnknownCommand(command)),
+        };
+        Ok(command)
+    }
+}
+
+/// Format a salad the way the fruit salad programs print it
+pub fn format_salad(fruits: &[String]) -> String {
+    if fruits.is_empty() {
+        "Fruit Salad: (empty)".to_string()
+    } else {
+        format!("Fruit Salad: {}", fruits.join(", "))
+    }
+}
+
+/// A shell session on one bowl.
+pub struct Session<B: Bowl> {
+    bowl: B,
+    rng: StdRng,
+    history: Vec<String>,
+    undo: Vec<(String, B)>,
+    redo: Vec<(String, B)>,
+}
+
+impl<B: Bowl> Session<B> {
+    pub fn new(bowl: B) -> Self {
+        Self::with_rng(bowl, StdRng::from_entropy())
+    }
+
+    /// A session with a seeded shuffle
+    pub fn with_seed(bowl: B, seed: u64) -> Self {
+        Self::with_rng(bowl, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(bowl: B, rng: StdRng) -> Self {
+        Session {
+            bowl,
+            rng,
+            history: Vec::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    pub fn bowl(&self) -> &B {
+        &self.bowl
+    }
+
+    pub fn into_bowl(self) -> B {
+        self.bowl
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    // Snapshot for undo before a change
+    fn checkpoint(&mut self, line: &str) {
+        self.undo.push((line.to_string(), self.bowl.clone()));
+        self.redo.clear();
+    }
+
+    fn check_position(&self, position: usize, len: usize) -> Result<(), ReplError> {
+        if position >= len {
+            return Err(ReplError::OutOfBounds {
+                position,
+                len: self.bowl.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Run one command line and return what to print. `None` means quit.
+    pub fn execute(&mut self, line: &str) -> Result<Option<String>, ReplError> {
+        let line = line.trim();
+        self.history.push(line.to_string());
+        let command: Command = line.parse()?;
+
+        let message = match command {
+            Command::Add(position, fruit) => {
+                // One past the end appends
+                self.check_position(position, self.bowl.len() + 1)?;
+                self.checkpoint(line);
+                let message = format!("Added {} at {}", fruit, position);
+                self.bowl.insert(position, fruit);
+                message
+            }
+            Command::Remove(position) => {
+                self.check_position(position, self.bowl.len())?;
+                self.checkpoint(line);
+                let fruit = self.bowl.remove(position);
+                format!("Removed {} at {}", fruit, position)
+            }
+            Command::Push(end, fruit) => {
+                self.checkpoint(line);
+                let message = format!("Added {} to the {}", fruit, end_name(end));
+                match end {
+                    End::Front => self.bowl.push_front(fruit),
+                    End::Back => self.bowl.push_back(fruit),
+                }
+                message
+            }
+            Command::Pop(end) => {
+                if self.bowl.is_empty() {
+                    return Err(ReplError::Empty);
+                }
+                self.checkpoint(line);
+                let fruit = match end {
+                    End::Front => self.bowl.pop_front(),
+                    End::Back => self.bowl.pop_back(),
+                };
+                format!(
+                    "Removed {} from the {}",
+                    fruit.unwrap_or_default(),
+                    end_name(end)
+                )
+            }
+            Command::Shuffle => {
+                self.checkpoint(line);
+                self.bowl.shuffle(&mut self.rng);
+                format_salad(&self.bowl.fruits())
+            }
+            Command::Sort => {
+                self.checkpoint(line);
+                self.bowl.sort();
+                format_salad(&self.bowl.fruits())
+            }
+            Command::Undo => {
+                let (undone, bowl) = self.undo.pop().ok_or(ReplError::NothingToUndo)?;
+                let current = std::mem::replace(&mut self.bowl, bowl);
+                self.redo.push((undone.clone(), current));
+                format!("Undid '{}'", undone)
+            }
+            Command::Redo => {
+                let (redone, bowl) = self.redo.pop().ok_or(ReplError::NothingToRedo)?;
+                let current = std::mem::replace(&mut self.bowl, bowl);
+                self.undo.push((redone.clone(), current));
+                format!("Redid '{}'", redone)
+            }
+            Command::Show => format_salad(&self.bowl.fruits()),
+            Command::History => self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, line)| format!("{:>3}  {}", i + 1, line))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Command::Save(path) => {
+                self.bowl
+                    .save(Path::new(&path))
+                    .map_err(|e| ReplError::Save(e.to_string()))?;
+                format!("Saved {} fruits to {}", self.bowl.len(), path)
+            }
+            Command::Help => HELP.to_string(),
+            Command::Quit => return Ok(None),
+        };
+        Ok(Some(message))
+    }
+
+    /*
+    Read commands until the input ends or 'quit'. Interactive sessions show
+    a prompt. Errors are printed and the session carries on; the number of
+    failed commands is returned so scripts can report them.
+    */
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        input: R,
+        out: &mut W,
+        interactive: bool,
+    ) -> io::Result<usize> {
+        let mut errors = 0;
+        let mut lines = input.lines();
+        loop {
+            if interactive {
+                write!(out, "salad> ")?;
+                out.flush()?;
+            }
+            let Some(line) = lines.next() else {
+                break;
+            };
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            match self.execute(trimmed) {
+                Ok(Some(message)) => writeln!(out, "{}", message)?,
+                Ok(None) => break,
+                Err(e) => {
+                    errors += 1;
+                    writeln!(out, "Error: {}", e)?;
+                }
+            }
+        }
+        Ok(errors)
+    }
+}
+
+fn end_name(end: End) -> &'static str {
+    match end {
+        End::Front => "front",
+        End::Back => "back",
+    }
+}
+
+/// Run a session from a script file, or from stdin with a prompt when no
+/// script is given. Returns the bowl and the number of failed commands.
+pub fn run_session<B: Bowl>(
+    bowl: B,
+    script: Option<&Path>,
+    seed: Option<u64>,
+) -> io::Result<(B, usize)> {
+    let mut session = match seed {
+        Some(seed) => Session::with_seed(bowl, seed),
+        None => Session::new(bowl),
+    };
+    let mut stdout = io::stdout();
+    let errors = match script {
+        Some(path) => {
+            let file = io::BufReader::new(std::fs::File::open(path)?);
+            session.run(file, &mut stdout, false)?
+        }
+        None => {
+            writeln!(stdout, "{}", format_salad(&session.bowl.fruits()))?;
+            writeln!(stdout, "Type 'help' for the commands.")?;
+            session.run(io::stdin().lock(), &mut stdout, true)?
+        }
+    };
+    Ok((session.into_bowl(), errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::seq::SliceRandom;
+
+    // A plain Vec bowl for the tests
+    #[derive(Clone, Debug, PartialEq)]
+    struct VecBowl(Vec<String>);
+
+    impl Bowl for VecBowl {
+        fn insert(&mut self, position: usize, fruit: String) {
+            self.0.insert(position, fruit);
+        }
+        fn remove(&mut self, position: usize) -> String {
+            self.0.remove(position)
+        }
+        fn push_front(&mut self, fruit: String) {
+            self.0.insert(0, fruit);
+        }
+        fn push_back(&mut self, fruit: String) {
+            self.0.push(fruit);
+        }
+        fn pop_front(&mut self) -> Option<String> {
+            (!self.0.is_empty()).then(|| self.0.remove(0))
+        }
+        fn pop_back(&mut self) -> Option<String> {
+            self.0.pop()
+        }
+        fn shuffle(&mut self, rng: &mut StdRng) {
+            self.0.shuffle(rng);
+        }
+        fn sort(&mut self) {
+            self.0.sort();
+        }
+        fn fruits(&self) -> Vec<String> {
+            self.0.clone()
+        }
+    }
+
+    fn session(fruits: &[&str]) -> Session<VecBowl> {
+        Session::with_seed(VecBowl(fruits.iter().map(|s| s.to_string()).collect()), 1)
+    }
+
+    fn run(session: &mut Session<VecBowl>, script: &str) -> (String, usize) {
+        let mut out = Vec::new();
+        let errors = session.run(script.as_bytes(), &mut out, false).unwrap();
+        (String::from_utf8(out).unwrap(), errors)
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            "add 2 Strawberry Tree Berry".parse::<Command>().unwrap(),
+            Command::Add(2, "Strawberry Tree Berry".to_string())
+        );
+        assert_eq!(
+            "PUSH Front Fig".parse::<Command>().unwrap(),
+            Command::Push(End::Front, "Fig".to_string())
+        );
+        assert_eq!(
+            "pop back".parse::<Command>().unwrap(),
+            Command::Pop(End::Back)
+        );
+        assert!(matches!(
+            "add two Fig".parse::<Command>(),
+            Err(ReplError::Usage(_))
+        ));
+        assert!(matches!(
+            "push middle Fig".parse::<Command>(),
+            Err(ReplError::Usage(_))
+        ));
+        assert!(matches!(
+            "blend".parse::<Command>(),
+            Err(ReplError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_rest_keeps_spaces() {
+        assert_eq!(
+            "save  my salads/two  spaces.txt "
+                .parse::<Command>()
+                .unwrap(),
+            Command::Save("my salads/two  spaces.txt".to_string())
+        );
+        assert_eq!(
+            "\tadd 0 Strawberry Tree Berry".parse::<Command>().unwrap(),
+            Command::Add(0, "Strawberry Tree Berry".to_string())
+        );
+        let mut session = session(&[]);
+        let (out, _) = run(&mut session, "help\n");
+        assert!(out.contains("help                     list the commands\n"));
+    }
+
+    #[test]
+    fn test_edit_commands() {
+        let mut session = session(&["Fig", "Cherry"]);
+        let (out, errors) = run(
+            &mut session,
+            "add 1 Kiwi\npush front Lime\npush back Pear\nremove 0\npop back\nsort\nshow\n",
+        );
+        assert_eq!(errors, 0);
+        assert!(out.contains("Added Kiwi at 1\n"));
+        assert!(out.contains("Removed Lime at 0\n"));
+        assert!(out.contains("Removed Pear from the back\n"));
+        assert!(out.ends_with("Fruit Salad: Cherry, Fig, Kiwi\n"));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut session = session(&["Fig"]);
+        let (out, errors) = run(
+            &mut session,
+            "push back Kiwi\npush back Lime\nundo\nundo\nredo\nshow\nundo\nundo\n",
+        );
+        assert_eq!(errors, 1);
+        assert!(out.contains("Undid 'push back Lime'\n"));
+        assert!(out.contains("Redid 'push back Kiwi'\n"));
+        assert!(out.contains("Fruit Salad: Fig, Kiwi\n"));
+        assert!(out.ends_with("Error: nothing to undo\n"));
+        assert_eq!(session.bowl().0, ["Fig"]);
+
+        // A new change clears the redo steps
+        let (out, _) = run(&mut session, "redo\npush back Pear\nundo\nredo\nredo\n");
+        assert!(out.ends_with("Error: nothing to redo\n"));
+        assert_eq!(session.bowl().0, ["Fig", "Kiwi", "Pear"]);
+    }
+
+    #[test]
+    fn test_errors_keep_the_salad() {
+        let mut session = session(&["Fig"]);
+        let (out, errors) = run(&mut session, "remove 3\nadd 5 Kiwi\npop front\npop back\n");
+        assert_eq!(errors, 3);
+        assert!(out.contains("Error: position 3 is out of bounds, the salad has 1 fruits\n"));
+        assert!(out.ends_with("Error: the salad is empty\n"));
+        // Failed commands leave nothing to undo
+        let (out, _) = run(&mut session, "undo\nundo\n");
+        assert!(out.ends_with("Error: nothing to undo\n"));
+    }
+
+    #[test]
+    fn test_seeded_shuffle() {
+        let fruits = ["Apple", "Banana", "Cherry", "Date", "Fig"];
+        let (first, _) = run(&mut session(&fruits), "shuffle\n");
+        let (second, _) = run(&mut session(&fruits), "shuffle\n");
+        assert_eq!(first, second);
+        assert_ne!(first, "Fruit Salad: Apple, Banana, Cherry, Date, Fig\n");
+    }
+
+    #[test]
+    fn test_history_comments_and_quit() {
+        let mut session = session(&[]);
+        let (out, _) = run(
+            &mut session,
+            "# a comment\n\npush back Fig\nhistory\nquit\npush back Kiwi\n",
+        );
+        assert_eq!(
+            out,
+            "Added Fig to the back\n  1  push back Fig\n  2  history\n"
+        );
+        assert_eq!(session.history(), ["push back Fig", "history", "quit"]);
+    }
+
+    #[test]
+    fn test_save() {
+        let path =
+            std::env::temp_dir().join(format!("fruit-salad-repl-{}.txt", std::process::id()));
+        let mut session = session(&["Fig", "Kiwi"]);
+        let (out, errors) = run(&mut session, &format!("save {}\n", path.display()));
+        assert_eq!(errors, 0);
+        assert!(out.starts_with("Saved 2 fruits to "));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "Fig\nKiwi\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+/*
+The VecDeque salad as a bowl for the interactive shell.
+
+Both ends are O(1) and VecDeque inserts and removes in the middle by
+shifting the shorter side. Shuffling and sorting work on the contiguous
+slice from make_contiguous.
+*/
+
+use std::collections::VecDeque;
+
+use fruit_salad_repl::Bowl;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+#[derive(Clone, Debug, Default)]
+pub struct VecDequeBowl(pub VecDeque<String>);
+
+impl Bowl for VecDequeBowl {
+    fn insert(&mut self, position: usize, fruit: String) {
+        self.0.insert(position, fruit);
+    }
+
+    fn remove(&mut self, position: usize) -> String {
+        self.0
+            .remove(position)
+            .expect("position is inside the queue")
+    }
+
+    fn push_front(&mut self, fruit: String) {
+        self.0.push_front(fruit);
+    }
+
+    fn push_back(&mut self, fruit: String) {
+        self.0.push_back(fruit);
+    }
+
+    fn pop_front(&mut self) -> Option<String> {
+        self.0.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<String> {
+        self.0.pop_back()
+    }
+
+    fn shuffle(&mut self, rng: &mut StdRng) {
+        self.0.make_contiguous().shuffle(rng);
+    }
+
+    fn sort(&mut self) {
+        self.0.make_contiguous().sort();
+    }
+
+    fn fruits(&self) -> Vec<String> {
+        self.0.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
@@ -8,10 +8,14 @@ A VecDeque is a double-ended queue, which means that you can push and pop from b
 of the queue.
 */
 
+mod bowl;
+
+use bowl::VecDequeBowl;
 use clap::Parser; // clap is a command line argument parser for Rust
 use rand::seq::SliceRandom; // rand is a random number generation library in Rust
 use rand::thread_rng;
 use std::collections::VecDeque;
+use std::path::PathBuf;
 
 // 1. Modify the program to allow the user to add fruits to either end of the queue after shuffling?
 //    Using clap command line arguments.
@@ -24,12 +28,46 @@ struct Args {
     /// Fruits to add to front of the salad
     #[clap(short = 'f', long, value_name = "FRUIT_FRONT", num_args = 1.., required = false)]
     fruit_front: Vec<String>,
+    /// Edit the salad in an interactive shell
+    #[clap(long)]
+    repl: bool,
+    /// Run the shell commands of a file instead of reading them interactively
+    #[clap(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+    /// Seed for the shuffle command of the shell
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+// 4. Interactive shell on the salad before shuffling, see fruit-salad-repl for the commands
+fn run_repl(args: &Args) {
+    let bowl = VecDequeBowl(
+        fruit_salad_repl::STARTING_SALAD
+            .iter()
+            .map(|f| f.to_string())
+            .collect(),
+    );
+    match fruit_salad_repl::run_session(bowl, args.script.as_deref(), args.seed) {
+        Ok((_, 0)) => {}
+        Ok((_, errors)) => {
+            eprintln!("{} commands failed", errors);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 fn main() {
 
     // 1. claps command line arguments
     let args = Args::parse();
+    if args.repl || args.script.is_some() {
+        run_repl(&args);
+        return;
+    }
 
     let mut fruit: VecDeque<&str> = VecDeque::new();
     fruit.push_back("Arbutus");
@@ -0,0 +1,46 @@
+use std::process::Command;
+
+// Run the shell on a script and return stdout and whether it succeeded
+fn run_script(name: &str, script: &str) -> (String, bool) {
+    let path = std::env::temp_dir().join(format!("vecdeq-{}-{}.txt", name, std::process::id()));
+    std::fs::write(&path, script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_fruit-salad-vecdeq"))
+        .arg("--script")
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.success(),
+    )
+}
+
+// Pushing at the front wraps the deque around its buffer, so sort has to
+// make it contiguous first
+#[test]
+fn test_script_works_both_ends() {
+    let (out, ok) = run_script(
+        "ends",
+        "pop back\npush front Lime\npush front Kiwi\npush back Pear\nsort\n",
+    );
+    assert!(ok);
+    assert_eq!(
+        out,
+        "Removed Cherry from the back\n\
+         Added Lime to the front\n\
+         Added Kiwi to the front\n\
+         Added Pear to the back\n\
+         Fruit Salad: Arbutus, Fig, Kiwi, Lime, Loquat, Pear, Pomegranate, Strawberry Tree Berry\n"
+    );
+}
+
+#[test]
+fn test_script_empties_the_deque() {
+    let script = "pop front\n".repeat(3) + &"pop back\n".repeat(4) + "undo\nshow\n";
+    let (out, ok) = run_script("empty", &script);
+    assert!(!ok);
+    assert!(out.contains("Removed Strawberry Tree Berry from the back\n"));
+    assert!(out.contains("Error: the salad is empty\n"));
+    assert!(out.ends_with("Fruit Salad: Strawberry Tree Berry\n"));
+}
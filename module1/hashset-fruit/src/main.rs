@@ -1,6 +1,7 @@
 use clap::Parser;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
 
 /// Simple program to generate random fruits
@@ -10,9 +11,13 @@ struct Args {
     /// Number of random fruits to generate (default: 100)
     #[arg(short, long, default_value_t = 100)]
     count: usize,
+
+    /// Seed for the random fruits, the same seed gives the same fruits
+    #[arg(short, long)]
+    seed: Option<u64>,
 }
 
-fn generate_fruit() -> &'static str {
+fn generate_fruit<R: Rng + ?Sized>(rng: &mut R) -> &'static str {
     let fruits = [
         "Apple",
         "Banana",
@@ -23,22 +28,39 @@ fn generate_fruit() -> &'static str {
         "Grape",
         "Honeydew",
     ];
-    let mut rng = thread_rng();
-    fruits.choose(&mut rng).unwrap()
+    fruits.choose(rng).unwrap()
+}
+
+fn count_fruits<R: Rng + ?Sized>(count: usize, rng: &mut R) -> HashMap<&'static str, usize> {
+    let mut fruit_counts: HashMap<&str, usize> = HashMap::new();
+    for _ in 0..count {
+        let fruit = generate_fruit(rng);
+        *fruit_counts.entry(fruit).or_insert(0) += 1;
+    }
+    fruit_counts
+}
+
+// HashMap order changes from run to run, sort by name so a seed always
+// prints the same output
+fn sorted_counts(fruit_counts: &HashMap<&'static str, usize>) -> Vec<(&'static str, usize)> {
+    let mut counts: Vec<(&str, usize)> = fruit_counts.iter().map(|(f, c)| (*f, *c)).collect();
+    counts.sort();
+    counts
 }
 
 fn main() {
     let args = Args::parse();
-    let mut fruit_counts: HashMap<&str, usize> = HashMap::new();
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
 
     println!("Generating {} random fruits...", args.count);
-    for _ in 0..args.count {
-        let fruit = generate_fruit();
-        *fruit_counts.entry(fruit).or_insert(0) += 1;
-    }
+    let fruit_counts = count_fruits(args.count, &mut rng);
+    let sorted = sorted_counts(&fruit_counts);
 
     println!("\nFruit generation summary:");
-    for (fruit, count) in &fruit_counts {
+    for (fruit, count) in &sorted {
         println!("{:<10}: {}", fruit, count);
     }
 
@@ -47,7 +69,39 @@ fn main() {
         fruit_counts.len()
     );
 
-    let unique_fruits: Vec<&str> = fruit_counts.keys().cloned().collect();
+    let unique_fruits: Vec<&str> = sorted.iter().map(|(fruit, _)| *fruit).collect();
     println!("\nUnique fruits generated ({}):", unique_fruits.len());
     println!("{}", unique_fruits.join(", "));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_fruits() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let fruits: Vec<&str> = (0..5).map(|_| generate_fruit(&mut rng)).collect();
+        assert_eq!(fruits, ["Grape", "Date", "Banana", "Grape", "Cherry"]);
+    }
+
+    #[test]
+    fn test_seeded_counts() {
+        let counts = count_fruits(100, &mut StdRng::seed_from_u64(1));
+        assert_eq!(counts.values().sum::<usize>(), 100);
+        assert_eq!(
+            sorted_counts(&counts),
+            [
+                ("Apple", 12),
+                ("Banana", 16),
+                ("Cherry", 15),
+                ("Date", 9),
+                ("Elderberry", 8),
+                ("Fig", 11),
+                ("Grape", 19),
+                ("Honeydew", 10)
+            ]
+        );
+        assert_eq!(counts, count_fruits(100, &mut StdRng::seed_from_u64(1)));
+    }
+}
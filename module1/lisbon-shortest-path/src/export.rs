@@ -0,0 +1,53 @@
+/*
+Routes as GeoJSON: a FeatureCollection with a LineString per route
+through the coordinates of its landmarks, shortest first. The properties
+of each route are its rank (1 for the shortest), its length in km and the
+names of its landmarks.
+*/
+
+use serde_json::{Value, json};
+
+use crate::load::MapError;
+use crate::network::{Network, Route};
+
+/// A route as a GeoJSON LineString feature. All its landmarks need
+/// coordinates.
+pub fn route_feature(network: &Network, route: &Route, rank: usize) -> Result<Value, MapError> {
+    let coordinates = route
+        .landmarks
+        .iter()
+        .map(|&node| {
+            let landmark = network.landmark(node);
+            let position = landmark
+                .position
+                .ok_or_else(|| MapError::NoPosition(landmark.name.clone()))?;
+            // GeoJSON puts the longitude first
+            Ok(json!([position.lon, position.lat]))
+        })
+        .collect::<Result<Vec<Value>, MapError>>()?;
+    Ok(json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": coordinates,
+        },
+        "properties": {
+            "rank": rank,
+            "km": route.km,
+            "landmarks": network.names(route),
+        },
+    }))
+}
+
+pub fn to_geojson(network: &Network, routes: &[Route]) -> Result<String, MapError> {
+    let features = routes
+        .iter()
+        .enumerate()
+        .map(|(rank, route)| route_feature(network, route, rank + 1))
+        .collect::<Result<Vec<Value>, MapError>>()?;
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    Ok(serde_json::to_string_pretty(&collection)? + "\n")
+}
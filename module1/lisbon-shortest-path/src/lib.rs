@@ -0,0 +1,253 @@
+/*
+Routing between landmarks.
+
+A Network holds landmarks, with their coordinates when they are known,
+and the two-way roads between them. The landmarks and roads come from
+CSV or GeoJSON files (MapData), or from the five Lisbon landmarks of the
+lab. Routes are found with A* guided by the great-circle distance to the
+destination, alternatives with Yen's k-shortest-paths algorithm, and
+they can be written out as GeoJSON LineStrings.
+*/
+
+pub mod export;
+pub mod load;
+pub mod network;
+
+pub use export::{route_feature, to_geojson};
+pub use load::{MapData, MapError, Road};
+pub use network::{EARTH_RADIUS_KM, Landmark, Network, Position, Route, haversine};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::algo::{all_simple_paths, dijkstra};
+    use petgraph::prelude::*;
+
+    // The length of every route without loops between two landmarks,
+    // shortest first
+    fn all_route_lengths(network: &Network, from: &str, to: &str) -> Vec<f64> {
+        let mut graph = UnGraph::<(), f64>::new_undirected();
+        for _ in 0..network.landmark_count() {
+            graph.add_node(());
+        }
+        for (a, b, km) in network.roads() {
+            graph.add_edge(network.node(a).unwrap(), network.node(b).unwrap(), km);
+        }
+        let (from, to) = (network.node(from).unwrap(), network.node(to).unwrap());
+        let mut lengths: Vec<f64> =
+            all_simple_paths::<Vec<NodeIndex>, _>(&graph, from, to, 0, None)
+                .map(|path| {
+                    path.windows(2)
+                        .map(|pair| {
+                            graph
+                                .edges_connecting(pair[0], pair[1])
+                                .map(|road| *road.weight())
+                                .fold(f64::INFINITY, f64::min)
+                        })
+                        .sum()
+                })
+                .collect();
+        lengths.sort_by(f64::total_cmp);
+        lengths
+    }
+
+    #[test]
+    fn test_haversine() {
+        // A degree along the equator or a meridian
+        let one_degree = EARTH_RADIUS_KM * std::f64::consts::PI / 180.0;
+        let origin = Position::new(0.0, 0.0);
+        assert!((haversine(origin, Position::new(0.0, 1.0)) - one_degree).abs() < 1e-9);
+        assert!((haversine(origin, Position::new(1.0, 0.0)) - one_degree).abs() < 1e-9);
+        assert_eq!(haversine(origin, origin), 0.0);
+
+        // Lisbon to Porto is about 274 km
+        let lisbon = Position::new(38.7223, -9.1393);
+        let porto = Position::new(41.1579, -8.6291);
+        assert!((haversine(lisbon, porto) - 274.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_lisbon_route() {
+        let network = Network::lisbon();
+        let from = network.node("Belem Tower").unwrap();
+        let to = network.node("Lisbon Cathedral").unwrap();
+        let route = network.shortest_route(from, to).unwrap();
+        assert_eq!(route.km, 8.0);
+        assert_eq!(
+            network.names(&route),
+            ["Belem Tower", "Commerce Square", "Lisbon Cathedral"]
+        );
+
+        let mut network = network;
+        network.add_landmark("Island", None);
+        assert_eq!(
+            network.shortest_route(from, network.node("Island").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra() {
+        // Road lengths below the straight line, which would make the plain
+        // haversine estimate overshoot
+        let mut network = Network::lisbon();
+        network.add_landmark("Gulbenkian Museum", Some(Position::new(38.7374, -9.1540)));
+        network.add_landmark("Oceanarium", Some(Position::new(38.7633, -9.0937)));
+        network.add_road("Lisbon Cathedral", "Gulbenkian Museum", 2.5);
+        network.add_road("Gulbenkian Museum", "Oceanarium", 5.0);
+        network.add_road("Commerce Square", "Oceanarium", 9.0);
+        network.add_road("LX Factory", "Gulbenkian Museum", 4.0);
+
+        let mut graph = UnGraph::<(), f64>::new_undirected();
+        for _ in 0..network.landmark_count() {
+            graph.add_node(());
+        }
+        for (a, b, km) in network.roads() {
+            graph.add_edge(network.node(a).unwrap(), network.node(b).unwrap(), km);
+        }
+        for from in graph.node_indices() {
+            let distances = dijkstra(&graph, from, None, |road| *road.weight());
+            for to in graph.node_indices() {
+                let route = network.shortest_route(from, to).unwrap();
+                assert!((route.km - distances[&to]).abs() < 1e-12);
+                assert_eq!(route.landmarks.first(), Some(&from));
+                assert_eq!(route.landmarks.last(), Some(&to));
+            }
+        }
+    }
+
+    #[test]
+    fn test_k_shortest_routes() {
+        let mut network = Network::lisbon();
+        network.add_road("LX Factory", "Lisbon Cathedral", 6.5);
+        network.add_road("Jerónimos Monastery", "Lisbon Cathedral", 8.0);
+        let from = network.node("Belem Tower").unwrap();
+        let to = network.node("Lisbon Cathedral").unwrap();
+
+        let routes = network.k_shortest_routes(from, to, 20);
+        let lengths: Vec<f64> = routes.iter().map(|route| route.km).collect();
+        // Every route without loops, in order
+        let expected = all_route_lengths(&network, "Belem Tower", "Lisbon Cathedral");
+        assert!(expected.len() > 10 && expected.len() < 20);
+        assert_eq!(lengths, expected);
+        // and no route twice
+        for (i, a) in routes.iter().enumerate() {
+            assert!(routes[i + 1..].iter().all(|b| b.landmarks != a.landmarks));
+            let mut seen = a.landmarks.clone();
+            seen.sort();
+            seen.dedup();
+            assert_eq!(seen.len(), a.landmarks.len(), "a loop in {a:?}");
+        }
+
+        assert_eq!(network.k_shortest_routes(from, to, 2).len(), 2);
+        assert!(network.k_shortest_routes(from, to, 0).is_empty());
+        assert_eq!(network.k_shortest_routes(from, from, 3).len(), 1);
+    }
+
+    #[test]
+    fn test_read_csv_and_geojson() {
+        let mut data = MapData::new();
+        data.read_csv("from,to,km\nA,B,2\nB,C,\n".as_bytes())
+            .unwrap();
+        data.read_csv("Name, Lat, Lon\nA,38.70,-9.20\nB,38.70,-9.19\nC,38.71,-9.19\n".as_bytes())
+            .unwrap();
+        let network = data.into_network().unwrap();
+        assert_eq!((network.landmark_count(), network.road_count()), (3, 2));
+        let roads: Vec<_> = network.roads().collect();
+        assert_eq!(roads[0], ("A", "B", 2.0));
+        // No length given: the straight line, 0.01° of latitude
+        assert!((roads[1].2 - 1.112).abs() < 1e-3);
+
+        let text = r#"{
+          "type": "FeatureCollection",
+          "features": [
+            {"type": "Feature", "properties": {"name": "A"},
+             "geometry": {"type": "Point", "coordinates": [-9.20, 38.70]}},
+            {"type": "Feature", "properties": {"name": "B"},
+             "geometry": {"type": "Point", "coordinates": [-9.19, 38.70]}},
+            {"type": "Feature", "properties": {"from": "A", "to": "B", "km": 1.5},
+             "geometry": {"type": "LineString", "coordinates": [[-9.20, 38.70], [-9.19, 38.70]]}},
+            {"type": "Feature", "properties": {"from": "B", "to": "A"},
+             "geometry": {"type": "LineString",
+                          "coordinates": [[-9.19, 38.70], [-9.19, 38.71], [-9.20, 38.70]]}}
+          ]
+        }"#;
+        let mut data = MapData::new();
+        data.read_geojson(text.as_bytes()).unwrap();
+        assert_eq!(
+            data.landmarks[1],
+            ("B".to_string(), Position::new(38.70, -9.19))
+        );
+        assert_eq!(data.roads[0].km, Some(1.5));
+        // The length of the line through its three points
+        let bent = data.roads[1].km.unwrap();
+        assert!(bent > haversine(Position::new(38.70, -9.19), Position::new(38.70, -9.20)));
+
+        let mut data = MapData::new();
+        data.read_csv("from,to\nX,Y\n".as_bytes()).unwrap();
+        let error = data.into_network().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the road from X to Y has no length and its landmarks no coordinates"
+        );
+        // A road to a misspelt landmark
+        let mut data = MapData::new();
+        data.read_csv("name,lat,lon\nA,38.70,-9.20\nB,38.70,-9.19\n".as_bytes())
+            .unwrap();
+        data.read_csv("from,to,km\nA,B,1\nA,Bee,2\n".as_bytes())
+            .unwrap();
+        let error = data.into_network().unwrap_err();
+        assert_eq!(error.to_string(), "unknown landmark Bee");
+        // Roads alone, with lengths, still make a network
+        let mut data = MapData::new();
+        data.read_csv("from,to,km\nA,B,1\n".as_bytes()).unwrap();
+        assert_eq!(data.into_network().unwrap().landmark_count(), 2);
+
+        let error = MapData::new()
+            .read_csv("name,lat,lon\nA,north,1\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 2: bad latitude \"north\"");
+    }
+
+    #[test]
+    fn test_sample_files() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let mut csv = MapData::new();
+        csv.load(&dir.join("landmarks.csv")).unwrap();
+        csv.load(&dir.join("roads.csv")).unwrap();
+        let mut geojson = MapData::new();
+        geojson.load(&dir.join("lisbon.geojson")).unwrap();
+
+        for network in [csv.into_network().unwrap(), geojson.into_network().unwrap()] {
+            let from = network.node("Belem Tower").unwrap();
+            let to = network.node("Oceanarium").unwrap();
+            let route = network.shortest_route(from, to).unwrap();
+            assert_eq!(network.names(&route)[3], "Santa Apolónia");
+            assert!((route.km - 17.765).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_geojson_export() {
+        let network = Network::lisbon();
+        let from = network.node("Belem Tower").unwrap();
+        let to = network.node("Lisbon Cathedral").unwrap();
+        let routes = network.k_shortest_routes(from, to, 2);
+        let json: serde_json::Value =
+            serde_json::from_str(&to_geojson(&network, &routes).unwrap()).unwrap();
+        let feature = &json["features"][0];
+        assert_eq!(feature["geometry"]["type"], "LineString");
+        assert_eq!(feature["geometry"]["coordinates"][0][0], -9.2160);
+        assert_eq!(feature["geometry"]["coordinates"][0][1], 38.6916);
+        assert_eq!(feature["properties"]["km"], 8.0);
+        assert_eq!(feature["properties"]["landmarks"][2], "Lisbon Cathedral");
+        assert_eq!(json["features"][1]["properties"]["rank"], 2);
+
+        let mut network = network;
+        network.add_road("Lisbon Cathedral", "Castle", 1.0);
+        let castle = network.node("Castle").unwrap();
+        let route = network.shortest_route(from, castle).unwrap();
+        let error = route_feature(&network, &route, 1).unwrap_err();
+        assert_eq!(error.to_string(), "Castle has no coordinates");
+    }
+}
@@ -0,0 +1,323 @@
+/*
+Reading landmarks and roads from CSV and GeoJSON files.
+
+- CSV: a file of landmarks has a header with a name column ("name" or
+  "landmark") and coordinates ("lat"/"latitude", "lon"/"lng"/"longitude").
+  A file of roads has "from"/"source" and "to"/"target" columns and
+  optionally the length in km ("km", "distance", "distance_km" or
+  "length"). The header tells which kind of file it is.
+- GeoJSON: a FeatureCollection where Point features are landmarks named
+  by their "name" property. LineString features are roads between the
+  landmarks of their "from" and "to" properties. A road without a length
+  property is as long as its line.
+
+A road without a length is as long as the straight line between its
+landmarks, so those need coordinates. All files are read before the
+lengths are worked out, so roads may come before their landmarks. Once
+any landmarks are given, every road must join two of them: a misspelt
+name is an error, not a new landmark without coordinates.
+*/
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::network::{Network, Position, haversine};
+
+// Names of the column or property with the length of a road
+const LENGTH_KEYS: [&str; 4] = ["km", "distance", "distance_km", "length"];
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    /// Bad input at a line (1-based, 0 when not known)
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// A road without a length between landmarks without coordinates
+    NoDistance {
+        from: String,
+        to: String,
+    },
+    /// A landmark without coordinates where they are needed
+    NoPosition(String),
+    /// A name that is not one of the landmarks
+    UnknownLandmark(String),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "{err}"),
+            MapError::Csv(err) => write!(f, "{err}"),
+            MapError::Json(err) => write!(f, "{err}"),
+            MapError::Parse { line: 0, message } => write!(f, "{message}"),
+            MapError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            MapError::NoDistance { from, to } => write!(
+                f,
+                "the road from {from} to {to} has no length and its landmarks no coordinates"
+            ),
+            MapError::NoPosition(name) => write!(f, "{name} has no coordinates"),
+            MapError::UnknownLandmark(name) => write!(f, "unknown landmark {name}"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+impl From<io::Error> for MapError {
+    fn from(err: io::Error) -> Self {
+        MapError::Io(err)
+    }
+}
+
+impl From<csv::Error> for MapError {
+    fn from(err: csv::Error) -> Self {
+        MapError::Csv(err)
+    }
+}
+
+impl From<serde_json::Error> for MapError {
+    fn from(err: serde_json::Error) -> Self {
+        MapError::Json(err)
+    }
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> MapError {
+    MapError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Road {
+    pub from: String,
+    pub to: String,
+    /// None for the straight-line distance
+    pub km: Option<f64>,
+}
+
+/// Landmarks and roads read from files, not yet joined into a Network
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MapData {
+    pub landmarks: Vec<(String, Position)>,
+    pub roads: Vec<Road>,
+}
+
+impl MapData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a .csv, .geojson or .json file
+    pub fn load(&mut self, path: &Path) -> Result<(), MapError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let file = BufReader::new(File::open(path)?);
+        match extension.as_deref() {
+            Some("csv") => self.read_csv(file),
+            Some("geojson" | "json") => self.read_geojson(file),
+            _ => Err(parse_error(
+                0,
+                format!("{}: expected a .csv or .geojson file", path.display()),
+            )),
+        }
+    }
+
+    /// Read a CSV file of landmarks or of roads
+    pub fn read_csv<R: Read>(&mut self, reader: R) -> Result<(), MapError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let headers: Vec<String> = reader
+            .headers()?
+            .iter()
+            .map(|header| header.to_lowercase())
+            .collect();
+        let column = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|header| names.contains(&header.as_str()))
+        };
+
+        let name = column(&["name", "landmark"]);
+        let lat = column(&["lat", "latitude"]);
+        let lon = column(&["lon", "lng", "long", "longitude"]);
+        let from = column(&["from", "source"]);
+        let to = column(&["to", "target"]);
+        let km = column(&LENGTH_KEYS);
+
+        for (number, record) in reader.records().enumerate() {
+            let record = record?;
+            // The header is line 1
+            let line = number + 2;
+            let field = |index: usize| record.get(index).unwrap_or("");
+            match (name, lat, lon, from, to) {
+                (Some(name), Some(lat), Some(lon), _, _) => {
+                    let position = Position::new(
+                        parse_number(line, field(lat), "latitude")?,
+                        parse_number(line, field(lon), "longitude")?,
+                    );
+                    self.landmarks.push((field(name).to_string(), position));
+                }
+                (_, _, _, Some(from), Some(to)) => {
+                    let km = match km.map(field).filter(|text| !text.is_empty()) {
+                        Some(text) => Some(parse_km(line, text)?),
+                        None => None,
+                    };
+                    self.roads.push(Road {
+                        from: field(from).to_string(),
+                        to: field(to).to_string(),
+                        km,
+                    });
+                }
+                _ => {
+                    return Err(parse_error(
+                        1,
+                        "expected name,lat,lon columns for landmarks or from,to[,km] for roads",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a GeoJSON FeatureCollection or Feature
+    pub fn read_geojson<R: Read>(&mut self, reader: R) -> Result<(), MapError> {
+        let json: Value = serde_json::from_reader(reader)?;
+        let features = match json["type"].as_str() {
+            Some("FeatureCollection") => json["features"]
+                .as_array()
+                .ok_or_else(|| parse_error(0, "a FeatureCollection without features"))?
+                .iter()
+                .collect(),
+            Some("Feature") => vec![&json],
+            _ => return Err(parse_error(0, "expected a FeatureCollection or a Feature")),
+        };
+
+        for (number, feature) in features.into_iter().enumerate() {
+            let properties = &feature["properties"];
+            let geometry = &feature["geometry"];
+            let coordinates = &geometry["coordinates"];
+            let at = |message: &str| parse_error(0, format!("feature {number}: {message}"));
+            match geometry["type"].as_str() {
+                Some("Point") => {
+                    let name = properties["name"]
+                        .as_str()
+                        .ok_or_else(|| at("a Point without a name"))?;
+                    let position = position(coordinates).ok_or_else(|| at("bad coordinates"))?;
+                    self.landmarks.push((name.to_string(), position));
+                }
+                Some("LineString") => {
+                    let (Some(from), Some(to)) =
+                        (properties["from"].as_str(), properties["to"].as_str())
+                    else {
+                        return Err(at("a LineString without from and to"));
+                    };
+                    let line: Vec<Position> = coordinates
+                        .as_array()
+                        .ok_or_else(|| at("bad coordinates"))?
+                        .iter()
+                        .map(position)
+                        .collect::<Option<_>>()
+                        .ok_or_else(|| at("bad coordinates"))?;
+                    // The length property, else the length of the line
+                    let km = match LENGTH_KEYS.iter().find_map(|key| properties[key].as_f64()) {
+                        Some(km) if !km.is_finite() || km < 0.0 => return Err(at("bad length")),
+                        Some(km) => Some(km),
+                        None if line.len() >= 2 => Some(
+                            line.windows(2)
+                                .map(|pair| haversine(pair[0], pair[1]))
+                                .sum(),
+                        ),
+                        None => None,
+                    };
+                    self.roads.push(Road {
+                        from: from.to_string(),
+                        to: to.to_string(),
+                        km,
+                    });
+                }
+                // Other features, such as areas, are not part of the network
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Join the landmarks and roads into a network
+    pub fn into_network(self) -> Result<Network, MapError> {
+        let mut network = Network::new();
+        self.add_to(&mut network)?;
+        Ok(network)
+    }
+
+    /// Add the landmarks and roads to a network. Without any landmarks,
+    /// in the files or the network, the roads bring their own.
+    pub fn add_to(self, network: &mut Network) -> Result<(), MapError> {
+        for (name, position) in &self.landmarks {
+            network.add_landmark(name, Some(*position));
+        }
+        let known_landmarks = network.landmark_count() > 0;
+        for road in self.roads {
+            if known_landmarks {
+                for name in [&road.from, &road.to] {
+                    if network.node(name).is_none() {
+                        return Err(MapError::UnknownLandmark(name.clone()));
+                    }
+                }
+            }
+            let km = match road.km {
+                Some(km) => km,
+                None => {
+                    let position = |name: &str| {
+                        network
+                            .node(name)
+                            .and_then(|node| network.landmark(node).position)
+                    };
+                    match (position(&road.from), position(&road.to)) {
+                        (Some(a), Some(b)) => haversine(a, b),
+                        _ => {
+                            return Err(MapError::NoDistance {
+                                from: road.from,
+                                to: road.to,
+                            });
+                        }
+                    }
+                }
+            };
+            network.add_road(&road.from, &road.to, km);
+        }
+        Ok(())
+    }
+}
+
+// [lon, lat], the GeoJSON order
+fn position(coordinates: &Value) -> Option<Position> {
+    let lon = coordinates.get(0)?.as_f64()?;
+    let lat = coordinates.get(1)?.as_f64()?;
+    Some(Position::new(lat, lon))
+}
+
+fn parse_number(line: usize, text: &str, what: &str) -> Result<f64, MapError> {
+    text.parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| parse_error(line, format!("bad {what} {text:?}")))
+}
+
+fn parse_km(line: usize, text: &str) -> Result<f64, MapError> {
+    match text.parse::<f64>() {
+        Ok(km) if km.is_finite() && km >= 0.0 => Ok(km),
+        _ => Err(parse_error(line, format!("bad distance {text:?}"))),
+    }
+}
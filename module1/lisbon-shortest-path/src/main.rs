@@ -1,7 +1,7 @@
 use clap::Parser;
-use petgraph::algo::dijkstra;
-use petgraph::prelude::*;
-use std::collections::HashMap;
+use lisbon_shortest_path::{MapData, MapError, Network, to_geojson};
+use std::path::PathBuf;
+use std::process;
 
 #[derive(Parser)]
 #[command(name = "Lisbon Path Finder")]
@@ -18,39 +18,41 @@ struct Args {
     /// Custom connections in format Foo:Bar:Distance
     #[arg(long)]
     distance: Vec<String>,
+
+    /// Landmarks or roads from a CSV or GeoJSON file instead of the Lisbon
+    /// landmarks, can be repeated
+    #[arg(short, long = "file", value_name = "FILE")]
+    files: Vec<PathBuf>,
+
+    /// Number of routes to find, the shortest and its alternatives
+    #[arg(short = 'k', long, default_value_t = 1)]
+    routes: usize,
+
+    /// Write the routes as GeoJSON LineStrings to a file
+    #[arg(long, value_name = "FILE")]
+    geojson: Option<PathBuf>,
+
+    /// Print the landmarks and roads of the network
+    #[arg(short, long)]
+    verbose: bool,
 }
 
-fn main() {
-    let args = Args::parse();
-    let mut graph = Graph::<&str, u32, Undirected>::new_undirected();
-
-    let belem_tower = graph.add_node("Belem Tower");
-    let monastery = graph.add_node("Jerónimos Monastery");
-    let lx_factory = graph.add_node("LX Factory");
-    let commerce_square = graph.add_node("Commerce Square");
-    let lisbon_cathedral = graph.add_node("Lisbon Cathedral");
-
-    graph.extend_with_edges([
-        (belem_tower, monastery, 1), // The distance from Belem Tower to Jerónimos Monastery is 1 km
-        (belem_tower, lx_factory, 3), // The distance from Belem Tower to LX Factory is 3 km
-        (belem_tower, commerce_square, 7), // The distance from Belem Tower to Commerce Square is 7 km
-        (monastery, lx_factory, 3), // The distance from Jerónimos Monastery to LX Factory is 3 km
-        (monastery, commerce_square, 6), // The distance from Jerónimos Monastery to Commerce Square is 6 km
-        (lx_factory, commerce_square, 5), // The distance from LX Factory to Commerce Square is 5 km
-        (commerce_square, lisbon_cathedral, 1), // The distance from Commerce Square to Lisbon Cathedral is 1 km
-    ]);
-
-    // Bind names to node
-    let mut name_to_node: HashMap<&str, NodeIndex> = [
-        ("Belem Tower", belem_tower),
-        ("Jerónimos Monastery", monastery),
-        ("LX Factory", lx_factory),
-        ("Commerce Square", commerce_square),
-        ("Lisbon Cathedral", lisbon_cathedral),
-    ]
-    .iter()
-    .cloned()
-    .collect();
+// Kilometres rounded to metres, without trailing zeros
+fn km(distance: f64) -> f64 {
+    (distance * 1000.0).round() / 1000.0
+}
+
+fn network(args: &Args) -> Result<Network, Box<dyn std::error::Error>> {
+    let mut network = if args.files.is_empty() {
+        Network::lisbon()
+    } else {
+        let mut data = MapData::new();
+        for path in &args.files {
+            data.load(path)
+                .map_err(|err| format!("{}: {err}", path.display()))?;
+        }
+        data.into_network()?
+    };
 
     // Add custom connections from --distance
     for entry in &args.distance {
@@ -65,58 +67,90 @@ fn main() {
 
         let from = parts[0].trim();
         let to = parts[1].trim();
-        let dist = match parts[2].trim().parse::<u32>() {
-            Ok(d) => d,
-            Err(_) => {
+        let dist = match parts[2].trim().parse::<f64>() {
+            Ok(d) if d.is_finite() && d >= 0.0 => d,
+            _ => {
                 eprintln!("Invalid distance value in '{}'", entry);
                 continue;
             }
         };
 
-        let from_node = *name_to_node
-            .entry(from)
-            .or_insert_with(|| graph.add_node(from));
-        let to_node = *name_to_node.entry(to).or_insert_with(|| graph.add_node(to));
-
-        graph.add_edge(from_node, to_node, dist);
+        network.add_road(from, to, dist);
     }
+    Ok(network)
+}
 
-    let start_name = args.start.unwrap_or_else(|| "Belem Tower".to_string());
-    let end_name = args.end.unwrap_or_else(|| "Lisbon Cathedral".to_string());
+fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let network = network(args)?;
 
-    let start_node = name_to_node.get(start_name.as_str());
-    let end_node = name_to_node.get(end_name.as_str());
+    let start_name = args.start.as_deref().unwrap_or("Belem Tower");
+    let end_name = args.end.as_deref().unwrap_or("Lisbon Cathedral");
 
-    println!("\n");
+    if args.verbose {
+        println!("Landmarks:");
+        for landmark in network.landmarks() {
+            match landmark.position {
+                Some(position) => {
+                    println!(" - {} ({}, {})", landmark.name, position.lat, position.lon)
+                }
+                None => println!(" - {}", landmark.name),
+            }
+        }
 
-    for l in name_to_node.iter() {
-        println!("Name {:?}", l);
+        println!("\nStored graph edges:");
+        for (source, target, weight) in network.roads() {
+            println!(" - {} <-> {} : {} km", source, target, km(weight));
+        }
+        println!();
     }
 
-    println!("\nStored graph edges:");
-    for edge in graph.edge_references() {
-        let source = graph[edge.source()];
-        let target = graph[edge.target()];
-        let weight = edge.weight();
-        println!(" - {} <-> {} : {} km", source, target, weight);
+    let landmark = |name: &str| {
+        network
+            .node(name)
+            .ok_or_else(|| MapError::UnknownLandmark(name.to_string()))
+    };
+    let (start, end) = (landmark(start_name)?, landmark(end_name)?);
+    let routes = network.k_shortest_routes(start, end, args.routes.max(1));
+    let Some(shortest) = routes.first() else {
+        println!("No route found from {} to {}.", start_name, end_name);
+        return Ok(());
+    };
+    // Before printing, so a landmark of the routes without coordinates is
+    // an error on its own
+    let geojson = match &args.geojson {
+        Some(path) => Some((path, to_geojson(&network, &routes)?)),
+        None => None,
+    };
+
+    println!(
+        "The shortest distance from {} to {} is {} km",
+        start_name,
+        end_name,
+        km(shortest.km)
+    );
+    println!("Route: {}", network.names(shortest).join(" -> "));
+    for (rank, route) in routes.iter().enumerate().skip(1) {
+        println!(
+            "Alternative {}: {} km, {}",
+            rank,
+            km(route.km),
+            network.names(route).join(" -> ")
+        );
+    }
+    if routes.len() < args.routes {
+        println!("No more routes without loops.");
     }
 
-    println!("\n");
-
-    match (start_node, end_node) {
-        (Some(&start), Some(&end)) => {
-            let node_map = dijkstra(&graph, start, Some(end), |e| *e.weight());
-            if let Some(distance) = node_map.get(&end) {
-                println!(
-                    "The shortest distance from {} to {} is {} km",
-                    start_name, end_name, distance
-                );
-            } else {
-                println!("No route found from {} to {}.", start_name, end_name);
-            }
-        }
-        _ => {
-            println!("No route found from {} to {}.", start_name, end_name);
-        }
+    if let Some((path, text)) = geojson {
+        std::fs::write(path, text)?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(error) = run(&args) {
+        eprintln!("Error: {error}");
+        process::exit(1);
     }
 }
@@ -0,0 +1,300 @@
+/*
+A network of landmarks joined by two-way roads, and routes through it.
+
+Routes are found with A*. Its estimate of the distance left is the
+great-circle (haversine) distance to the destination. Road lengths are
+often rounded, so a road can be a little shorter than the straight line
+between its ends. Then the plain haversine estimate would overshoot, and
+A* could miss the shortest route. So the estimate is scaled down by the
+smallest ratio of road length to straight-line length in the network.
+That keeps it below the length of every route. Without coordinates for
+every landmark the estimate is 0, and A* works like Dijkstra.
+
+k_shortest_routes finds alternatives with Yen's algorithm: each
+next-shortest route leaves an earlier one at some landmark (the spur) and
+avoids the roads the earlier routes took from there.
+*/
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::algo::astar;
+use petgraph::graph::EdgeReference;
+use petgraph::prelude::*;
+use petgraph::visit::EdgeFiltered;
+
+/// Mean radius of the Earth in kilometres
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Position {
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+}
+
+/// Great-circle distance in kilometres
+pub fn haversine(a: Position, b: Position) -> f64 {
+    let (lat_a, lat_b) = (a.lat.to_radians(), b.lat.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.lon - a.lon).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Landmark {
+    pub name: String,
+    pub position: Option<Position>,
+}
+
+/// A way through the network: its landmarks from start to end and its
+/// length
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub landmarks: Vec<NodeIndex>,
+    pub km: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Network {
+    graph: UnGraph<Landmark, f64>,
+    index: HashMap<String, NodeIndex>,
+}
+
+impl Network {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The landmarks and distances of the lab
+    pub fn lisbon() -> Self {
+        let mut network = Self::new();
+        for (name, lat, lon) in [
+            ("Belem Tower", 38.6916, -9.2160),
+            ("Jerónimos Monastery", 38.6979, -9.2068),
+            ("LX Factory", 38.7034, -9.1785),
+            ("Commerce Square", 38.7076, -9.1365),
+            ("Lisbon Cathedral", 38.7099, -9.1335),
+        ] {
+            network.add_landmark(name, Some(Position::new(lat, lon)));
+        }
+        for (from, to, km) in [
+            ("Belem Tower", "Jerónimos Monastery", 1.0),
+            ("Belem Tower", "LX Factory", 3.0),
+            ("Belem Tower", "Commerce Square", 7.0),
+            ("Jerónimos Monastery", "LX Factory", 3.0),
+            ("Jerónimos Monastery", "Commerce Square", 6.0),
+            ("LX Factory", "Commerce Square", 5.0),
+            ("Commerce Square", "Lisbon Cathedral", 1.0),
+        ] {
+            network.add_road(from, to, km);
+        }
+        network
+    }
+
+    /// The node of a landmark, added if it is new. A position given for a
+    /// known landmark replaces the old one.
+    pub fn add_landmark(&mut self, name: &str, position: Option<Position>) -> NodeIndex {
+        if let Some(&node) = self.index.get(name) {
+            if position.is_some() {
+                self.graph[node].position = position;
+            }
+            return node;
+        }
+        let node = self.graph.add_node(Landmark {
+            name: name.to_string(),
+            position,
+        });
+        self.index.insert(name.to_string(), node);
+        node
+    }
+
+    /// Add a two-way road, and its landmarks if they are new
+    pub fn add_road(&mut self, from: &str, to: &str, km: f64) -> EdgeIndex {
+        let from = self.add_landmark(from, None);
+        let to = self.add_landmark(to, None);
+        self.graph.add_edge(from, to, km)
+    }
+
+    pub fn node(&self, name: &str) -> Option<NodeIndex> {
+        self.index.get(name).copied()
+    }
+
+    pub fn landmark(&self, node: NodeIndex) -> &Landmark {
+        &self.graph[node]
+    }
+
+    pub fn landmark_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn road_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn landmarks(&self) -> impl Iterator<Item = &Landmark> {
+        self.graph.node_weights()
+    }
+
+    /// Every road as (from, to, km)
+    pub fn roads(&self) -> impl Iterator<Item = (&str, &str, f64)> {
+        self.graph.edge_references().map(|road| {
+            (
+                self.graph[road.source()].name.as_str(),
+                self.graph[road.target()].name.as_str(),
+                *road.weight(),
+            )
+        })
+    }
+
+    /// The names of the landmarks of a route
+    pub fn names(&self, route: &Route) -> Vec<&str> {
+        route
+            .landmarks
+            .iter()
+            .map(|&node| self.graph[node].name.as_str())
+            .collect()
+    }
+
+    /// The shortest route, found with A*
+    pub fn shortest_route(&self, from: NodeIndex, to: NodeIndex) -> Option<Route> {
+        self.search(
+            from,
+            to,
+            self.estimate_scale(),
+            &HashSet::new(),
+            &HashSet::new(),
+        )
+    }
+
+    /// Up to `k` shortest routes without loops, shortest first (Yen's
+    /// algorithm)
+    pub fn k_shortest_routes(&self, from: NodeIndex, to: NodeIndex, k: usize) -> Vec<Route> {
+        let scale = self.estimate_scale();
+        let mut routes: Vec<Route> = Vec::new();
+        if k == 0 {
+            return routes;
+        }
+        let Some(shortest) = self.search(from, to, scale, &HashSet::new(), &HashSet::new()) else {
+            return routes;
+        };
+        routes.push(shortest);
+        let mut candidates: Vec<Route> = Vec::new();
+
+        while routes.len() < k {
+            let last = &routes[routes.len() - 1].landmarks;
+            for spur in 0..last.len() - 1 {
+                let root = &last[..=spur];
+                // Leave the roads the routes found so far take after this root
+                let closed_roads: HashSet<(NodeIndex, NodeIndex)> = routes
+                    .iter()
+                    .filter(|route| route.landmarks.len() > spur + 1)
+                    .filter(|route| route.landmarks[..=spur] == *root)
+                    .map(|route| (route.landmarks[spur], route.landmarks[spur + 1]))
+                    .collect();
+                // and do not go back through the root, which would be a loop
+                let closed_landmarks: HashSet<NodeIndex> = root[..spur].iter().copied().collect();
+
+                let Some(rest) =
+                    self.search(root[spur], to, scale, &closed_landmarks, &closed_roads)
+                else {
+                    continue;
+                };
+                let mut landmarks = root[..spur].to_vec();
+                landmarks.extend(rest.landmarks);
+                let candidate = Route {
+                    km: self.length(&landmarks),
+                    landmarks,
+                };
+                let known = |route: &Route| route.landmarks == candidate.landmarks;
+                if !routes.iter().any(known) && !candidates.iter().any(known) {
+                    candidates.push(candidate);
+                }
+            }
+
+            // The shortest candidate is the next route
+            let Some(next) = (0..candidates.len()).min_by(|&a, &b| {
+                candidates[a]
+                    .km
+                    .total_cmp(&candidates[b].km)
+                    .then_with(|| candidates[a].landmarks.cmp(&candidates[b].landmarks))
+            }) else {
+                break;
+            };
+            routes.push(candidates.swap_remove(next));
+        }
+        routes
+    }
+
+    // A* without some landmarks and roads
+    fn search(
+        &self,
+        from: NodeIndex,
+        to: NodeIndex,
+        scale: f64,
+        closed_landmarks: &HashSet<NodeIndex>,
+        closed_roads: &HashSet<(NodeIndex, NodeIndex)>,
+    ) -> Option<Route> {
+        let open = EdgeFiltered::from_fn(&self.graph, |road: EdgeReference<f64>| {
+            let (a, b) = (road.source(), road.target());
+            !closed_landmarks.contains(&a)
+                && !closed_landmarks.contains(&b)
+                && !closed_roads.contains(&(a, b))
+                && !closed_roads.contains(&(b, a))
+        });
+        let goal = self.graph[to].position;
+        let (km, landmarks) = astar(
+            &open,
+            from,
+            |node| node == to,
+            |road| *road.weight(),
+            |node| match (self.graph[node].position, goal) {
+                (Some(here), Some(goal)) => scale * haversine(here, goal),
+                _ => 0.0,
+            },
+        )?;
+        Some(Route { landmarks, km })
+    }
+
+    // The factor that keeps the haversine estimate of A* below every road
+    // length, 0 when some landmark has no position
+    fn estimate_scale(&self) -> f64 {
+        let Some(positions) = self
+            .graph
+            .node_weights()
+            .map(|landmark| landmark.position)
+            .collect::<Option<Vec<Position>>>()
+        else {
+            return 0.0;
+        };
+        let mut scale: f64 = 1.0;
+        for road in self.graph.edge_references() {
+            let straight = haversine(
+                positions[road.source().index()],
+                positions[road.target().index()],
+            );
+            if straight > 0.0 {
+                scale = scale.min(road.weight() / straight);
+            }
+        }
+        scale
+    }
+
+    // Length of the shortest roads between the landmarks of a route
+    fn length(&self, landmarks: &[NodeIndex]) -> f64 {
+        landmarks
+            .windows(2)
+            .map(|pair| {
+                self.graph
+                    .edges_connecting(pair[0], pair[1])
+                    .map(|road| *road.weight())
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .sum()
+    }
+}
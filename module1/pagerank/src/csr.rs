@@ -0,0 +1,141 @@
+/*
+A graph in compressed sparse row (CSR) form for the PageRank iteration.
+
+The edges are grouped by target: the sources of the edges into node i are
+sources[offsets[i]..offsets[i + 1]], with their weights at the same
+positions in weights. Each node then pulls its new rank from its incoming
+edges, so the nodes can be updated in parallel without any locking.
+
+A node passes its rank on in proportion to the weights of its edges: an
+edge weighing 2 carries twice as much as one weighing 1. Unweighted
+graphs have all weights at 1.
+*/
+
+use clap::ValueEnum;
+
+use crate::graph::Graph;
+
+/// What to do with edges from a node to itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SelfLoops {
+    /// A self-loop is a link like any other: part of the node's rank
+    /// flows back to it
+    #[default]
+    Keep,
+    /// Self-loops are left out, a node with only self-loops is dangling
+    Remove,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Csr {
+    offsets: Vec<usize>,
+    sources: Vec<usize>,
+    weights: Vec<f64>,
+    out_weight: Vec<f64>,
+    dangling: Vec<usize>,
+}
+
+impl Csr {
+    /// Build from (source, target, weight) edges between `node_count` nodes.
+    /// An edge given twice counts twice.
+    pub fn from_edges<I>(node_count: usize, edges: I, self_loops: SelfLoops) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, f64)>,
+    {
+        let edges: Vec<(usize, usize, f64)> = edges
+            .into_iter()
+            .filter(|(from, to, _)| self_loops == SelfLoops::Keep || from != to)
+            .collect();
+
+        let mut out_weight = vec![0.0; node_count];
+        let mut offsets = vec![0; node_count + 1];
+        for &(from, to, weight) in &edges {
+            out_weight[from] += weight;
+            offsets[to + 1] += 1;
+        }
+        for node in 0..node_count {
+            offsets[node + 1] += offsets[node];
+        }
+
+        // Counting sort of the sources by target
+        let mut next = offsets.clone();
+        let mut sources = vec![0; edges.len()];
+        let mut weights = vec![0.0; edges.len()];
+        for (from, to, weight) in edges {
+            sources[next[to]] = from;
+            weights[next[to]] = weight;
+            next[to] += 1;
+        }
+
+        let dangling = (0..node_count)
+            .filter(|&node| out_weight[node] == 0.0)
+            .collect();
+        Self {
+            offsets,
+            sources,
+            weights,
+            out_weight,
+            dangling,
+        }
+    }
+
+    /// Build from the targets of the outgoing edges of each node, all
+    /// weighing 1
+    pub fn from_adjacency(adjacency: &[Vec<usize>], self_loops: SelfLoops) -> Self {
+        let edges = adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(from, targets)| targets.iter().map(move |&to| (from, to, 1.0)));
+        Self::from_edges(adjacency.len(), edges, self_loops)
+    }
+
+    pub fn from_graph(graph: &Graph, self_loops: SelfLoops) -> Self {
+        let edges = (0..graph.node_count()).flat_map(|from| {
+            graph
+                .edges(from)
+                .iter()
+                .map(move |&(to, weight)| (from, to, weight))
+        });
+        Self::from_edges(graph.node_count(), edges, self_loops)
+    }
+
+    /// The same graph with every edge turned around, so incoming() gives
+    /// the targets of the outgoing edges
+    pub fn reversed(&self) -> Self {
+        let edges = (0..self.node_count()).flat_map(|to| {
+            self.incoming(to)
+                .iter()
+                .zip(self.incoming_weights(to))
+                .map(move |(&from, &weight)| (to, from, weight))
+        });
+        Self::from_edges(self.node_count(), edges, SelfLoops::Keep)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.out_weight.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Sources of the edges into a node
+    pub fn incoming(&self, node: usize) -> &[usize] {
+        &self.sources[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// Weights of the edges into a node, in the order of incoming()
+    pub fn incoming_weights(&self, node: usize) -> &[f64] {
+        &self.weights[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// Total weight of the edges out of a node
+    pub fn out_weight(&self, node: usize) -> f64 {
+        self.out_weight[node]
+    }
+
+    /// Nodes without outgoing edges, or only edges weighing 0
+    pub fn dangling(&self) -> &[usize] {
+        &self.dangling
+    }
+}
@@ -0,0 +1,124 @@
+/*
+PageRank results, sorted from the highest rank down, as text, CSV or
+JSON. Nodes with the same rank are in name order. HITS results are
+sorted by authority, then hub score.
+*/
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::graph::Graph;
+use crate::hits::HitsScores;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Csv,
+    Json,
+}
+
+/// One node of the results
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Ranked<'a> {
+    /// 1 for the highest rank
+    pub position: usize,
+    pub node: &'a str,
+    pub rank: f64,
+}
+
+/// One node of the HITS results
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HitsRanked<'a> {
+    pub position: usize,
+    pub node: &'a str,
+    pub authority: f64,
+    pub hub: f64,
+}
+
+// Node indexes by `first` then `second`, both descending, then by name
+fn order(graph: &Graph, first: &[f64], second: &[f64], top: Option<usize>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..first.len()).collect();
+    order.sort_by(|a, b| {
+        first[*b]
+            .total_cmp(&first[*a])
+            .then_with(|| second[*b].total_cmp(&second[*a]))
+            .then_with(|| graph.name(*a).cmp(graph.name(*b)))
+    });
+    order.truncate(top.unwrap_or(usize::MAX));
+    order
+}
+
+/// The nodes of a graph with their ranks, highest first, at most `top`
+pub fn ranking<'a>(graph: &'a Graph, ranks: &[f64], top: Option<usize>) -> Vec<Ranked<'a>> {
+    order(graph, ranks, ranks, top)
+        .into_iter()
+        .enumerate()
+        .map(|(position, node)| Ranked {
+            position: position + 1,
+            node: graph.name(node),
+            rank: ranks[node],
+        })
+        .collect()
+}
+
+/// "The PageRank of A is 0.42" lines, the ranks multiplied by `scale`
+pub fn to_text(ranking: &[Ranked], scale: f64) -> String {
+    ranking
+        .iter()
+        .map(|ranked| {
+            format!(
+                "The PageRank of {} is {}\n",
+                ranked.node,
+                ranked.rank * scale
+            )
+        })
+        .collect()
+}
+
+/// The nodes of a graph by authority, highest first, at most `top`
+pub fn hits_ranking<'a>(
+    graph: &'a Graph,
+    scores: &HitsScores,
+    top: Option<usize>,
+) -> Vec<HitsRanked<'a>> {
+    order(graph, &scores.authorities, &scores.hubs, top)
+        .into_iter()
+        .enumerate()
+        .map(|(position, node)| HitsRanked {
+            position: position + 1,
+            node: graph.name(node),
+            authority: scores.authorities[node],
+            hub: scores.hubs[node],
+        })
+        .collect()
+}
+
+/// "The authority of A is 0.42 and its hub score 0.1" lines, the scores
+/// multiplied by `scale`
+pub fn hits_to_text(ranking: &[HitsRanked], scale: f64) -> String {
+    ranking
+        .iter()
+        .map(|ranked| {
+            format!(
+                "The authority of {} is {} and its hub score {}\n",
+                ranked.node,
+                ranked.authority * scale,
+                ranked.hub * scale
+            )
+        })
+        .collect()
+}
+
+pub fn to_csv<T: Serialize>(ranking: &[T]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for ranked in ranking {
+        writer.serialize(ranked).expect("writing to a Vec");
+    }
+    let bytes = writer.into_inner().expect("flushing a Vec");
+    String::from_utf8(bytes).expect("CSV of strings is UTF-8")
+}
+
+pub fn to_json<T: Serialize>(ranking: &[T]) -> String {
+    serde_json::to_string_pretty(ranking).expect("ranks serialize to JSON") + "\n"
+}
@@ -0,0 +1,99 @@
+/*
+A directed graph with named nodes.
+
+Node names are interned: each name gets the next index the first time it
+is seen, and the edges refer to nodes by index. That keeps a graph with
+millions of edges at two integers and a weight per edge, however long
+the names are.
+*/
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Graph {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    // Outgoing edges of each node: (target, weight)
+    edges: Vec<Vec<(usize, f64)>>,
+    edge_count: usize,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The index of a node, added if it is new
+    pub fn add_node(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.index.get(name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), index);
+        self.edges.push(Vec::new());
+        index
+    }
+
+    /// Add an edge by node index. Both nodes must exist.
+    pub fn add_edge_by_index(&mut self, from: usize, to: usize, weight: f64) {
+        assert!(to < self.names.len(), "unknown node {to}");
+        self.edges[from].push((to, weight));
+        self.edge_count += 1;
+    }
+
+    /// Add an edge by node name, adding the nodes that are new
+    pub fn add_edge(&mut self, from: &str, to: &str, weight: f64) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.add_edge_by_index(from, to, weight);
+    }
+
+    /// Remove an edge by node name, the first one if it was added more than
+    /// once. The nodes stay. Returns its weight, or None if there is no such
+    /// edge.
+    pub fn remove_edge(&mut self, from: &str, to: &str) -> Option<f64> {
+        let from = self.index(from)?;
+        let to = self.index(to)?;
+        let position = self.edges[from]
+            .iter()
+            .position(|(target, _)| *target == to)?;
+        let (_, weight) = self.edges[from].remove(position);
+        self.edge_count -= 1;
+        Some(weight)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Outgoing edges of a node as (target, weight)
+    pub fn edges(&self, node: usize) -> &[(usize, f64)] {
+        &self.edges[node]
+    }
+
+    /// Targets of the outgoing edges of each node, the input of
+    /// PageRank::rank. An edge given twice is listed twice.
+    pub fn adjacency(&self) -> Vec<Vec<usize>> {
+        self.edges
+            .iter()
+            .map(|edges| edges.iter().map(|(to, _)| *to).collect())
+            .collect()
+    }
+}
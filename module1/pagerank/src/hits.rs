@@ -0,0 +1,116 @@
+/*
+HITS (hyperlink-induced topic search), the sibling of PageRank.
+
+HITS gives every node two scores. A good hub links to good authorities,
+and a good authority is linked from good hubs. Each iteration sets
+authority(i) to the weighted sum of the hub scores of the nodes linking
+to i. Then it sets hub(j) to the weighted sum of the authority scores of
+the nodes j links to. Both are scaled to sum to 1 each time. Like
+PageRank it stops when the scores change by less than the tolerance.
+*/
+
+use rayon::prelude::*;
+
+use crate::csr::{Csr, SelfLoops};
+use crate::graph::Graph;
+use crate::pagerank::{DEFAULT_TOLERANCE, MIN_CHUNK};
+
+/// Hub and authority scores, each summing to 1 (or all 0 without edges)
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitsScores {
+    pub hubs: Vec<f64>,
+    pub authorities: Vec<f64>,
+    pub iterations: usize,
+    /// L1 norm of the change of both scores in the last iteration
+    pub residual: f64,
+    pub converged: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hits {
+    pub iterations: usize,
+    pub tolerance: f64,
+    pub self_loops: SelfLoops,
+}
+
+impl Hits {
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            tolerance: DEFAULT_TOLERANCE,
+            self_loops: SelfLoops::default(),
+        }
+    }
+
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn self_loops(mut self, self_loops: SelfLoops) -> Self {
+        self.self_loops = self_loops;
+        self
+    }
+
+    pub fn run(&self, graph: &Graph) -> HitsScores {
+        self.run_csr(&Csr::from_graph(graph, self.self_loops))
+    }
+
+    pub fn run_csr(&self, csr: &Csr) -> HitsScores {
+        let n = csr.node_count();
+        let outgoing = csr.reversed();
+        let mut hubs = vec![1.0 / n as f64; n];
+        let mut authorities = vec![1.0 / n as f64; n];
+        let mut iterations = 0;
+        let mut residual = if n == 0 { 0.0 } else { f64::INFINITY };
+
+        while iterations < self.iterations && residual >= self.tolerance {
+            let next_authorities = normalized(pull(csr, &hubs));
+            let next_hubs = normalized(pull(&outgoing, &next_authorities));
+            residual = distance(&next_hubs, &hubs) + distance(&next_authorities, &authorities);
+            hubs = next_hubs;
+            authorities = next_authorities;
+            iterations += 1;
+        }
+
+        HitsScores {
+            hubs,
+            authorities,
+            iterations,
+            residual,
+            converged: residual < self.tolerance,
+        }
+    }
+}
+
+// For each node the weighted sum of `scores` over its incoming edges
+fn pull(csr: &Csr, scores: &[f64]) -> Vec<f64> {
+    (0..csr.node_count())
+        .into_par_iter()
+        .with_min_len(MIN_CHUNK)
+        .map(|node| {
+            csr.incoming(node)
+                .iter()
+                .zip(csr.incoming_weights(node))
+                .map(|(&from, weight)| scores[from] * weight)
+                // Not sum(), which gives -0 for a node without edges
+                .fold(0.0, |total, score| total + score)
+        })
+        .collect()
+}
+
+fn normalized(mut scores: Vec<f64>) -> Vec<f64> {
+    let sum: f64 = scores.par_iter().sum();
+    if sum > 0.0 {
+        scores.par_iter_mut().for_each(|score| *score /= sum);
+    }
+    scores
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.par_iter()
+        .with_min_len(MIN_CHUNK)
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .sum()
+}
@@ -0,0 +1,591 @@
+/*
+PageRank over graphs with named nodes.
+
+A Graph interns node names to indexes, so large link graphs stay compact.
+Graphs are read from edge lists, CSV, TSV, GraphML, GML or Graphviz DOT
+files, ranked with PageRank and written out sorted by rank as text, CSV
+or JSON. PageRank runs on a compressed sparse row copy of the graph (Csr)
+until the ranks stop changing, in parallel with rayon. Edge weights are
+followed, and the variants are personalized PageRank (a teleport vector
+on seed nodes), topic-sensitive PageRank and HITS. IncrementalPageRank
+keeps the ranks up to date while edges are added and removed.
+*/
+
+pub mod csr;
+pub mod export;
+pub mod graph;
+pub mod hits;
+pub mod incremental;
+pub mod load;
+pub mod pagerank;
+pub mod teleport;
+
+pub use csr::{Csr, SelfLoops};
+pub use export::{
+    HitsRanked, OutputFormat, Ranked, hits_ranking, hits_to_text, ranking, to_csv, to_json, to_text,
+};
+pub use graph::Graph;
+pub use hits::{Hits, HitsScores};
+pub use incremental::IncrementalPageRank;
+pub use load::{InputFormat, LoadError, load, load_into, read_into};
+pub use pagerank::{DEFAULT_TOLERANCE, Dangling, PageRank, Ranks, TopicRanks};
+pub use teleport::{Teleport, load_topics, read_topics};
+
+/// The graph of the lab: links between sports websites
+pub fn sports_graph() -> Graph {
+    let mut graph = Graph::new();
+    for name in ["ESPN", "NFL", "NBA", "UFC", "MLB"] {
+        graph.add_node(name);
+    }
+    for (from, to) in [
+        ("ESPN", "NFL"),
+        ("ESPN", "NBA"),
+        ("NFL", "ESPN"),
+        ("NBA", "ESPN"),
+        ("NBA", "UFC"),
+        ("UFC", "ESPN"),
+        ("MLB", "ESPN"),
+        ("MLB", "NFL"),
+    ] {
+        graph.add_edge(from, to, 1.0);
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str, format: InputFormat) -> Graph {
+        let mut graph = Graph::new();
+        read_into(&mut graph, text.as_bytes(), format).unwrap();
+        graph
+    }
+
+    fn edges(graph: &Graph) -> Vec<(String, String, f64)> {
+        let mut edges = Vec::new();
+        for from in 0..graph.node_count() {
+            for (to, weight) in graph.edges(from) {
+                edges.push((
+                    graph.name(from).to_string(),
+                    graph.name(*to).to_string(),
+                    *weight,
+                ));
+            }
+        }
+        edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        edges
+    }
+
+    fn edge(from: &str, to: &str, weight: f64) -> (String, String, f64) {
+        (from.to_string(), to.to_string(), weight)
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", 1.0);
+        graph.add_edge("b", "a", 1.0);
+        graph.add_edge("a", "c", 2.0);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.index("c"), Some(2));
+        assert_eq!(graph.add_node("a"), 0);
+        assert_eq!(graph.adjacency(), [vec![1, 2], vec![0], vec![]]);
+    }
+
+    #[test]
+    fn test_sports_graph_ranks() {
+        let graph = sports_graph();
+        let ranks = PageRank::new(0.85, 100).rank(&graph);
+        let ranking = ranking(&graph, &ranks.values, Some(2));
+        assert_eq!(ranking[0].node, "ESPN");
+        assert!((ranking[0].rank - 0.4208).abs() < 1e-4);
+        assert_eq!((ranking[1].position, ranking[1].node), (2, "NFL"));
+        assert_eq!(ranking.len(), 2);
+    }
+
+    #[test]
+    fn test_edge_list_csv_tsv() {
+        let graph = read(
+            "# comment\nsource target\na b 2.5\nb c\n\nc a\n",
+            InputFormat::EdgeList,
+        );
+        assert_eq!(
+            edges(&graph),
+            [
+                edge("a", "b", 2.5),
+                edge("b", "c", 1.0),
+                edge("c", "a", 1.0)
+            ]
+        );
+
+        let graph = read(
+            "from,to,weight\n\"New York\",Boston,3\nBoston,\"New York\",\n",
+            InputFormat::Csv,
+        );
+        assert_eq!(
+            edges(&graph),
+            [
+                edge("Boston", "New York", 1.0),
+                edge("New York", "Boston", 3.0)
+            ]
+        );
+
+        let graph = read("New York\tBoston\t0.5\n", InputFormat::Tsv);
+        assert_eq!(edges(&graph), [edge("New York", "Boston", 0.5)]);
+
+        let mut graph = Graph::new();
+        let error =
+            read_into(&mut graph, "a b heavy\n".as_bytes(), InputFormat::EdgeList).unwrap_err();
+        assert_eq!(error.to_string(), "line 1: bad edge weight \"heavy\"");
+    }
+
+    #[test]
+    fn test_graphml() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+  <key id="d1" for="edge" attr.name="weight" attr.type="double"/>
+  <graph id="G" edgedefault="directed">
+    <node id="n0"><data key="d0">ESPN</data></node>
+    <node id="n1"><data key="d0">NFL</data></node>
+    <node id="n2"/>
+    <edge source="n0" target="n1"><data key="d1">2.0</data></edge>
+    <edge source="n1" target="n2"/>
+    <edge source="n2" target="n0" directed="false"/>
+  </graph>
+</graphml>"#;
+        let graph = read(text, InputFormat::GraphMl);
+        assert_eq!(graph.names(), ["ESPN", "NFL", "n2"]);
+        assert_eq!(
+            edges(&graph),
+            [
+                edge("ESPN", "NFL", 2.0),
+                edge("ESPN", "n2", 1.0),
+                edge("NFL", "n2", 1.0),
+                edge("n2", "ESPN", 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gml() {
+        let text = r#"
+# An undirected graph unless it says otherwise
+graph [
+  directed 1
+  node [ id 1 label "ESPN" ]
+  node [ id 2 label "NFL" ]
+  node [ id 3 ]
+  edge [ source 1 target 2 weight 0.5 ]
+  edge [ source 2 target 3 ]
+]"#;
+        let graph = read(text, InputFormat::Gml);
+        assert_eq!(graph.names(), ["ESPN", "NFL", "3"]);
+        assert_eq!(
+            edges(&graph),
+            [edge("ESPN", "NFL", 0.5), edge("NFL", "3", 1.0)]
+        );
+
+        let undirected = read(
+            "graph [ node [ id 1 ] node [ id 2 ] edge [ source 1 target 2 ] ]",
+            InputFormat::Gml,
+        );
+        assert_eq!(undirected.edge_count(), 2);
+    }
+
+    #[test]
+    fn test_gml_label_same_as_id() {
+        let text = r#"graph [
+  directed 1
+  node [ id 1 label "2" ]
+  node [ id 2 ]
+  node [ id 3 label "2" ]
+  node [ id 4 label "four" ]
+  node [ id 5 label "four" ]
+  edge [ source 1 target 2 ]
+  edge [ source 3 target 4 ]
+]"#;
+        let graph = read(text, InputFormat::Gml);
+        assert_eq!(graph.names(), ["1", "2", "3", "four", "5"]);
+        assert_eq!(edges(&graph), [edge("1", "2", 1.0), edge("3", "four", 1.0)]);
+    }
+
+    #[test]
+    fn test_graphml_label_same_as_id() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+    <node id="a"><data key="d0">b</data></node>
+    <node id="b"/>
+    <node id="c"><data key="d0">c</data></node>
+    <edge source="a" target="b"/>
+    <edge source="b" target="c"/>
+  </graph>
+</graphml>"#;
+        let graph = read(text, InputFormat::GraphMl);
+        assert_eq!(graph.names(), ["a", "b", "c"]);
+        assert_eq!(edges(&graph), [edge("a", "b", 1.0), edge("b", "c", 1.0)]);
+    }
+
+    #[test]
+    fn test_dot() {
+        let text = r#"
+// Sports sites
+digraph "sports links" {
+    node [shape=box];
+    rankdir = LR;
+    ESPN -> NFL -> "New York" [weight=2, color="red"];
+    /* a subgraph as the end of an edge */
+    MLB:port -> { ESPN NFL };
+    UFC;
+}"#;
+        let graph = read(text, InputFormat::Dot);
+        assert_eq!(graph.names(), ["ESPN", "NFL", "New York", "MLB", "UFC"]);
+        assert_eq!(
+            edges(&graph),
+            [
+                edge("ESPN", "NFL", 2.0),
+                edge("MLB", "ESPN", 1.0),
+                edge("MLB", "NFL", 1.0),
+                edge("NFL", "New York", 2.0)
+            ]
+        );
+
+        let undirected = read("graph { a--b; b -- c }", InputFormat::Dot);
+        assert_eq!(undirected.edge_count(), 4);
+
+        let mut graph = Graph::new();
+        let error = read_into(
+            &mut graph,
+            "digraph { a -- b }".as_bytes(),
+            InputFormat::Dot,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected ->");
+    }
+
+    #[test]
+    fn test_export() {
+        let graph = sports_graph();
+        let ranks = [0.1, 0.4, 0.4, 0.05, 0.05];
+        let ranking = ranking(&graph, &ranks, Some(3));
+        assert_eq!(
+            to_csv(&ranking),
+            "position,node,rank\n1,NBA,0.4\n2,NFL,0.4\n3,ESPN,0.1\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&to_json(&ranking)).unwrap();
+        assert_eq!(json[0]["node"], "NBA");
+        assert_eq!(json[2]["position"], 3);
+        assert_eq!(to_text(&ranking[..1], 100.0), "The PageRank of NBA is 40\n");
+    }
+
+    // A graph with `n` nodes and some pseudo-random links, every `dangle`th
+    // node without any
+    fn scrambled(n: usize, dangle: usize) -> Vec<Vec<usize>> {
+        let mut seed: u64 = 42;
+        let mut next = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+        (0..n)
+            .map(|node| {
+                if node % dangle == 0 {
+                    return Vec::new();
+                }
+                let degree = 1 + next() % 4;
+                (0..degree).map(|_| next() % n).collect()
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+        assert_eq!(actual.len(), expected.len());
+        for (node, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!((a - e).abs() < tolerance, "node {node}: {a} != {e}");
+        }
+    }
+
+    #[test]
+    fn test_csr() {
+        let csr = Csr::from_adjacency(&[vec![1, 2, 2], vec![1], vec![]], SelfLoops::Keep);
+        assert_eq!((csr.node_count(), csr.edge_count()), (3, 4));
+        assert_eq!(csr.incoming(1), [0, 1]);
+        assert_eq!(csr.incoming(2), [0, 0]);
+        assert_eq!(csr.out_weight(0), 3.0);
+        assert_eq!(csr.dangling(), [2]);
+
+        let csr = Csr::from_adjacency(&[vec![1, 2, 2], vec![1], vec![]], SelfLoops::Remove);
+        assert_eq!(csr.incoming(1), [0]);
+        assert_eq!(csr.dangling(), [1, 2]);
+    }
+
+    #[test]
+    fn test_matches_dense_reference() {
+        let graphs = [
+            sports_graph().adjacency(),
+            // Dangling nodes, a self-loop and a duplicate link
+            vec![vec![1, 1], vec![2], vec![], vec![3, 0], vec![]],
+            // Large enough for rayon to split the work
+            scrambled(5000, 7),
+        ];
+        for adjacency in &graphs {
+            let expected = PageRank::new(0.85, 500).rank_dense(adjacency);
+            let csr = Csr::from_adjacency(adjacency, SelfLoops::Keep);
+            let ranks = PageRank::new(0.85, 500)
+                .tolerance(1e-14)
+                .dangling(Dangling::Drop)
+                .rank_csr(&csr);
+            assert_close(&ranks.values, &expected, 1e-10);
+        }
+
+        // Without dangling nodes, where the rank goes makes no difference
+        let adjacency = sports_graph().adjacency();
+        let expected = PageRank::new(0.85, 500).rank_dense(&adjacency);
+        let csr = Csr::from_adjacency(&adjacency, SelfLoops::Keep);
+        let ranks = PageRank::new(0.85, 500).tolerance(1e-14).rank_csr(&csr);
+        assert_close(&ranks.values, &expected, 1e-10);
+    }
+
+    #[test]
+    fn test_dangling_uniform() {
+        // b links nowhere, so its rank is spread over a and b
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", 1.0);
+        let ranks = PageRank::new(0.85, 1000).tolerance(1e-14).rank(&graph);
+        let b = 0.925 / 1.425;
+        assert_close(&ranks.values, &[1.0 - b, b], 1e-12);
+
+        let csr = Csr::from_adjacency(&scrambled(500, 3), SelfLoops::Keep);
+        let ranks = PageRank::new(0.85, 1000).rank_csr(&csr);
+        assert!((ranks.values.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_self_loops() {
+        let mut graph = sports_graph();
+        graph.add_edge("NFL", "NFL", 1.0);
+        graph.add_edge("MLB", "MLB", 1.0);
+        let removed = PageRank::new(0.85, 100)
+            .self_loops(SelfLoops::Remove)
+            .rank(&graph);
+        let plain = PageRank::new(0.85, 100).rank(&sports_graph());
+        assert_eq!(removed.values, plain.values);
+
+        // A kept self-loop returns part of the rank to the node
+        let kept = PageRank::new(0.85, 100).rank(&graph);
+        assert!(kept.values[1] > plain.values[1]);
+    }
+
+    #[test]
+    fn test_convergence_report() {
+        let graph = sports_graph();
+        let ranks = PageRank::new(0.85, 1000).tolerance(1e-9).rank(&graph);
+        assert!(ranks.converged);
+        assert!(ranks.residual < 1e-9);
+        assert!(ranks.iterations > 3 && ranks.iterations < 1000);
+
+        let ranks = PageRank::new(0.85, 3).tolerance(1e-9).rank(&graph);
+        assert!(!ranks.converged);
+        assert_eq!(ranks.iterations, 3);
+        assert!(ranks.residual > 1e-9);
+
+        let empty = PageRank::new(0.85, 100).rank(&Graph::new());
+        assert!(empty.values.is_empty() && empty.converged);
+    }
+
+    #[test]
+    fn test_weighted() {
+        // An edge weighing 3 carries as much as three edges weighing 1
+        let mut weighted = Graph::new();
+        weighted.add_edge("a", "b", 3.0);
+        weighted.add_edge("a", "c", 1.0);
+        weighted.add_edge("b", "a", 0.5);
+        weighted.add_edge("c", "a", 2.0);
+        let adjacency = [vec![1, 1, 1, 2], vec![0], vec![0]];
+        let pagerank = PageRank::new(0.85, 1000).tolerance(1e-14);
+        let expected = pagerank.rank_csr(&Csr::from_adjacency(&adjacency, SelfLoops::Keep));
+        assert_close(&pagerank.rank(&weighted).values, &expected.values, 1e-12);
+    }
+
+    #[test]
+    fn test_personalized() {
+        let mut graph = sports_graph();
+        graph.add_edge("Chess", "Go", 1.0);
+        let pagerank = PageRank::new(0.85, 1000).tolerance(1e-12);
+
+        let uniform = Teleport::uniform(graph.node_count());
+        assert_close(
+            &pagerank.personalized(&graph, &uniform).values,
+            &pagerank.rank(&graph).values,
+            1e-12,
+        );
+
+        // Nothing reachable from UFC links to MLB or the chess sites
+        let seeds = Teleport::from_nodes(&graph, &["UFC"]).unwrap();
+        let ranks = pagerank.personalized(&graph, &seeds).values;
+        for node in ["MLB", "Chess", "Go"] {
+            assert_eq!(ranks[graph.index(node).unwrap()], 0.0, "{node}");
+        }
+        assert!(ranks[graph.index("ESPN").unwrap()] > ranks[graph.index("NFL").unwrap()]);
+
+        let teleport = Teleport::read(&graph, "# seeds\nUFC 3\nChess\n".as_bytes()).unwrap();
+        assert_eq!(teleport.weights()[graph.index("UFC").unwrap()], 0.75);
+        assert_eq!(teleport.weights()[graph.index("Chess").unwrap()], 0.25);
+
+        let error = Teleport::from_nodes(&graph, &["FIFA"]).unwrap_err();
+        assert_eq!(error.to_string(), "unknown node \"FIFA\"");
+        let error = Teleport::read(&graph, "UFC 0\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "the teleport weights sum to 0");
+    }
+
+    #[test]
+    fn test_topic_sensitive() {
+        let graph = sports_graph();
+        let topics = read_topics(
+            &graph,
+            "football NFL\nfighting UFC 2\nfootball ESPN\n".as_bytes(),
+        )
+        .unwrap();
+        let pagerank = PageRank::new(0.85, 1000).tolerance(1e-12);
+        let ranks = pagerank.topic_sensitive(&graph, &topics);
+        assert_eq!(ranks.topics, ["football", "fighting"]);
+        assert_eq!(ranks.index("fighting"), Some(1));
+
+        let football = Teleport::from_nodes(&graph, &["NFL", "ESPN"]).unwrap();
+        assert_eq!(ranks.ranks[0], pagerank.personalized(&graph, &football));
+        assert_eq!(ranks.blend(&[1.0, 0.0]), ranks.ranks[0].values);
+
+        let blended = ranks.blend(&[2.0, 2.0]);
+        for (node, rank) in blended.iter().enumerate() {
+            let mean = (ranks.ranks[0].values[node] + ranks.ranks[1].values[node]) / 2.0;
+            assert!((rank - mean).abs() < 1e-15);
+        }
+    }
+
+    #[test]
+    fn test_hits() {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "c", 1.0);
+        graph.add_edge("b", "c", 1.0);
+        graph.add_edge("b", "d", 1.0);
+        let scores = Hits::new(1000).tolerance(1e-12).run(&graph);
+        assert!(scores.converged);
+        // b links to both authorities, c is linked from both hubs
+        let ranking = hits_ranking(&graph, &scores, None);
+        assert_eq!(ranking[0].node, "c");
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| graph.index(name).unwrap());
+        assert!(scores.hubs[b] > scores.hubs[a] && scores.hubs[a] > 0.0);
+        assert_eq!((scores.hubs[c], scores.authorities[a]), (0.0, 0.0));
+        let sum: f64 = scores.authorities.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-12);
+
+        // The leading eigenvectors of A^T A and A A^T, golden ratio apart
+        let golden = (1.0 + 5f64.sqrt()) / 2.0;
+        assert!((scores.authorities[c] / scores.authorities[d] - golden).abs() < 1e-9);
+        assert!((scores.hubs[b] / scores.hubs[a] - golden).abs() < 1e-9);
+
+        let csv = to_csv(&ranking[..1]);
+        assert!(csv.starts_with("position,node,authority,hub\n1,c,"));
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut graph = sports_graph();
+        assert_eq!(graph.remove_edge("ESPN", "NBA"), Some(1.0));
+        assert_eq!(graph.remove_edge("ESPN", "NBA"), None);
+        assert_eq!(graph.remove_edge("ESPN", "FIFA"), None);
+        assert_eq!(graph.edge_count(), 7);
+        assert_eq!(graph.adjacency()[0], [1]);
+    }
+
+    #[test]
+    fn test_incremental_matches_full_recompute() {
+        let adjacency = scrambled(3000, 5);
+        let mut graph = Graph::new();
+        for node in 0..adjacency.len() {
+            graph.add_node(&node.to_string());
+        }
+        for (from, targets) in adjacency.iter().enumerate() {
+            for to in targets {
+                graph.add_edge(&from.to_string(), &to.to_string(), 1.0);
+            }
+        }
+        let pagerank = PageRank::new(0.85, 1000).tolerance(1e-10);
+        let mut incremental = IncrementalPageRank::new(pagerank, graph);
+        let cold = incremental.ranks().iterations;
+
+        let batches: [&[(&str, &str, bool)]; 3] = [
+            &[("1", "2", true), ("2", "1", true), ("1", "new", true)],
+            &[("new", "7", true), ("1", "2", false), ("4", "4", true)],
+            &[
+                ("0", "3", true),
+                ("new", "7", false),
+                ("missing", "1", false),
+            ],
+        ];
+        for batch in batches {
+            for &(from, to, insert) in batch {
+                if insert {
+                    incremental.insert_edge(from, to, 1.0).unwrap();
+                } else {
+                    incremental.remove_edge(from, to);
+                }
+            }
+            assert!(incremental.is_pending());
+            let warm = incremental.update().clone();
+            assert!(warm.converged);
+            assert!(warm.iterations < cold, "{} >= {cold}", warm.iterations);
+
+            let full = pagerank.rank(incremental.graph());
+            assert_close(&warm.values, &full.values, 1e-9);
+        }
+        assert!(!incremental.is_pending());
+    }
+
+    #[test]
+    fn test_rank_changes() {
+        let mut incremental = IncrementalPageRank::new(PageRank::new(0.85, 1000), sports_graph());
+        assert_eq!(incremental.change("ESPN"), Some(0.0));
+
+        incremental.insert_edge("ESPN", "MLB", 1.0).unwrap();
+        incremental.insert_edge("Chess", "MLB", 1.0).unwrap();
+        incremental.update();
+        let changes = incremental.changes();
+        let mlb = incremental.graph().index("MLB").unwrap();
+        assert!(changes[mlb] > 0.0);
+        assert_eq!(incremental.change("MLB"), Some(changes[mlb]));
+        assert_eq!(
+            incremental.change("Chess"),
+            Some(incremental.ranks().values[5])
+        );
+        assert_eq!(incremental.change("FIFA"), None);
+        // Rank only moves between nodes, the total stays 1
+        assert!(changes.iter().sum::<f64>().abs() < 1e-12);
+
+        let largest = incremental.largest_changes(2);
+        assert_eq!(largest[0].0, mlb);
+        assert!(largest[0].1.abs() >= largest[1].1.abs());
+    }
+
+    #[test]
+    fn test_insert_bad_weight() {
+        let mut incremental = IncrementalPageRank::new(PageRank::new(0.85, 1000), sports_graph());
+        let edges = incremental.graph().edge_count();
+        for weight in [f64::NAN, -1.0, f64::INFINITY] {
+            let error = incremental
+                .insert_edge("ESPN", "Chess", weight)
+                .unwrap_err();
+            assert_eq!(error.to_string(), format!("bad edge weight {weight}"));
+        }
+        assert_eq!(incremental.graph().edge_count(), edges);
+        assert_eq!(incremental.graph().index("Chess"), None);
+        assert!(!incremental.is_pending());
+        incremental.insert_edge("ESPN", "Chess", 0.0).unwrap();
+        assert!(incremental.is_pending());
+    }
+}
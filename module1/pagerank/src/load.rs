@@ -0,0 +1,801 @@
+/*
+Reading graphs from files.
+
+- Edge list: one edge per line, "from to [weight]" separated by spaces.
+- CSV and TSV: "from,to[,weight]", optionally with a header row such as
+  "source,target,weight", quoted names may contain the separator.
+- GraphML: nodes are named by their "label" or "name" data if they have
+  one, otherwise by their id. Undirected graphs get both directions.
+- GML: the same for node labels and ids; "directed 1" for a directed
+  graph, as GML graphs are undirected by default.
+- Graphviz DOT: "digraph" edges (->) go one way, "graph" edges (--) both.
+  Chains (a -> b -> c), subgraphs as edge ends (a -> {b c}) and the
+  "weight" attribute are understood; other attributes are ignored.
+
+Edges without a weight weigh 1. The edge list, CSV and TSV readers stream
+their input, so only the graph itself has to fit in memory.
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::graph::Graph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    EdgeList,
+    Csv,
+    Tsv,
+    #[value(name = "graphml")]
+    GraphMl,
+    Gml,
+    Dot,
+}
+
+impl InputFormat {
+    /// The format for a file extension: .txt/.edges/.el, .csv, .tsv,
+    /// .graphml/.xml, .gml, .dot/.gv
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "txt" | "edges" | "el" => Some(InputFormat::EdgeList),
+            "csv" => Some(InputFormat::Csv),
+            "tsv" => Some(InputFormat::Tsv),
+            "graphml" | "xml" => Some(InputFormat::GraphMl),
+            "gml" => Some(InputFormat::Gml),
+            "dot" | "gv" => Some(InputFormat::Dot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Csv(csv::Error),
+    Xml(xml::reader::Error),
+    /// Bad input at a line (1-based, 0 when not known)
+    Parse {
+        line: usize,
+        message: String,
+    },
+    UnknownFormat(PathBuf),
+    /// A node named in a teleport or topic file that is not in the graph
+    UnknownNode(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "{err}"),
+            LoadError::Csv(err) => write!(f, "{err}"),
+            LoadError::Xml(err) => write!(f, "{err}"),
+            LoadError::Parse { line: 0, message } => write!(f, "{message}"),
+            LoadError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            LoadError::UnknownFormat(path) => write!(
+                f,
+                "{}: unknown graph format, use --input-format",
+                path.display()
+            ),
+            LoadError::UnknownNode(name) => write!(f, "unknown node {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<csv::Error> for LoadError {
+    fn from(err: csv::Error) -> Self {
+        LoadError::Csv(err)
+    }
+}
+
+impl From<xml::reader::Error> for LoadError {
+    fn from(err: xml::reader::Error) -> Self {
+        LoadError::Xml(err)
+    }
+}
+
+pub(crate) fn parse_error(line: usize, message: impl Into<String>) -> LoadError {
+    LoadError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Read a graph file. Without a format it is taken from the extension.
+pub fn load(path: &Path, format: Option<InputFormat>) -> Result<Graph, LoadError> {
+    let mut graph = Graph::new();
+    load_into(&mut graph, path, format)?;
+    Ok(graph)
+}
+
+/// Add the nodes and edges of a graph file to a graph
+pub fn load_into(
+    graph: &mut Graph,
+    path: &Path,
+    format: Option<InputFormat>,
+) -> Result<(), LoadError> {
+    let format = format
+        .or_else(|| InputFormat::from_path(path))
+        .ok_or_else(|| LoadError::UnknownFormat(path.to_path_buf()))?;
+    let file = BufReader::new(File::open(path)?);
+    read_into(graph, file, format)
+}
+
+/// Add the nodes and edges read from `reader` to a graph
+pub fn read_into<R: BufRead>(
+    graph: &mut Graph,
+    reader: R,
+    format: InputFormat,
+) -> Result<(), LoadError> {
+    match format {
+        InputFormat::EdgeList => read_edge_list(graph, reader),
+        InputFormat::Csv => read_delimited(graph, reader, b','),
+        InputFormat::Tsv => read_delimited(graph, reader, b'\t'),
+        InputFormat::GraphMl => read_graphml(graph, reader),
+        InputFormat::Gml => read_gml(graph, reader),
+        InputFormat::Dot => read_dot(graph, reader),
+    }
+}
+
+fn parse_weight(line: usize, text: Option<&str>) -> Result<f64, LoadError> {
+    let Some(text) = text.map(str::trim).filter(|text| !text.is_empty()) else {
+        return Ok(1.0);
+    };
+    match text.parse::<f64>() {
+        Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok(weight),
+        _ => Err(parse_error(line, format!("bad edge weight {text:?}"))),
+    }
+}
+
+// "source,target", "from,to" or "src,dst", whatever the case
+fn is_header(from: &str, to: &str) -> bool {
+    let from = from.trim().to_lowercase();
+    let to = to.trim().to_lowercase();
+    matches!(from.as_str(), "source" | "from" | "src")
+        && matches!(to.as_str(), "target" | "to" | "dst")
+}
+
+fn read_edge_list<R: BufRead>(graph: &mut Graph, reader: R) -> Result<(), LoadError> {
+    let mut first = true;
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let (Some(from), Some(to)) = (fields.next(), fields.next()) else {
+            return Err(parse_error(number + 1, "expected \"from to [weight]\""));
+        };
+        if std::mem::take(&mut first) && is_header(from, to) {
+            continue;
+        }
+        let weight = parse_weight(number + 1, fields.next())?;
+        graph.add_edge(from, to, weight);
+    }
+    Ok(())
+}
+
+fn read_delimited<R: Read>(graph: &mut Graph, reader: R, delimiter: u8) -> Result<(), LoadError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .from_reader(reader);
+    let mut record = csv::StringRecord::new();
+    let mut first = true;
+    while reader.read_record(&mut record)? {
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let (Some(from), Some(to)) = (record.get(0), record.get(1)) else {
+            return Err(parse_error(line, "expected \"from,to[,weight]\""));
+        };
+        if std::mem::take(&mut first) && is_header(from, to) {
+            continue;
+        }
+        let weight = parse_weight(line, record.get(2))?;
+        graph.add_edge(from.trim(), to.trim(), weight);
+    }
+    Ok(())
+}
+
+/// Nodes by file id and edges between them, for formats where a node can
+/// be named by a label found anywhere in the file
+#[derive(Default)]
+struct Pending {
+    ids: HashMap<String, usize>,
+    labels: Vec<Option<String>>,
+    edges: Vec<(usize, usize, f64)>,
+    ids_in_order: Vec<String>,
+}
+
+impl Pending {
+    fn node(&mut self, id: &str) -> usize {
+        if let Some(&index) = self.ids.get(id) {
+            return index;
+        }
+        let index = self.labels.len();
+        self.ids.insert(id.to_string(), index);
+        self.ids_in_order.push(id.to_string());
+        self.labels.push(None);
+        index
+    }
+
+    fn edge(&mut self, from: &str, to: &str, weight: f64, directed: bool) {
+        let from = self.node(from);
+        let to = self.node(to);
+        self.edges.push((from, to, weight));
+        if !directed && from != to {
+            self.edges.push((to, from, weight));
+        }
+    }
+
+    // A label names the node unless it is the id of another node or an
+    // earlier node has it already, then the id does. Every id is taken
+    // first, so two nodes can never end up with the same name.
+    fn finish(self, graph: &mut Graph) {
+        let mut taken: std::collections::HashSet<String> =
+            self.ids_in_order.iter().cloned().collect();
+        let mut index = Vec::with_capacity(self.labels.len());
+        for (id, label) in self.ids_in_order.iter().zip(self.labels) {
+            let name = match label {
+                Some(label) if taken.insert(label.clone()) => label,
+                _ => id.clone(),
+            };
+            index.push(graph.add_node(&name));
+        }
+        for (from, to, weight) in self.edges {
+            graph.add_edge_by_index(index[from], index[to], weight);
+        }
+    }
+}
+
+fn attribute<'a>(attributes: &'a [xml::attribute::OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == name)
+        .map(|attribute| attribute.value.as_str())
+}
+
+fn read_graphml<R: Read>(graph: &mut Graph, reader: R) -> Result<(), LoadError> {
+    // Data keys by id: "label"/"name" for nodes, "weight" for edges
+    let mut label_keys = Vec::new();
+    let mut weight_keys = Vec::new();
+    let mut directed = true;
+    let mut pending = Pending::default();
+    // The node or edge being read and the data key being read
+    let mut node: Option<usize> = None;
+    let mut edge: Option<(String, String, f64, bool)> = None;
+    let mut data_key: Option<String> = None;
+    let mut text = String::new();
+
+    for event in EventReader::new(reader) {
+        match event? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                "key" => {
+                    let id = attribute(&attributes, "id").unwrap_or_default().to_string();
+                    let domain = attribute(&attributes, "for").unwrap_or("all");
+                    let attr_name = attribute(&attributes, "attr.name").unwrap_or_default();
+                    match (domain, attr_name) {
+                        ("node" | "all", "label" | "name") => label_keys.push(id),
+                        ("edge" | "all", "weight") => weight_keys.push(id),
+                        _ => {}
+                    }
+                }
+                "graph" => {
+                    directed = attribute(&attributes, "edgedefault") != Some("undirected");
+                }
+                "node" => {
+                    let id = attribute(&attributes, "id")
+                        .ok_or_else(|| parse_error(0, "GraphML node without an id"))?;
+                    node = Some(pending.node(id));
+                }
+                "edge" => {
+                    let (Some(source), Some(target)) = (
+                        attribute(&attributes, "source"),
+                        attribute(&attributes, "target"),
+                    ) else {
+                        return Err(parse_error(0, "GraphML edge without a source or target"));
+                    };
+                    let edge_directed = match attribute(&attributes, "directed") {
+                        Some(value) => value == "true",
+                        None => directed,
+                    };
+                    edge = Some((source.to_string(), target.to_string(), 1.0, edge_directed));
+                }
+                "data" => {
+                    data_key = attribute(&attributes, "key").map(str::to_string);
+                    text.clear();
+                }
+                _ => {}
+            },
+            XmlEvent::Characters(characters) | XmlEvent::CData(characters)
+                if data_key.is_some() =>
+            {
+                text.push_str(&characters);
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "data" => {
+                    let Some(key) = data_key.take() else { continue };
+                    if let Some((_, _, weight, _)) = &mut edge {
+                        if weight_keys.contains(&key) {
+                            *weight = parse_weight(0, Some(&text))?;
+                        }
+                    } else if let Some(index) = node
+                        && label_keys.contains(&key)
+                        && !text.trim().is_empty()
+                    {
+                        pending.labels[index] = Some(text.trim().to_string());
+                    }
+                }
+                "node" => node = None,
+                "edge" => {
+                    if let Some((source, target, weight, directed)) = edge.take() {
+                        pending.edge(&source, &target, weight, directed);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    pending.finish(graph);
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum GmlValue {
+    Number(String),
+    Text(String),
+    List(Vec<(String, GmlValue)>),
+}
+
+impl GmlValue {
+    fn get(&self, key: &str) -> Option<&GmlValue> {
+        match self {
+            GmlValue::List(items) => items
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            GmlValue::Number(text) | GmlValue::Text(text) => Some(text),
+            GmlValue::List(_) => None,
+        }
+    }
+}
+
+// GML tokens: keys, numbers, "strings", [ and ], with # comments to the
+// end of the line
+fn gml_tokens(text: &str) -> Result<Vec<(usize, String, bool)>, LoadError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '[' | ']' => tokens.push((line, c.to_string(), false)),
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            string.push(c);
+                        }
+                        None => return Err(parse_error(line, "unterminated GML string")),
+                    }
+                }
+                tokens.push((line, string, true));
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '[' && *c != ']')
+                {
+                    word.push(c);
+                }
+                tokens.push((line, word, false));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn gml_list(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<(usize, String, bool)>>,
+    nested: bool,
+) -> Result<Vec<(String, GmlValue)>, LoadError> {
+    let mut items = Vec::new();
+    loop {
+        let Some((line, key, quoted)) = tokens.next() else {
+            if nested {
+                return Err(parse_error(0, "GML list without a closing ]"));
+            }
+            return Ok(items);
+        };
+        if key == "]" && !quoted {
+            if nested {
+                return Ok(items);
+            }
+            return Err(parse_error(line, "unexpected ] in GML"));
+        }
+        let value = match tokens.next() {
+            Some((_, open, false)) if open == "[" => GmlValue::List(gml_list(tokens, true)?),
+            Some((_, text, true)) => GmlValue::Text(text),
+            Some((_, number, false)) if number != "]" => GmlValue::Number(number),
+            _ => return Err(parse_error(line, format!("GML key {key} without a value"))),
+        };
+        items.push((key, value));
+    }
+}
+
+fn read_gml<R: Read>(graph: &mut Graph, mut reader: R) -> Result<(), LoadError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut tokens = gml_tokens(&text)?.into_iter().peekable();
+    let top = GmlValue::List(gml_list(&mut tokens, false)?);
+    let gml = top
+        .get("graph")
+        .ok_or_else(|| parse_error(0, "no graph [ ... ] in GML"))?;
+    let GmlValue::List(items) = gml else {
+        return Err(parse_error(0, "graph in GML is not a list"));
+    };
+    let directed = gml.get("directed").and_then(GmlValue::as_str) == Some("1");
+
+    let mut pending = Pending::default();
+    for (key, value) in items {
+        match key.as_str() {
+            "node" => {
+                let id = value
+                    .get("id")
+                    .and_then(GmlValue::as_str)
+                    .ok_or_else(|| parse_error(0, "GML node without an id"))?;
+                let index = pending.node(id);
+                if let Some(label) = value.get("label").and_then(GmlValue::as_str) {
+                    pending.labels[index] = Some(label.to_string());
+                }
+            }
+            "edge" => {
+                let source = value.get("source").and_then(GmlValue::as_str);
+                let target = value.get("target").and_then(GmlValue::as_str);
+                let (Some(source), Some(target)) = (source, target) else {
+                    return Err(parse_error(0, "GML edge without a source or target"));
+                };
+                let weight = value
+                    .get("weight")
+                    .or_else(|| value.get("value"))
+                    .and_then(GmlValue::as_str);
+                pending.edge(source, target, parse_weight(0, weight)?, directed);
+            }
+            _ => {}
+        }
+    }
+    pending.finish(graph);
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DotToken {
+    // An identifier, number or string, and whether it was quoted
+    Id(String, bool),
+    // -> -- { } [ ] ; , = :
+    Symbol(&'static str),
+}
+
+fn dot_tokens(text: &str) -> Result<Vec<(usize, DotToken)>, LoadError> {
+    const SYMBOLS: [&str; 10] = ["->", "--", "{", "}", "[", "]", ";", ",", "=", ":"];
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = text;
+    let mut at_line_start = true;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+            at_line_start = true;
+            rest = &rest[1..];
+            continue;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        // Preprocessor lines, and // and /* */ comments
+        if (at_line_start && c == '#') || rest.starts_with("//") {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+            continue;
+        }
+        at_line_start = false;
+        if let Some(after) = rest.strip_prefix("/*") {
+            let end = after
+                .find("*/")
+                .ok_or_else(|| parse_error(line, "unterminated comment"))?;
+            line += after[..end].matches('\n').count();
+            rest = &after[end + 2..];
+            continue;
+        }
+        if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push((line, DotToken::Symbol(symbol)));
+            rest = &rest[symbol.len()..];
+            continue;
+        }
+        if c == '"' {
+            let mut string = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, '"')) => string.push('"'),
+                        Some((_, '\n')) => line += 1,
+                        Some((_, c)) => {
+                            string.push('\\');
+                            string.push(c);
+                        }
+                        None => return Err(parse_error(line, "unterminated string")),
+                    },
+                    Some((_, c)) => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        string.push(c);
+                    }
+                    None => return Err(parse_error(line, "unterminated string")),
+                }
+            };
+            tokens.push((line, DotToken::Id(string, true)));
+            rest = &rest[end..];
+            continue;
+        }
+        if c == '<' {
+            // HTML string: up to the matching >
+            let mut depth = 0;
+            let mut end = None;
+            for (i, c) in rest.char_indices() {
+                match c {
+                    '<' => depth += 1,
+                    '>' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let end = end.ok_or_else(|| parse_error(line, "unterminated HTML string"))?;
+            line += rest[..end].matches('\n').count();
+            tokens.push((line, DotToken::Id(rest[1..end].to_string(), true)));
+            rest = &rest[end + 1..];
+            continue;
+        }
+        // Names and numbers; an edge operator ends them: a->b, 1--2
+        let end = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                let operator = rest[i..].starts_with("->") || rest[i..].starts_with("--");
+                operator || !(c.is_alphanumeric() || c == '_' || c == '.' || c == '-')
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        if end == 0 {
+            return Err(parse_error(line, format!("unexpected {c:?}")));
+        }
+        tokens.push((line, DotToken::Id(rest[..end].to_string(), false)));
+        rest = &rest[end..];
+    }
+    Ok(tokens)
+}
+
+struct DotParser {
+    tokens: Vec<(usize, DotToken)>,
+    position: usize,
+    directed: bool,
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&DotToken> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position.min(self.tokens.len().saturating_sub(1)))
+            .map_or(0, |(line, _)| *line)
+    }
+
+    fn next(&mut self) -> Option<DotToken> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(DotToken::Symbol(next)) if *next == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), LoadError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(parse_error(self.line(), format!("expected {symbol}")))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, LoadError> {
+        match self.next() {
+            Some(DotToken::Id(id, _)) => Ok(id),
+            _ => {
+                self.position -= 1;
+                Err(parse_error(self.line(), "expected a name"))
+            }
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(DotToken::Id(id, false)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn graph(&mut self, pending: &mut Pending) -> Result<(), LoadError> {
+        if self.is_keyword("strict") {
+            self.position += 1;
+        }
+        if self.is_keyword("digraph") {
+            self.directed = true;
+        } else if self.is_keyword("graph") {
+            self.directed = false;
+        } else {
+            return Err(parse_error(self.line(), "expected graph or digraph"));
+        }
+        self.position += 1;
+        if matches!(self.peek(), Some(DotToken::Id(..))) {
+            self.position += 1;
+        }
+        self.expect("{")?;
+        self.statements(pending)?;
+        Ok(())
+    }
+
+    // Statements up to the closing }, returns the nodes they mention
+    fn statements(&mut self, pending: &mut Pending) -> Result<Vec<usize>, LoadError> {
+        let mut nodes = Vec::new();
+        loop {
+            if self.eat("}") {
+                return Ok(nodes);
+            }
+            if self.peek().is_none() {
+                return Err(parse_error(self.line(), "expected }"));
+            }
+            if self.eat(";") {
+                continue;
+            }
+            if self.is_keyword("graph") || self.is_keyword("node") || self.is_keyword("edge") {
+                self.position += 1;
+                self.attributes()?;
+                continue;
+            }
+            // Graph attribute: name = value
+            if matches!(
+                self.tokens.get(self.position + 1),
+                Some((_, DotToken::Symbol("=")))
+            ) {
+                self.position += 3;
+                continue;
+            }
+            nodes.extend(self.edge_statement(pending)?);
+        }
+    }
+
+    // A node, subgraph or chain of edges with optional attributes
+    fn edge_statement(&mut self, pending: &mut Pending) -> Result<Vec<usize>, LoadError> {
+        let mut ends = vec![self.end(pending)?];
+        loop {
+            let operator = if self.directed { "->" } else { "--" };
+            if self.eat(operator) {
+                ends.push(self.end(pending)?);
+            } else if self.eat(if self.directed { "--" } else { "->" }) {
+                return Err(parse_error(self.line(), format!("expected {operator}")));
+            } else {
+                break;
+            }
+        }
+        let attributes = self.attributes()?;
+        let weight = attributes
+            .iter()
+            .find(|(name, _)| name == "weight")
+            .map(|(_, value)| value.as_str());
+        let weight = parse_weight(self.line(), weight)?;
+        for pair in ends.windows(2) {
+            for &from in &pair[0] {
+                for &to in &pair[1] {
+                    pending.edges.push((from, to, weight));
+                    if !self.directed && from != to {
+                        pending.edges.push((to, from, weight));
+                    }
+                }
+            }
+        }
+        Ok(ends.concat())
+    }
+
+    // One end of an edge: a node (with an optional port) or a subgraph
+    fn end(&mut self, pending: &mut Pending) -> Result<Vec<usize>, LoadError> {
+        if self.is_keyword("subgraph") {
+            self.position += 1;
+            if matches!(self.peek(), Some(DotToken::Id(..))) {
+                self.position += 1;
+            }
+        }
+        if self.eat("{") {
+            return self.statements(pending);
+        }
+        let name = self.id()?;
+        // node:port or node:port:compass
+        while self.eat(":") {
+            self.id()?;
+        }
+        Ok(vec![pending.node(&name)])
+    }
+
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, LoadError> {
+        let mut attributes = Vec::new();
+        while self.eat("[") {
+            while !self.eat("]") {
+                let name = self.id()?;
+                let value = if self.eat("=") {
+                    self.id()?
+                } else {
+                    "true".to_string()
+                };
+                attributes.push((name, value));
+                let _ = self.eat(",") || self.eat(";");
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+fn read_dot<R: Read>(graph: &mut Graph, mut reader: R) -> Result<(), LoadError> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut parser = DotParser {
+        tokens: dot_tokens(&text)?,
+        position: 0,
+        directed: true,
+    };
+    let mut pending = Pending::default();
+    parser.graph(&mut pending)?;
+    pending.finish(graph);
+    Ok(())
+}
@@ -1,8 +1,30 @@
-// Importing the fill function from the textwrap crate to wrap text at 78 characters per line.
+use std::path::PathBuf;
+use std::process;
+
 use clap::Parser;
-use std::collections::HashMap;
+use clap::ValueEnum;
+use pagerank::{
+    DEFAULT_TOLERANCE, Dangling, Graph, Hits, InputFormat, OutputFormat, PageRank, SelfLoops,
+    Teleport, hits_ranking, hits_to_text, load, load_topics, ranking, sports_graph, to_csv,
+    to_json, to_text,
+};
+// Importing the fill function from the textwrap crate to wrap text at 78 characters per line.
 use textwrap::fill;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+enum Algorithm {
+    /// PageRank, jumping to any node
+    #[default]
+    #[value(name = "pagerank")]
+    PageRank,
+    /// PageRank jumping only to the --seed nodes or the --teleport weights
+    Personalized,
+    /// Personalized PageRank per topic of --topics, blended by --topic-weight
+    TopicSensitive,
+    /// Hub and authority scores
+    Hits,
+}
+
 /// Command-line arguments for the PageRank program
 #[derive(Parser, Debug)]
 #[command(
@@ -11,7 +33,10 @@ use textwrap::fill;
     about = "Run PageRank on a user-defined graph with named edges"
 )]
 struct Args {
-    /// Number of iterations
+    #[arg(short, long, value_enum, default_value_t)]
+    algorithm: Algorithm,
+
+    /// Maximum number of iterations
     #[arg(short, long, default_value_t = 100)]
     iterations: usize,
 
@@ -19,6 +44,18 @@ struct Args {
     #[arg(short, long, default_value_t = 0.85)]
     damping: f64,
 
+    /// Stop once the ranks change by less than this in total (L1 norm)
+    #[arg(short, long, default_value_t = DEFAULT_TOLERANCE)]
+    tolerance: f64,
+
+    /// Where the rank of nodes without outgoing links goes
+    #[arg(long, value_enum, default_value_t)]
+    dangling: Dangling,
+
+    /// Whether links from a node to itself count
+    #[arg(long, value_enum, default_value_t)]
+    self_loops: SelfLoops,
+
     /// Edges in the graph, entered as name pairs: A:B
     /// Example: --edge ESPN:NFL --edge NBA:UFC
     #[arg(long = "edge")]
@@ -28,151 +65,216 @@ struct Args {
     /// Example: --expand 100 prints percentages; default 1.0 prints originals
     #[arg(long, default_value_t = 1.0)]
     expand: f64,
-}
 
-// The PageRank struct holds the damping factor and the number of iterations to run the algorithm.
-struct PageRank {
-    damping: f64,
-    iterations: usize,
-}
+    /// A seed node of personalized PageRank, can be repeated
+    #[arg(long = "seed", value_name = "NODE")]
+    seeds: Vec<String>,
 
-impl PageRank {
-    // The new function creates a new instance of the PageRank struct.
-    fn new(damping: f64, iterations: usize) -> Self {
-        Self {
-            damping,
-            iterations,
-        }
-    }
+    /// Teleport weights of personalized PageRank, "node [weight]" per line
+    #[arg(long, value_name = "FILE", conflicts_with = "seeds")]
+    teleport: Option<PathBuf>,
 
-    // The rank function calculates and returns the PageRank for each node in the graph.
-    fn rank(&self, graph: &[Vec<usize>]) -> Vec<f64> {
-        // The number of nodes in the graph.
-        let n = graph.len();
+    /// Seed nodes of topic-sensitive PageRank, "topic node [weight]" per line
+    #[arg(long, value_name = "FILE")]
+    topics: Option<PathBuf>,
 
-        // The initial PageRank value for each node.
-        let mut ranks = vec![1.0 / (n as f64); n];
+    /// Weight of a topic in the blend, entered as TOPIC:WEIGHT; topics not
+    /// given weigh 0, all topics weigh the same when none is given
+    #[arg(long = "topic-weight", value_name = "TOPIC:WEIGHT")]
+    topic_weights: Vec<String>,
 
-        // Iterates the specified number of times.
-        for _ in 0..self.iterations {
-            // A new vector to hold the updated PageRank values.
-            let mut new_ranks = vec![0.0; n];
+    /// Read the graph from a file instead of using the sports websites
+    #[arg(long, value_name = "FILE")]
+    input: Option<PathBuf>,
 
-            // Iterates over each node and its edges in the graph.
-            for (node, edges) in graph.iter().enumerate() {
-                // The amount of PageRank value this node contributes to its linked nodes.
-                let contribution = ranks[node] / (edges.len() as f64);
+    /// Format of the input file, guessed from its extension when not given
+    #[arg(long, value_enum, requires = "input")]
+    input_format: Option<InputFormat>,
 
-                // Distributes the PageRank value to the linked nodes.
-                for &edge in edges {
-                    new_ranks[edge] += contribution;
-                }
-            }
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
 
-            // Updates the PageRank values using the damping factor.
-            for rank in &mut new_ranks {
-                *rank = *rank * self.damping + (1.0 - self.damping) / (n as f64);
-            }
+    /// Only show the highest ranked nodes
+    #[arg(long)]
+    top: Option<usize>,
 
-            // Normalize after each iteration
-            let sum: f64 = new_ranks.iter().sum();
-            if sum > 0.0 {
-                for rank in &mut new_ranks {
-                    *rank /= sum;
-                }
-            }
+    /// Write to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
 
-            // Replaces the old PageRank values with the new ones.
-            ranks = new_ranks;
-        }
+fn convergence(iterations: usize, residual: f64, converged: bool) -> String {
+    if converged {
+        format!("Converged after {iterations} iterations (residual {residual:.2e})")
+    } else {
+        format!(
+            "Stopped after {iterations} iterations without converging (residual {residual:.2e})"
+        )
+    }
+}
 
-        // Normalize so that ranks sum to 1
-        let sum: f64 = ranks.iter().sum();
-        if sum > 0.0 {
-            for rank in &mut ranks {
-                *rank /= sum;
-            }
+fn teleport(graph: &Graph, args: &Args) -> Result<Teleport, Box<dyn std::error::Error>> {
+    match &args.teleport {
+        Some(path) => Ok(Teleport::load(graph, path)?),
+        None if args.seeds.is_empty() => {
+            Err("personalized PageRank needs --seed or --teleport".into())
         }
-
-        // Returns the final PageRank values.
-        ranks
+        None => Ok(Teleport::from_nodes(graph, &args.seeds)?),
     }
 }
 
-fn main() {
-    let args = Args::parse();
-
-    // The graph represents links between sports websites. Each index represents a website,
-    // and the values in the vectors are the indexes of the websites they link to.
-    let mut graph: Vec<Vec<usize>> = vec![
-        vec![1, 2], // ESPN links to NFL, NBA
-        vec![0],    // NFL links to ESPN
-        vec![0, 3], // NBA links to ESPN, UFC
-        vec![0],    // UFC links to ESPN
-        vec![0, 1], // MLB links to ESPN, NFL
-    ];
+// The weight of each topic, in the order of `topics`
+fn topic_weights(topics: &[String], args: &Args) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+    if args.topic_weights.is_empty() {
+        return Ok(vec![1.0; topics.len()]);
+    }
+    let mut weights = vec![0.0; topics.len()];
+    for entry in &args.topic_weights {
+        let (topic, weight) = entry
+            .rsplit_once(':')
+            .ok_or_else(|| format!("bad topic weight '{entry}', expected TOPIC:WEIGHT"))?;
+        let index = topics
+            .iter()
+            .position(|name| name == topic.trim())
+            .ok_or_else(|| format!("unknown topic '{}'", topic.trim()))?;
+        weights[index] = weight
+            .trim()
+            .parse()
+            .map_err(|_| format!("bad topic weight '{entry}', expected TOPIC:WEIGHT"))?;
+    }
+    Ok(weights)
+}
 
-    let mut names: Vec<String> = vec![
-        "ESPN".into(),
-        "NFL".into(),
-        "NBA".into(),
-        "UFC".into(),
-        "MLB".into(),
+// Options read by one algorithm only are an error with any other
+fn check_options(args: &Args) -> Result<(), String> {
+    let options = [
+        ("--seed", !args.seeds.is_empty(), Algorithm::Personalized),
+        (
+            "--teleport",
+            args.teleport.is_some(),
+            Algorithm::Personalized,
+        ),
+        ("--topics", args.topics.is_some(), Algorithm::TopicSensitive),
+        (
+            "--topic-weight",
+            !args.topic_weights.is_empty(),
+            Algorithm::TopicSensitive,
+        ),
     ];
-
-    // Build name -> index map from current names
-    let mut index_by_name: HashMap<String, usize> = HashMap::new();
-    for (i, name) in names.iter().enumerate() {
-        index_by_name.insert(name.clone(), i);
+    for (option, given, algorithm) in options {
+        if given && args.algorithm != algorithm {
+            let name = algorithm.to_possible_value().unwrap();
+            return Err(format!("{option} needs --algorithm {}", name.get_name()));
+        }
     }
+    Ok(())
+}
 
-    // Ensure graph length matches names length
-    while graph.len() < names.len() {
-        graph.push(Vec::new());
-    }
+fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    check_options(args)?;
+
+    // The graph represents links between sports websites, unless one is read from a file.
+    let mut graph: Graph = match &args.input {
+        Some(path) => load(path, args.input_format)?,
+        None => sports_graph(),
+    };
 
-    // Process edges A:B, appending names and graph nodes when needed
+    // Process edges A:B, appending nodes when needed
     for edge in &args.edges {
         // Accept "A:B" only; ignore malformed entries politely
         if let Some((a, b)) = edge.split_once(':') {
-            let a = a.trim();
-            let b = b.trim();
-
-            let a_idx = *index_by_name.entry(a.to_string()).or_insert_with(|| {
-                let new_idx = names.len();
-                names.push(a.to_string());
-                graph.push(Vec::new()); // new node with no outbound edges yet
-                new_idx
-            });
-
-            let b_idx = *index_by_name.entry(b.to_string()).or_insert_with(|| {
-                let new_idx = names.len();
-                names.push(b.to_string());
-                graph.push(Vec::new());
-                new_idx
-            });
-
-            // Append edge A -> B
-            graph[a_idx].push(b_idx);
+            graph.add_edge(a.trim(), b.trim(), 1.0);
         } else {
             eprintln!("Ignoring malformed edge '{}'. Expected format: A:B", edge);
         }
     }
 
     // Run PageRank
-    let pagerank = PageRank::new(args.damping, args.iterations);
+    let pagerank = PageRank::new(args.damping, args.iterations)
+        .tolerance(args.tolerance)
+        .dangling(args.dangling)
+        .self_loops(args.self_loops);
 
-    // Calculates the PageRank values.
-    let ranks = pagerank.rank(&graph);
+    // Calculates the PageRank values, or the HITS scores.
+    let (text, report) = if args.algorithm == Algorithm::Hits {
+        let scores = Hits::new(args.iterations)
+            .tolerance(args.tolerance)
+            .self_loops(args.self_loops)
+            .run(&graph);
+        let ranking = hits_ranking(&graph, &scores, args.top);
+        let report = convergence(scores.iterations, scores.residual, scores.converged);
+        let text = match args.format {
+            OutputFormat::Text => hits_to_text(&ranking, args.expand),
+            OutputFormat::Csv => to_csv(&ranking),
+            OutputFormat::Json => to_json(&ranking),
+        };
+        (text, report)
+    } else {
+        let (ranks, report) = match args.algorithm {
+            Algorithm::Personalized => {
+                let ranks = pagerank.personalized(&graph, &teleport(&graph, args)?);
+                let report = convergence(ranks.iterations, ranks.residual, ranks.converged);
+                (ranks.values, report)
+            }
+            Algorithm::TopicSensitive => {
+                let path = args
+                    .topics
+                    .as_ref()
+                    .ok_or("topic-sensitive PageRank needs --topics")?;
+                let ranks = pagerank.topic_sensitive(&graph, &load_topics(&graph, path)?);
+                let weights = topic_weights(&ranks.topics, args)?;
+                let report = ranks
+                    .topics
+                    .iter()
+                    .zip(&ranks.ranks)
+                    .map(|(topic, ranks)| {
+                        let line = convergence(ranks.iterations, ranks.residual, ranks.converged);
+                        format!("{topic}: {line}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (ranks.blend(&weights), report)
+            }
+            _ => {
+                let ranks = pagerank.rank(&graph);
+                let report = convergence(ranks.iterations, ranks.residual, ranks.converged);
+                (ranks.values, report)
+            }
+        };
+        let ranking = ranking(&graph, &ranks, args.top);
+        let text = match args.format {
+            OutputFormat::Text => to_text(&ranking, args.expand),
+            OutputFormat::Csv => to_csv(&ranking),
+            OutputFormat::Json => to_json(&ranking),
+        };
+        (text, report)
+    };
 
-    // Prints the PageRank values.
-    for (i, rank) in ranks.iter().enumerate() {
-        println!("The PageRank of {} is {}", names[i], rank);
-    }
+    let text = match args.format {
+        OutputFormat::Text => {
+            // Explanation of how PageRank works.
+            let explanation = "PageRank is a link analysis algorithm used by Google that uses the hyperlink structure of the web to determine a quality ranking for each web page. It works by counting the number and quality of links to a page to determine a rough estimate of how important the website is.";
 
-    // Explanation of how PageRank works.
-    let explanation = "PageRank is a link analysis algorithm used by Google that uses the hyperlink structure of the web to determine a quality ranking for each web page. It works by counting the number and quality of links to a page to determine a rough estimate of how important the website is.";
+            // The explanation wrapped at 78 characters per line.
+            format!("{text}{report}\n{}\n", fill(explanation, 78))
+        }
+        // Keep the data clean, the convergence goes to stderr
+        OutputFormat::Csv | OutputFormat::Json => {
+            eprintln!("{report}");
+            text
+        }
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, text)?,
+        None => print!("{text}"),
+    }
+    Ok(())
+}
 
-    // Prints the explanation wrapped at 78 characters per line.
-    println!("{}", fill(explanation, 78));
+fn main() {
+    let args = Args::parse();
+    if let Err(error) = run(&args) {
+        eprintln!("Error: {error}");
+        process::exit(1);
+    }
 }
@@ -0,0 +1,147 @@
+/*
+Teleport vectors for personalized and topic-sensitive PageRank.
+
+When the random surfer stops following links it jumps to a node picked
+by the teleport vector. Plain PageRank jumps anywhere with the same
+chance. A teleport vector on a seed set, such as a few accounts known to
+be trolls, makes the ranks measure how close each node is to the seeds.
+
+Weight files have one "node [weight]" per line, topic files one
+"topic node [weight]" per line, separated by spaces. # starts a comment
+and a missing weight is 1.
+*/
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::graph::Graph;
+use crate::load::{LoadError, parse_error};
+
+/// A weight per node, summing to 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Teleport {
+    weights: Vec<f64>,
+}
+
+impl Teleport {
+    /// The same chance for every node, as in plain PageRank
+    pub fn uniform(node_count: usize) -> Self {
+        Self {
+            weights: vec![1.0 / node_count as f64; node_count],
+        }
+    }
+
+    /// The same chance for each of the seed nodes and none for the others
+    pub fn from_nodes<S: AsRef<str>>(graph: &Graph, nodes: &[S]) -> Result<Self, LoadError> {
+        Self::from_weights(graph, nodes.iter().map(|node| (node, 1.0)))
+    }
+
+    /// Chances in proportion to the weights; a node given twice gets both
+    pub fn from_weights<S, I>(graph: &Graph, weights: I) -> Result<Self, LoadError>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = (S, f64)>,
+    {
+        let mut vector = vec![0.0; graph.node_count()];
+        for (node, weight) in weights {
+            let node = node.as_ref();
+            let index = graph
+                .index(node)
+                .ok_or_else(|| LoadError::UnknownNode(node.to_string()))?;
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(parse_error(0, format!("bad weight {weight} for {node:?}")));
+            }
+            vector[index] += weight;
+        }
+        let total: f64 = vector.iter().sum();
+        if total <= 0.0 {
+            return Err(parse_error(0, "the teleport weights sum to 0"));
+        }
+        for weight in &mut vector {
+            *weight /= total;
+        }
+        Ok(Self { weights: vector })
+    }
+
+    /// Read "node [weight]" lines
+    pub fn read<R: BufRead>(graph: &Graph, reader: R) -> Result<Self, LoadError> {
+        let lines = read_weighted(reader, 1)?;
+        Self::from_weights(
+            graph,
+            lines
+                .into_iter()
+                .map(|(mut names, weight)| (names.remove(0), weight)),
+        )
+    }
+
+    pub fn load(graph: &Graph, path: &Path) -> Result<Self, LoadError> {
+        Self::read(graph, BufReader::new(File::open(path)?))
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+/// Read "topic node [weight]" lines into a teleport vector per topic, in
+/// the order the topics first appear
+pub fn read_topics<R: BufRead>(
+    graph: &Graph,
+    reader: R,
+) -> Result<Vec<(String, Teleport)>, LoadError> {
+    let mut topics: Vec<(String, Vec<(String, f64)>)> = Vec::new();
+    for (mut names, weight) in read_weighted(reader, 2)? {
+        let node = names.pop().expect("two names");
+        let topic = names.pop().expect("two names");
+        match topics.iter_mut().find(|(name, _)| *name == topic) {
+            Some((_, seeds)) => seeds.push((node, weight)),
+            None => topics.push((topic, vec![(node, weight)])),
+        }
+    }
+    topics
+        .into_iter()
+        .map(|(topic, seeds)| Ok((topic, Teleport::from_weights(graph, seeds)?)))
+        .collect()
+}
+
+pub fn load_topics(graph: &Graph, path: &Path) -> Result<Vec<(String, Teleport)>, LoadError> {
+    read_topics(graph, BufReader::new(File::open(path)?))
+}
+
+// Lines of `names` words and an optional weight, without comments and blank lines
+fn read_weighted<R: BufRead>(
+    reader: R,
+    names: usize,
+) -> Result<Vec<(Vec<String>, f64)>, LoadError> {
+    let mut lines = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < names || fields.len() > names + 1 {
+            let expected = if names == 1 {
+                "expected \"node [weight]\""
+            } else {
+                "expected \"topic node [weight]\""
+            };
+            return Err(parse_error(number + 1, expected));
+        }
+        let weight = match fields.get(names) {
+            None => 1.0,
+            Some(text) => match text.parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                _ => return Err(parse_error(number + 1, format!("bad weight {text:?}"))),
+            },
+        };
+        let names = fields[..names]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        lines.push((names, weight));
+    }
+    Ok(lines)
+}
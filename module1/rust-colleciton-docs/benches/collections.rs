@@ -0,0 +1,52 @@
+// Criterion benchmarks of the collection workloads in lib_benchmark:
+//
+// cargo bench
+// cargo bench -- lookup/BTreeMap
+// BENCH_MAX_SIZE=100000 cargo bench
+//
+// One group per operation, one benchmark per collection and size, with the
+// throughput in elements. The sizes go from 10 to 10^7; BENCH_MAX_SIZE
+// leaves out the larger ones. For a CSV or Markdown table of the same
+// workloads run `cargo run --release -- --bench`.
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rust_colleciton_docs::lib_benchmark::{Collection, Operation, SIZES, Workload};
+
+fn max_size() -> usize {
+    std::env::var("BENCH_MAX_SIZE")
+        .ok()
+        .and_then(|max| max.parse().ok())
+        .unwrap_or(usize::MAX)
+}
+
+fn bench_collections(c: &mut Criterion) {
+    let max = max_size();
+    for operation in Operation::ALL {
+        let mut group = c.benchmark_group(operation.name());
+        for size in SIZES.into_iter().filter(|size| *size <= max) {
+            // Large collections take seconds to build and run
+            if size >= 100_000 {
+                group.sample_size(10);
+                group.warm_up_time(Duration::from_millis(500));
+            } else {
+                group.sample_size(50);
+                group.warm_up_time(Duration::from_secs(1));
+            }
+            for collection in Collection::ALL {
+                let Some(workload) = Workload::new(collection, operation, size, 42) else {
+                    continue;
+                };
+                let mut built = workload.setup();
+                group.throughput(Throughput::Elements(workload.elements() as u64));
+                group.bench_function(BenchmarkId::new(collection.name(), size), |b| {
+                    b.iter_custom(|iterations| workload.time(built.as_mut(), iterations))
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_collections);
+criterion_main!(benches);
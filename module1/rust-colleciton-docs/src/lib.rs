@@ -0,0 +1,4 @@
+// Library part of the collection docs examples, shared by the binary and
+// its tests.
+pub mod lib_benchmark;
+pub mod lib_priorityqueue;
@@ -1,117 +1,811 @@
-// Benchmark different collections in Rust on insert and lookup operations to compare performance.
-use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
-use std::time::Instant;
+// Benchmark different collections in Rust on insert, lookup, remove,
+// iterate and range operations to compare performance.
+//
+// A Workload is one collection, operation and size with its keys and probes
+// drawn from a seeded generator. Only the operation itself is timed: the
+// collection is built before and put back after each run, so every run
+// sees the same state.
+//
+// insert   insert `size` shuffled keys into an empty collection
+// lookup   look up PROBES random keys (heaps: peek, PriorityQueue: by handle)
+// remove   remove PROBES random keys (heaps, LinkedList: from the front)
+// iterate  sum every element
+// range    sum PROBES ranges of RANGE_WIDTH keys (BTreeMap, BTreeSet, VecDeque)
+//
+// run_suite measures every workload of a Config several times. Each sample
+// repeats the operation until it takes at least `min_sample` and reports the
+// time per element; the Report gives median, mean, standard deviation and a
+// 95% confidence interval per workload as CSV or a Markdown table. The same
+// workloads run under criterion in benches/collections.rs.
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-pub fn benchmark_collections() {
-    let mut hash_map = HashMap::new();
-    let mut btree_map = BTreeMap::new();
-    let mut vec_deque = VecDeque::new();
-    let mut linked_list = LinkedList::new();
-
-    // Insert
-    let start = Instant::now();
-    for i in 0..1000 {
-        hash_map.insert(i, i);
-    }
-    let duration = start.elapsed();
-    println!("HashMap insert duration: {:?}", duration);
-
-    let start = Instant::now();
-    for i in 0..1000 {
-        btree_map.insert(i, i);
-    }
-    let duration = start.elapsed();
-    println!("BTreeMap insert duration: {:?}", duration);
-
-    let start = Instant::now();
-    for i in 0..1000 {
-        vec_deque.push_back(i);
-    }
-    let duration = start.elapsed();
-    println!("VecDeque insert duration: {:?}", duration);
-
-    let start = Instant::now();
-    for i in 0..1000 {
-        linked_list.push_back(i);
-    }
-    let duration = start.elapsed();
-    println!("LinkedList insert duration: {:?}", duration);
-
-    // Lookup
-    let start = Instant::now();
-    for i in 0..1000 {
-        hash_map.get(&i);
-    }
-    let duration = start.elapsed();
-    println!("HashMap lookup duration: {:?}", duration);
-
-    let start = Instant::now();
-    for i in 0..1000 {
-        btree_map.get(&i);
-    }
-    let duration = start.elapsed();
-    println!("BTreeMap lookup duration: {:?}", duration);
-
-    let start = Instant::now();
-    for i in 0..1000 {
-        vec_deque.get(i);
-    }
-    let duration = start.elapsed();
-    println!("VecDeque lookup duration: {:?}", duration);
-
-    let start = Instant::now();
-    for i in 0..1000 {
-        linked_list.iter().nth(i);
-    }
-    let duration = start.elapsed();
-    println!("LinkedList lookup duration: {:?}", duration);
-
-    // Random delete/drop
-    let mut rng = thread_rng();
-    let mut indices: Vec<usize> = (0..1000).collect();
-    indices.shuffle(&mut rng);
-
-    // HashMap
-    let start = Instant::now();
-    for &i in &indices {
-        hash_map.remove(&i);
-    }
-    let duration = start.elapsed();
-    println!("HashMap random delete duration: {:?}", duration);
-
-    // BTreeMap
-    let start = Instant::now();
-    for &i in &indices {
-        btree_map.remove(&i);
-    }
-    let duration = start.elapsed();
-    println!("BTreeMap random delete duration: {:?}", duration);
-
-    // VecDeque
-    let start = Instant::now();
-    for &i in &indices {
-        if i < vec_deque.len() {
-            vec_deque.remove(i);
-        }
-    }
-    let duration = start.elapsed();
-    println!("VecDeque random delete duration: {:?}", duration);
-
-    // LinkedList (removing by value, since no index-based removal)
-    let start = Instant::now();
-    for &i in &indices {
-        // Remove all occurrences of i from the linked list
-        let mut new_list = LinkedList::new();
-        for &x in linked_list.iter() {
-            if x != i {
-                new_list.push_back(x);
+use rustc_hash::FxBuildHasher;
+
+use crate::lib_priorityqueue::{Handle, PriorityQueue};
+
+/// Sizes of the full suite, 10 to 10^7
+pub const SIZES: [usize; 7] = [10, 100, 1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+/// Sizes of the --quick smoke run
+pub const QUICK_SIZES: [usize; 2] = [10, 1_000];
+
+/// Keys looked up, removed or range queried per run
+pub const PROBES: usize = 1_000;
+
+/// Keys per range query
+pub const RANGE_WIDTH: u64 = 100;
+
+/// The collections under test. The HashMap variants compare hashers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collection {
+    HashMap,
+    HashMapFx,
+    HashMapAHash,
+    BTreeMap,
+    VecDeque,
+    LinkedList,
+    HashSet,
+    BTreeSet,
+    BinaryHeap,
+    PriorityQueue,
+}
+
+impl Collection {
+    pub const ALL: [Collection; 10] = [
+        Collection::HashMap,
+        Collection::HashMapFx,
+        Collection::HashMapAHash,
+        Collection::BTreeMap,
+        Collection::VecDeque,
+        Collection::LinkedList,
+        Collection::HashSet,
+        Collection::BTreeSet,
+        Collection::BinaryHeap,
+        Collection::PriorityQueue,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Collection::HashMap => "HashMap<SipHash>",
+            Collection::HashMapFx => "HashMap<FxHash>",
+            Collection::HashMapAHash => "HashMap<AHash>",
+            Collection::BTreeMap => "BTreeMap",
+            Collection::VecDeque => "VecDeque",
+            Collection::LinkedList => "LinkedList",
+            Collection::HashSet => "HashSet",
+            Collection::BTreeSet => "BTreeSet",
+            Collection::BinaryHeap => "BinaryHeap",
+            Collection::PriorityQueue => "PriorityQueue",
+        }
+    }
+
+    /// Whether the operation exists and stays affordable at this size.
+    /// LinkedList lookups walk the list and VecDeque removals shift it, so
+    /// both are capped.
+    pub fn supports(&self, operation: Operation, size: usize) -> bool {
+        match (self, operation) {
+            (
+                Collection::BTreeMap | Collection::BTreeSet | Collection::VecDeque,
+                Operation::Range,
+            ) => true,
+            (_, Operation::Range) => false,
+            (Collection::LinkedList, Operation::Lookup) => size <= 100_000,
+            (Collection::VecDeque, Operation::Remove) => size <= 1_000_000,
+            _ => true,
+        }
+    }
+
+    /// An empty collection
+    pub fn empty(&self) -> Box<dyn BenchCollection> {
+        match self {
+            Collection::HashMap => Box::new(HashMap::<u64, u64, RandomState>::default()),
+            Collection::HashMapFx => Box::new(HashMap::<u64, u64, FxBuildHasher>::default()),
+            Collection::HashMapAHash => {
+                Box::new(HashMap::<u64, u64, ahash::RandomState>::default())
+            }
+            Collection::BTreeMap => Box::new(BTreeMap::<u64, u64>::new()),
+            Collection::VecDeque => Box::new(Deque::default()),
+            Collection::LinkedList => Box::new(List::default()),
+            Collection::HashSet => Box::new(HashSet::<u64, RandomState>::default()),
+            Collection::BTreeSet => Box::new(BTreeSet::<u64>::new()),
+            Collection::BinaryHeap => Box::new(Heap::default()),
+            Collection::PriorityQueue => Box::new(Queue::default()),
+        }
+    }
+}
+
+impl fmt::Display for Collection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Insert,
+    Lookup,
+    Remove,
+    Iterate,
+    Range,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 5] = [
+        Operation::Insert,
+        Operation::Lookup,
+        Operation::Remove,
+        Operation::Iterate,
+        Operation::Range,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Insert => "insert",
+            Operation::Lookup => "lookup",
+            Operation::Remove => "remove",
+            Operation::Iterate => "iterate",
+            Operation::Range => "range",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What every benchmarked collection can do. Keys are stored as
+/// themselves, maps map a key to itself. The results are checksums, so the
+/// work cannot be optimised away.
+pub trait BenchCollection {
+    fn insert_all(&mut self, keys: &[u64]);
+    fn lookup(&self, probes: &[u64]) -> u64;
+    fn remove(&mut self, probes: &[u64]) -> u64;
+    /// Put back what the last remove took out
+    fn restore(&mut self, probes: &[u64]);
+    fn iterate(&self) -> u64;
+    /// Sum of the RANGE_WIDTH keys from each start
+    fn range(&self, _starts: &[u64]) -> u64 {
+        0
+    }
+    fn clear(&mut self);
+}
+
+impl<S: BuildHasher> BenchCollection for HashMap<u64, u64, S> {
+    fn insert_all(&mut self, keys: &[u64]) {
+        for &key in keys {
+            self.insert(key, key);
+        }
+    }
+
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes.iter().filter_map(|key| self.get(key)).sum()
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|key| HashMap::remove(self, key))
+            .sum()
+    }
+
+    fn restore(&mut self, probes: &[u64]) {
+        self.insert_all(probes);
+    }
+
+    fn iterate(&self) -> u64 {
+        self.values().sum()
+    }
+
+    fn clear(&mut self) {
+        HashMap::clear(self);
+    }
+}
+
+impl BenchCollection for BTreeMap<u64, u64> {
+    fn insert_all(&mut self, keys: &[u64]) {
+        for &key in keys {
+            self.insert(key, key);
+        }
+    }
+
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes.iter().filter_map(|key| self.get(key)).sum()
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|key| BTreeMap::remove(self, key))
+            .sum()
+    }
+
+    fn restore(&mut self, probes: &[u64]) {
+        self.insert_all(probes);
+    }
+
+    fn iterate(&self) -> u64 {
+        self.values().sum()
+    }
+
+    fn range(&self, starts: &[u64]) -> u64 {
+        starts
+            .iter()
+            .map(|&start| {
+                BTreeMap::range(self, start..start + RANGE_WIDTH)
+                    .map(|(_, v)| v)
+                    .sum::<u64>()
+            })
+            .sum()
+    }
+
+    fn clear(&mut self) {
+        BTreeMap::clear(self);
+    }
+}
+
+impl<S: BuildHasher> BenchCollection for HashSet<u64, S> {
+    fn insert_all(&mut self, keys: &[u64]) {
+        self.extend(keys.iter().copied());
+    }
+
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes.iter().filter(|key| self.contains(*key)).count() as u64
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter(|key| HashSet::remove(self, *key))
+            .count() as u64
+    }
+
+    fn restore(&mut self, probes: &[u64]) {
+        self.insert_all(probes);
+    }
+
+    fn iterate(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn clear(&mut self) {
+        HashSet::clear(self);
+    }
+}
+
+impl BenchCollection for BTreeSet<u64> {
+    fn insert_all(&mut self, keys: &[u64]) {
+        self.extend(keys.iter().copied());
+    }
+
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes.iter().filter(|key| self.contains(*key)).count() as u64
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter(|key| BTreeSet::remove(self, *key))
+            .count() as u64
+    }
+
+    fn restore(&mut self, probes: &[u64]) {
+        self.insert_all(probes);
+    }
+
+    fn iterate(&self) -> u64 {
+        self.iter().sum()
+    }
+
+    fn range(&self, starts: &[u64]) -> u64 {
+        starts
+            .iter()
+            .map(|&start| BTreeSet::range(self, start..start + RANGE_WIDTH).sum::<u64>())
+            .sum()
+    }
+
+    fn clear(&mut self) {
+        BTreeSet::clear(self);
+    }
+}
+
+// The sequences and heaps keep what remove took out, so restore can put
+// exactly that back
+#[derive(Default)]
+struct Deque {
+    items: VecDeque<u64>,
+    removed: Vec<(usize, u64)>,
+}
+
+impl BenchCollection for Deque {
+    fn insert_all(&mut self, keys: &[u64]) {
+        self.items.extend(keys.iter().copied());
+    }
+
+    /// By index
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|&i| self.items.get(i as usize))
+            .sum()
+    }
+
+    /// By index, wrapped to the shrinking length
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        let mut sum = 0;
+        for &i in probes {
+            if self.items.is_empty() {
+                break;
+            }
+            let index = i as usize % self.items.len();
+            if let Some(value) = self.items.remove(index) {
+                self.removed.push((index, value));
+                sum += value;
+            }
+        }
+        sum
+    }
+
+    fn restore(&mut self, _probes: &[u64]) {
+        while let Some((index, value)) = self.removed.pop() {
+            self.items.insert(index, value);
+        }
+    }
+
+    fn iterate(&self) -> u64 {
+        self.items.iter().sum()
+    }
+
+    /// Index ranges
+    fn range(&self, starts: &[u64]) -> u64 {
+        let len = self.items.len();
+        starts
+            .iter()
+            .map(|&start| {
+                let start = (start as usize).min(len);
+                let end = (start + RANGE_WIDTH as usize).min(len);
+                self.items.range(start..end).sum::<u64>()
+            })
+            .sum()
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+#[derive(Default)]
+struct List {
+    items: LinkedList<u64>,
+    removed: Vec<u64>,
+}
+
+impl BenchCollection for List {
+    fn insert_all(&mut self, keys: &[u64]) {
+        self.items.extend(keys.iter().copied());
+    }
+
+    /// By position, walking the list each time
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|&i| self.items.iter().nth(i as usize))
+            .sum()
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        let mut sum = 0;
+        for _ in probes {
+            if let Some(value) = self.items.pop_front() {
+                self.removed.push(value);
+                sum += value;
+            }
+        }
+        sum
+    }
+
+    fn restore(&mut self, _probes: &[u64]) {
+        while let Some(value) = self.removed.pop() {
+            self.items.push_front(value);
+        }
+    }
+
+    fn iterate(&self) -> u64 {
+        self.items.iter().sum()
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+#[derive(Default)]
+struct Heap {
+    items: BinaryHeap<u64>,
+    removed: Vec<u64>,
+}
+
+impl BenchCollection for Heap {
+    fn insert_all(&mut self, keys: &[u64]) {
+        self.items.extend(keys.iter().copied());
+    }
+
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|_| black_box(&self.items).peek())
+            .sum()
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        let mut sum = 0;
+        for _ in probes {
+            if let Some(value) = self.items.pop() {
+                self.removed.push(value);
+                sum += value;
             }
         }
-        linked_list = new_list;
+        sum
+    }
+
+    fn restore(&mut self, _probes: &[u64]) {
+        self.items.extend(self.removed.drain(..));
+    }
+
+    fn iterate(&self) -> u64 {
+        self.items.iter().sum()
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+// The handle of every key, so keys can be found and removed in O(log n)
+#[derive(Default)]
+struct Queue {
+    items: PriorityQueue<u64>,
+    handles: HashMap<u64, Handle, FxBuildHasher>,
+}
+
+impl BenchCollection for Queue {
+    fn insert_all(&mut self, keys: &[u64]) {
+        for &key in keys {
+            let handle = self.items.push(key);
+            self.handles.insert(key, handle);
+        }
+    }
+
+    fn lookup(&self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|key| self.items.get(*self.handles.get(key)?))
+            .sum()
+    }
+
+    fn remove(&mut self, probes: &[u64]) -> u64 {
+        probes
+            .iter()
+            .filter_map(|key| self.items.remove(*self.handles.get(key)?))
+            .sum()
+    }
+
+    fn restore(&mut self, probes: &[u64]) {
+        self.insert_all(probes);
+    }
+
+    fn iterate(&self) -> u64 {
+        self.items.iter().sum()
+    }
+
+    fn clear(&mut self) {
+        self.items.clear();
+        self.handles.clear();
+    }
+}
+
+/// One collection, operation and size, see the comment at the top of the file.
+pub struct Workload {
+    pub collection: Collection,
+    pub operation: Operation,
+    pub size: usize,
+    keys: Vec<u64>,
+    probes: Vec<u64>,
+}
+
+impl Workload {
+    /// None when the collection does not support the operation at this size
+    pub fn new(
+        collection: Collection,
+        operation: Operation,
+        size: usize,
+        seed: u64,
+    ) -> Option<Self> {
+        if !collection.supports(operation, size) {
+            return None;
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut keys: Vec<u64> = (0..size as u64).collect();
+        keys.shuffle(&mut rng);
+        // Distinct keys, so every removal finds something
+        let probes = keys[..size.min(PROBES)].to_vec();
+        Some(Workload {
+            collection,
+            operation,
+            size,
+            keys,
+            probes,
+        })
+    }
+
+    /// Elements touched by one run, to report the time per element
+    pub fn elements(&self) -> usize {
+        match self.operation {
+            Operation::Insert | Operation::Iterate => self.size,
+            Operation::Lookup | Operation::Remove => self.probes.len(),
+            Operation::Range => self.probes.len() * RANGE_WIDTH as usize,
+        }
+    }
+
+    /// The collection a run starts from: empty for insert, full otherwise
+    pub fn setup(&self) -> Box<dyn BenchCollection> {
+        let mut collection = self.collection.empty();
+        if self.operation != Operation::Insert {
+            collection.insert_all(&self.keys);
+        }
+        collection
+    }
+
+    /// The timed part
+    pub fn run(&self, collection: &mut dyn BenchCollection) -> u64 {
+        match self.operation {
+            Operation::Insert => {
+                collection.insert_all(&self.keys);
+                0
+            }
+            Operation::Lookup => collection.lookup(&self.probes),
+            Operation::Remove => collection.remove(&self.probes),
+            Operation::Iterate => collection.iterate(),
+            Operation::Range => collection.range(&self.probes),
+        }
+    }
+
+    /// Undo a run, so the next one sees the same collection
+    pub fn reset(&self, collection: &mut dyn BenchCollection) {
+        match self.operation {
+            Operation::Insert => collection.clear(),
+            Operation::Remove => collection.restore(&self.probes),
+            _ => {}
+        }
+    }
+
+    /// Time `iterations` runs, resetting after each one untimed
+    pub fn time(&self, collection: &mut dyn BenchCollection, iterations: u64) -> Duration {
+        let mut total = Duration::ZERO;
+        for _ in 0..iterations {
+            let start = Instant::now();
+            black_box(self.run(collection));
+            total += start.elapsed();
+            self.reset(collection);
+        }
+        total
+    }
+}
+
+/// Output format of a Report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "md" | "markdown" => Ok(Format::Markdown),
+            other => Err(format!(
+                "unknown format '{}'. Choose from: csv, markdown",
+                other
+            )),
+        }
+    }
+}
+
+/// What run_suite measures
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub sizes: Vec<usize>,
+    pub collections: Vec<Collection>,
+    pub operations: Vec<Operation>,
+    pub samples: usize,
+    /// Each sample repeats the run until it takes at least this long
+    pub min_sample: Duration,
+    pub seed: u64,
+}
+
+impl Config {
+    /// Every collection and operation at every size
+    pub fn full() -> Self {
+        Config {
+            sizes: SIZES.to_vec(),
+            collections: Collection::ALL.to_vec(),
+            operations: Operation::ALL.to_vec(),
+            samples: 10,
+            min_sample: Duration::from_millis(5),
+            seed: 42,
+        }
+    }
+
+    /// Every collection and operation at two small sizes with few short
+    /// samples, fast enough for tests
+    pub fn quick() -> Self {
+        Config {
+            sizes: QUICK_SIZES.to_vec(),
+            samples: 3,
+            min_sample: Duration::from_micros(100),
+            ..Config::full()
+        }
+    }
+
+    /// Leave out the sizes above `max`
+    pub fn max_size(mut self, max: usize) -> Self {
+        self.sizes.retain(|size| *size <= max);
+        self
+    }
+}
+
+/// Time per element of one workload, in nanoseconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub collection: Collection,
+    pub operation: Operation,
+    pub size: usize,
+    pub samples: usize,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Half width of the 95% confidence interval of the mean
+    pub ci95: f64,
+}
+
+impl Measurement {
+    /// Statistics of the samples of a workload. The standard deviation is
+    /// the sample one, the confidence interval uses the normal 1.96.
+    pub fn from_samples(workload: &Workload, samples: &[f64]) -> Self {
+        let n = samples.len() as f64;
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let median = match sorted.len() {
+            0 => 0.0,
+            len if len % 2 == 1 => sorted[len / 2],
+            len => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+        };
+        let mean = samples.iter().sum::<f64>() / n.max(1.0);
+        let variance = if samples.len() > 1 {
+            samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        let stddev = variance.sqrt();
+        Measurement {
+            collection: workload.collection,
+            operation: workload.operation,
+            size: workload.size,
+            samples: samples.len(),
+            median,
+            mean,
+            stddev,
+            ci95: 1.96 * stddev / n.max(1.0).sqrt(),
+        }
+    }
+}
+
+/// Measure one workload, see the comment at the top of the file
+pub fn measure(workload: &Workload, config: &Config) -> Measurement {
+    let mut collection = workload.setup();
+
+    // Warm up and find how many runs fill a sample
+    let mut iterations = 1;
+    while workload.time(collection.as_mut(), iterations) < config.min_sample {
+        iterations *= 2;
+    }
+
+    let elements = workload.elements().max(1) as f64;
+    let samples: Vec<f64> = (0..config.samples)
+        .map(|_| {
+            let elapsed = workload.time(collection.as_mut(), iterations);
+            elapsed.as_nanos() as f64 / iterations as f64 / elements
+        })
+        .collect();
+    Measurement::from_samples(workload, &samples)
+}
+
+/// Every supported workload of a Config, by size, operation and collection
+pub fn run_suite(config: &Config) -> Report {
+    let mut measurements = Vec::new();
+    for &size in &config.sizes {
+        for &operation in &config.operations {
+            for &collection in &config.collections {
+                if let Some(workload) = Workload::new(collection, operation, size, config.seed) {
+                    measurements.push(measure(&workload, config));
+                }
+            }
+        }
+    }
+    Report { measurements }
+}
+
+/// The measurements of a suite run
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+const HEADER: [&str; 8] = [
+    "collection",
+    "operation",
+    "size",
+    "samples",
+    "median_ns",
+    "mean_ns",
+    "stddev_ns",
+    "ci95_ns",
+];
+
+impl Report {
+    fn rows(&self) -> impl Iterator<Item = [String; 8]> + '_ {
+        self.measurements.iter().map(|m| {
+            [
+                m.collection.to_string(),
+                m.operation.to_string(),
+                m.size.to_string(),
+                m.samples.to_string(),
+                format!("{:.2}", m.median),
+                format!("{:.2}", m.mean),
+                format!("{:.2}", m.stddev),
+                format!("{:.2}", m.ci95),
+            ]
+        })
+    }
+
+    /// One line per measurement, times in nanoseconds per element
+    pub fn to_csv(&self) -> String {
+        let mut out = HEADER.join(",") + "\n";
+        for row in self.rows() {
+            out += &row.join(",");
+            out += "\n";
+        }
+        out
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("| {} |\n", HEADER.join(" | "));
+        out += "|---|---|---:|---:|---:|---:|---:|---:|\n";
+        for row in self.rows() {
+            out += &format!("| {} |\n", row.join(" | "));
+        }
+        out
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Csv => self.to_csv(),
+            Format::Markdown => self.to_markdown(),
+        }
     }
-    let duration = start.elapsed();
-    println!("LinkedList random delete duration: {:?}", duration);
 }
 
 // Test code
@@ -120,7 +814,91 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_benchmark_collections() {
-        benchmark_collections();
+    fn test_quick_suite() {
+        let report = run_suite(&Config::quick());
+        // 10 collections x 4 common operations + 3 with ranges, at 2 sizes
+        assert_eq!(report.measurements.len(), 2 * (10 * 4 + 3));
+        for m in &report.measurements {
+            assert_eq!(m.samples, 3);
+            assert!(
+                m.median > 0.0,
+                "{} {} {}",
+                m.collection,
+                m.operation,
+                m.size
+            );
+            assert!(m.ci95 >= 0.0);
+        }
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("collection,operation,size,samples,median_ns,mean_ns,stddev_ns,ci95_ns")
+        );
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("HashMap<SipHash>,insert,10,3,")
+        );
+        assert_eq!(
+            report.to_markdown().lines().count(),
+            2 + report.measurements.len()
+        );
+    }
+
+    #[test]
+    fn test_runs_are_repeatable() {
+        for collection in Collection::ALL {
+            for operation in Operation::ALL {
+                let Some(workload) = Workload::new(collection, operation, 500, 1) else {
+                    continue;
+                };
+                let mut built = workload.setup();
+                let first = workload.run(built.as_mut());
+                workload.reset(built.as_mut());
+                let second = workload.run(built.as_mut());
+                assert_eq!(first, second, "{} {}", collection, operation);
+                workload.reset(built.as_mut());
+                assert_eq!(
+                    built.iterate(),
+                    workload.setup().iterate(),
+                    "{} {}",
+                    collection,
+                    operation
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_workload_checksums() {
+        let all_keys: u64 = (0..500).sum();
+        let lookup = Workload::new(Collection::BTreeMap, Operation::Lookup, 500, 1).unwrap();
+        assert_eq!(lookup.run(lookup.setup().as_mut()), all_keys);
+        let iterate = Workload::new(Collection::LinkedList, Operation::Iterate, 500, 1).unwrap();
+        assert_eq!(iterate.run(iterate.setup().as_mut()), all_keys);
+        assert_eq!(iterate.elements(), 500);
+
+        assert!(Workload::new(Collection::HashMap, Operation::Range, 10, 1).is_none());
+        assert!(Workload::new(Collection::LinkedList, Operation::Lookup, 1_000_000, 1).is_none());
+    }
+
+    #[test]
+    fn test_statistics() {
+        let workload = Workload::new(Collection::HashSet, Operation::Insert, 10, 1).unwrap();
+        let m = Measurement::from_samples(&workload, &[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(m.median, 2.5);
+        assert_eq!(m.mean, 2.5);
+        assert!((m.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!((m.ci95 - 1.96 * m.stddev / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_config_and_format() {
+        assert_eq!(Config::full().max_size(1_000).sizes, [10, 100, 1_000]);
+        assert_eq!("MD".parse::<Format>(), Ok(Format::Markdown));
+        assert!("xml".parse::<Format>().is_err());
     }
 }
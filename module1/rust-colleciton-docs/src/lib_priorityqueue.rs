@@ -1,32 +1,148 @@
-use std::collections::BinaryHeap;
+// An indexed binary heap.
+//
+// The queue is a max-heap by default, a min-heap with `new_min` or ordered
+// by any comparator with `with_comparator`: the item the comparator calls
+// greatest comes out first. Every push returns a Handle. The queue keeps
+// the heap position of each handle, so an item can be re-prioritised or
+// removed in O(log n) without rebuilding the heap.
+//
+// A handle is a slot and a generation. The slot of an item that leaves the
+// queue goes on a free list for the next push, and its generation goes up,
+// so the queue never holds more slots than it once held items, and a stale
+// handle is not mistaken for the newer item in its slot.
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
 
-/// A priority queue that sorts items by priority using a BinaryHeap.
-#[derive(Debug)]
+/// Refers to one item of the PriorityQueue that returned it. A handle is
+/// valid until its item is popped, removed or drained, and never refers to
+/// another item after that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    slot: usize,
+    generation: u64,
+}
+
+// The heap position of the item in a slot, None while the slot is free
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    generation: u64,
+    position: Option<usize>,
+}
+
+/// Min or max queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Greatest item first, like BinaryHeap
+    Max,
+    /// Smallest item first
+    Min,
+}
+
+/// Why a key could not be changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityError {
+    /// The handle is not (or no longer) in the queue
+    UnknownHandle,
+    /// decrease_key was given an item that comes out later than the old one
+    WrongDirection,
+}
+
+impl fmt::Display for PriorityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PriorityError::UnknownHandle => write!(f, "handle is not in the queue"),
+            PriorityError::WrongDirection => {
+                write!(f, "new key would move the item away from the front")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PriorityError {}
+
+type Comparator<T> = Arc<dyn Fn(&T, &T) -> Ordering + Send + Sync>;
+
+/// A priority queue with handles, see the comment at the top of the file.
 pub struct PriorityQueue<T> {
-    heap: BinaryHeap<T>,
+    heap: Vec<(Handle, T)>,
+    slots: Vec<Slot>,
+    // Slots whose item left the queue, for the next pushes
+    free: Vec<usize>,
+    cmp: Comparator<T>,
 }
 
 impl<T: Ord> PriorityQueue<T> {
-    /// Creates a new, empty PriorityQueue.
+    /// Creates a new, empty max-queue.
     pub fn new() -> Self {
+        Self::with_order(Order::Max)
+    }
+
+    /// Creates a new, empty min-queue.
+    pub fn new_min() -> Self {
+        Self::with_order(Order::Min)
+    }
+
+    pub fn with_order(order: Order) -> Self {
+        match order {
+            Order::Max => Self::with_comparator(|a: &T, b: &T| a.cmp(b)),
+            Order::Min => Self::with_comparator(|a: &T, b: &T| b.cmp(a)),
+        }
+    }
+}
+
+impl<T: Ord> Default for PriorityQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PriorityQueue<T> {
+    /// Creates a new, empty queue where the greatest item by `cmp` comes out
+    /// first.
+    pub fn with_comparator(cmp: impl Fn(&T, &T) -> Ordering + Send + Sync + 'static) -> Self {
         PriorityQueue {
-            heap: BinaryHeap::new(),
+            heap: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            cmp: Arc::new(cmp),
         }
     }
 
     /// Inserts an item into the priority queue.
-    pub fn push(&mut self, item: T) {
-        self.heap.push(item);
+    pub fn push(&mut self, item: T) -> Handle {
+        let position = Some(self.heap.len());
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot].position = position;
+                slot
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    position,
+                });
+                self.slots.len() - 1
+            }
+        };
+        let handle = Handle {
+            slot,
+            generation: self.slots[slot].generation,
+        };
+        self.heap.push((handle, item));
+        self.sift_up(self.heap.len() - 1);
+        handle
     }
 
     /// Removes and returns the item with the highest priority, or None if empty.
     pub fn pop(&mut self) -> Option<T> {
-        self.heap.pop()
+        let (handle, _) = self.heap.first()?;
+        self.remove(*handle)
     }
 
     /// Returns a reference to the item with the highest priority, or None if empty.
     pub fn peek(&self) -> Option<&T> {
-        self.heap.peek()
+        self.heap.first().map(|(_, item)| item)
     }
 
     /// Returns true if the queue is empty.
@@ -38,11 +154,187 @@ impl<T: Ord> PriorityQueue<T> {
     pub fn len(&self) -> usize {
         self.heap.len()
     }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|pos| &self.heap[pos].1)
+    }
+
+    /// Removes the item of a handle, O(log n).
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let pos = self.position(handle)?;
+        let last = self.heap.len() - 1;
+        self.swap(pos, last);
+        let (_, item) = self.heap.pop()?;
+        self.release(handle);
+        if pos < last {
+            self.sift_up(pos);
+            self.sift_down(pos);
+        }
+        Some(item)
+    }
+
+    /// Replaces the item of a handle and moves it to its new place, O(log n).
+    /// Returns the old item.
+    pub fn change_priority(&mut self, handle: Handle, item: T) -> Result<T, PriorityError> {
+        let pos = self.position(handle).ok_or(PriorityError::UnknownHandle)?;
+        let old = std::mem::replace(&mut self.heap[pos].1, item);
+        let pos = self.sift_up(pos);
+        self.sift_down(pos);
+        Ok(old)
+    }
+
+    /// Like change_priority, but only towards the front of the queue: a
+    /// smaller item in a min-queue, a greater one in a max-queue. An item
+    /// that would move back leaves the queue unchanged.
+    pub fn decrease_key(&mut self, handle: Handle, item: T) -> Result<T, PriorityError> {
+        let pos = self.position(handle).ok_or(PriorityError::UnknownHandle)?;
+        if (self.cmp)(&item, &self.heap[pos].1) == Ordering::Less {
+            return Err(PriorityError::WrongDirection);
+        }
+        let old = std::mem::replace(&mut self.heap[pos].1, item);
+        self.sift_up(pos);
+        Ok(old)
+    }
+
+    /// Items in heap (not sorted) order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter().map(|(_, item)| item)
+    }
+
+    /// Removes all items, in heap (not sorted) order. Every handle becomes
+    /// invalid.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        for i in 0..self.heap.len() {
+            self.release(self.heap[i].0);
+        }
+        self.heap.drain(..).map(|(_, item)| item)
+    }
+
+    pub fn clear(&mut self) {
+        self.drain().for_each(drop);
+    }
+
+    /// The items in heap (not sorted) order
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// The items sorted so that the last one would be popped first, like
+    /// BinaryHeap::into_sorted_vec: ascending for a max-queue, descending
+    /// for a min-queue.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let cmp = self.cmp;
+        let mut items: Vec<T> = self.heap.into_iter().map(|(_, item)| item).collect();
+        items.sort_by(|a, b| cmp(a, b));
+        items
+    }
+
+    /// Moves all items of `other` into this queue, keeping this queue's
+    /// order. The handles of `other` do not carry over.
+    pub fn merge(&mut self, mut other: PriorityQueue<T>) {
+        self.extend(other.drain());
+    }
+
+    fn position(&self, handle: Handle) -> Option<usize> {
+        let slot = self.slots.get(handle.slot)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.position
+    }
+
+    // Frees the slot of an item that left the heap
+    fn release(&mut self, handle: Handle) {
+        let slot = &mut self.slots[handle.slot];
+        slot.position = None;
+        slot.generation += 1;
+        self.free.push(handle.slot);
+    }
+
+    fn greater(&self, i: usize, j: usize) -> bool {
+        (self.cmp)(&self.heap[i].1, &self.heap[j].1) == Ordering::Greater
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.slots[self.heap[i].0.slot].position = Some(i);
+        self.slots[self.heap[j].0.slot].position = Some(j);
+    }
+
+    // Returns the new position
+    fn sift_up(&mut self, mut pos: usize) -> usize {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.greater(pos, parent) {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+        pos
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut top = pos;
+            for child in [2 * pos + 1, 2 * pos + 2] {
+                if child < self.heap.len() && self.greater(child, top) {
+                    top = child;
+                }
+            }
+            if top == pos {
+                break;
+            }
+            self.swap(pos, top);
+            pos = top;
+        }
+    }
+}
+
+impl<T: Clone> Clone for PriorityQueue<T> {
+    fn clone(&self) -> Self {
+        PriorityQueue {
+            heap: self.heap.clone(),
+            slots: self.slots.clone(),
+            free: self.free.clone(),
+            cmp: Arc::clone(&self.cmp),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PriorityQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for PriorityQueue<T> {
+    /// A max-queue of the items
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut queue = PriorityQueue::new();
+        queue.extend(iter);
+        queue
+    }
+}
+
+impl<T> Extend<T> for PriorityQueue<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PriorityQueue;
+    use super::{Order, PriorityError, PriorityQueue};
+    use proptest::prelude::*;
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
 
     #[test]
     fn test_priority_queue() {
@@ -59,4 +351,184 @@ mod tests {
         assert_eq!(pq.pop(), Some(1));
         assert!(pq.is_empty());
     }
+
+    #[test]
+    fn test_min_queue_and_comparator() {
+        let mut pq: PriorityQueue<i32> = [4, 1, 3].into_iter().collect();
+        assert_eq!(pq.peek(), Some(&4));
+
+        pq = PriorityQueue::with_order(Order::Min);
+        pq.extend([4, 1, 3]);
+        assert_eq!(pq.pop(), Some(1));
+
+        // Shortest word first, then alphabetical
+        let mut words = PriorityQueue::with_comparator(|a: &&str, b: &&str| {
+            b.len().cmp(&a.len()).then_with(|| b.cmp(a))
+        });
+        words.extend(["pear", "fig", "apple", "kiwi"]);
+        assert_eq!(
+            words.clone().into_sorted_vec(),
+            ["apple", "pear", "kiwi", "fig"]
+        );
+        let order: Vec<&str> = std::iter::from_fn(|| words.pop()).collect();
+        assert_eq!(order, ["fig", "kiwi", "pear", "apple"]);
+    }
+
+    #[test]
+    fn test_handles() {
+        let mut pq = PriorityQueue::new_min();
+        let a = pq.push(10);
+        let b = pq.push(20);
+        let c = pq.push(30);
+
+        assert_eq!(pq.decrease_key(c, 5), Ok(30));
+        assert_eq!(pq.peek(), Some(&5));
+        assert_eq!(pq.decrease_key(a, 15), Err(PriorityError::WrongDirection));
+        assert_eq!(pq.get(a), Some(&10));
+
+        assert_eq!(pq.change_priority(c, 40), Ok(5));
+        assert_eq!(pq.remove(b), Some(20));
+        assert_eq!(pq.remove(b), None);
+        assert_eq!(pq.change_priority(b, 1), Err(PriorityError::UnknownHandle));
+        assert!(!pq.contains(b));
+
+        assert_eq!(pq.pop(), Some(10));
+        assert!(!pq.contains(a));
+        assert_eq!(pq.get(c), Some(&40));
+    }
+
+    #[test]
+    fn test_drain_and_merge() {
+        let mut a: PriorityQueue<i32> = [1, 7, 3].into_iter().collect();
+        let b: PriorityQueue<i32> = [6, 2].into_iter().collect();
+        let handle = a.push(4);
+        a.merge(b);
+        assert_eq!(a.len(), 6);
+        assert_eq!(a.clone().into_sorted_vec(), [1, 2, 3, 4, 6, 7]);
+
+        let mut drained: Vec<i32> = a.drain().collect();
+        drained.sort();
+        assert_eq!(drained, [1, 2, 3, 4, 6, 7]);
+        assert!(a.is_empty());
+        assert!(!a.contains(handle));
+        assert_eq!(format!("{:?}", a), "[]");
+    }
+
+    #[test]
+    fn test_slots_are_reused() {
+        let mut pq = PriorityQueue::new();
+        let first = pq.push(1);
+        for round in 0..1000 {
+            let handle = pq.push(round);
+            assert_eq!(pq.remove(handle), Some(round));
+            assert!(!pq.contains(handle));
+        }
+        assert_eq!(pq.slots.len(), 2);
+
+        // A stale handle does not reach the item now in its slot
+        let old = pq.push(5);
+        assert_eq!(pq.pop(), Some(5));
+        let new = pq.push(7);
+        assert_eq!(pq.get(old), None);
+        assert_eq!(pq.remove(old), None);
+        assert_eq!(pq.get(new), Some(&7));
+
+        pq.clear();
+        assert!(!pq.contains(first));
+        assert!(!pq.contains(new));
+        pq.extend([3, 4]);
+        assert_eq!(pq.slots.len(), 2);
+        assert_eq!(pq.len(), 2);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Push(i32),
+        Pop,
+        Peek,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            3 => any::<i32>().prop_map(Op::Push),
+            2 => Just(Op::Pop),
+            1 => Just(Op::Peek),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_binary_heap(ops in prop::collection::vec(op(), 0..200)) {
+            let mut ours = PriorityQueue::new();
+            let mut std_heap = BinaryHeap::new();
+            for op in ops {
+                match op {
+                    Op::Push(x) => {
+                        ours.push(x);
+                        std_heap.push(x);
+                    }
+                    Op::Pop => prop_assert_eq!(ours.pop(), std_heap.pop()),
+                    Op::Peek => prop_assert_eq!(ours.peek(), std_heap.peek()),
+                }
+                prop_assert_eq!(ours.len(), std_heap.len());
+            }
+            prop_assert_eq!(ours.into_sorted_vec(), std_heap.into_sorted_vec());
+        }
+
+        #[test]
+        fn prop_min_matches_reversed_heap(items in prop::collection::vec(any::<i32>(), 0..100)) {
+            let mut ours = PriorityQueue::new_min();
+            ours.extend(items.iter().copied());
+            let mut std_heap: BinaryHeap<Reverse<i32>> = items.iter().copied().map(Reverse).collect();
+            while let Some(Reverse(x)) = std_heap.pop() {
+                prop_assert_eq!(ours.pop(), Some(x));
+            }
+            prop_assert!(ours.is_empty());
+        }
+
+        #[test]
+        fn prop_remove_and_change(
+            items in prop::collection::vec(-100..100i32, 1..100),
+            picks in prop::collection::vec((any::<prop::sample::Index>(), -100..100i32, any::<bool>()), 0..50),
+        ) {
+            let mut ours = PriorityQueue::new();
+            let mut handles: Vec<_> = items.iter().map(|x| ours.push(*x)).collect();
+            let mut expected = items.clone();
+            for (index, value, remove) in picks {
+                if handles.is_empty() {
+                    break;
+                }
+                let i = index.index(handles.len());
+                let old = if remove {
+                    let handle = handles.swap_remove(i);
+                    ours.remove(handle).unwrap()
+                } else {
+                    ours.change_priority(handles[i], value).unwrap()
+                };
+                let at = expected.iter().position(|x| *x == old).unwrap();
+                if remove {
+                    expected.swap_remove(at);
+                } else {
+                    expected[at] = value;
+                }
+            }
+            let mut std_heap: BinaryHeap<i32> = expected.into_iter().collect();
+            prop_assert_eq!(ours.len(), std_heap.len());
+            while let Some(x) = std_heap.pop() {
+                prop_assert_eq!(ours.pop(), Some(x));
+            }
+        }
+
+        #[test]
+        fn prop_merge_matches_append(
+            a in prop::collection::vec(any::<i32>(), 0..50),
+            b in prop::collection::vec(any::<i32>(), 0..50),
+        ) {
+            let mut ours: PriorityQueue<i32> = a.iter().copied().collect();
+            ours.merge(b.iter().copied().collect());
+            let mut std_a: BinaryHeap<i32> = a.into_iter().collect();
+            std_a.append(&mut b.into_iter().collect());
+            prop_assert_eq!(ours.into_sorted_vec(), std_a.into_sorted_vec());
+        }
+    }
 }
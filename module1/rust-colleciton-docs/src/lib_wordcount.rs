@@ -1,16 +1,14 @@
 use std::collections::HashMap;
 
+use word_freq::{Options, count_text};
+
 // Implement a simple word counter in Rust using a HashMap and the entry API.
 
 /// Counts the occurrences of each word in the given text.
-/// Words are split by whitespace.
+/// Words are split by the Unicode word rules and lower cased, see the
+/// word-freq crate.
 pub fn word_count(text: &str) -> HashMap<String, usize> {
-    let mut counts = HashMap::new();
-    for word in text.split_whitespace() {
-        let word = word.to_lowercase();
-        *counts.entry(word).or_insert(0) += 1;
-    }
-    counts
+    count_text(text, &Options::new()).into_map()
 }
 
 #[cfg(test)]
@@ -24,4 +22,11 @@ mod tests {
         assert_eq!(result.get("hello"), Some(&2));
         assert_eq!(result.get("world"), Some(&1));
     }
+
+    #[test]
+    fn test_word_count_punctuation() {
+        let result = word_count("Hello, world! Hello.");
+        assert_eq!(result.get("hello"), Some(&2));
+        assert_eq!(result.len(), 2);
+    }
 }
@@ -1,13 +1,13 @@
 use std::collections::{HashMap, BTreeSet};
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use rust_colleciton_docs::lib_benchmark::{self, Config, Format};
+use rust_colleciton_docs::lib_priorityqueue;
 
-mod lib_benchmark;
-mod lib_priorityqueue;
 mod lib_wordcount;
 mod lib_test;
 
-fn main_with_clap() {
-    let matches = Command::new("rust-collection-docs")
+fn cli() -> Command {
+    Command::new("rust-collection-docs")
         .version("1.0")
         .author("Author Name")
         .about("Parses command line arguments using Vec, HashMap, and BTreeSet")
@@ -17,16 +17,55 @@ fn main_with_clap() {
                 .required(false)
                 .num_args(0..)
         )
-        .get_matches();
+        .arg(
+            Arg::new("bench")
+                .long("bench")
+                .help("Benchmark the collections and print the results table")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("quick")
+                .long("quick")
+                .help("With --bench: two small sizes and few samples, a smoke run")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("With --bench: csv or markdown")
+                .default_value("markdown")
+                .value_parser(|s: &str| s.parse::<Format>())
+        )
+        .arg(
+            Arg::new("max-size")
+                .long("max-size")
+                .help("With --bench: leave out sizes above this")
+                .value_parser(clap::value_parser!(usize))
+        )
+}
 
+fn main_with_clap(matches: &ArgMatches) {
     let args: Vec<String> = matches
         .get_many::<String>("args")
         .map(|vals| vals.cloned().collect())
-        .unwrap_or_else(Vec::new);
+        .unwrap_or_default();
 
     parse_args(args);
 }
 
+fn run_benchmarks(matches: &ArgMatches) {
+    let mut config = if matches.get_flag("quick") {
+        Config::quick()
+    } else {
+        Config::full()
+    };
+    if let Some(max) = matches.get_one::<usize>("max-size") {
+        config = config.max_size(*max);
+    }
+    let format = *matches.get_one::<Format>("format").expect("has a default");
+    print!("{}", lib_benchmark::run_suite(&config).render(format));
+}
+
 fn parse_args(args: Vec<String>) {
     // Store argument counts in a HashMap
     let mut arg_counts = HashMap::new();
@@ -79,17 +118,31 @@ fn test_priority_queue() {
     assert_eq!(pq.pop(), Some(3));
     assert_eq!(pq.pop(), Some(1));
     assert!(pq.is_empty());
-}
 
-fn test_benchmark_collections() {
-    lib_benchmark::benchmark_collections();
+    // Min-queue with handles: change an item's priority or remove it
+    let mut tasks = lib_priorityqueue::PriorityQueue::new_min();
+    let write = tasks.push((3, "write docs"));
+    tasks.push((2, "review"));
+    let deploy = tasks.push((5, "deploy"));
+    tasks.decrease_key(write, (1, "write docs")).unwrap();
+    tasks.remove(deploy);
+    println!("Tasks by priority: {:?}", tasks.clone().into_sorted_vec());
+
+    assert_eq!(tasks.pop(), Some((1, "write docs")));
+    assert_eq!(tasks.pop(), Some((2, "review")));
+    assert!(tasks.is_empty());
 }
 
 fn main() {
+    let matches = cli().get_matches();
+    if matches.get_flag("bench") {
+        run_benchmarks(&matches);
+        return;
+    }
+
     test_word_count();
     test_priority_queue();
-    test_benchmark_collections();
     test_parse_args();
-    main_with_clap();
+    main_with_clap(&matches);
     lib_test::test_custom_struct();
 }
@@ -1,29 +1,344 @@
-use std::slice::Iter;
+/*
+A generic collection backed by a Vec, meant as a template for domain
+collections.
 
-#[derive(Debug)]
-pub struct MyCollection<T> {
+The second type parameter picks the semantics:
+- List (the default) keeps every item, like a Vec.
+- Set keeps each item once. Adding an item that is already there does
+  nothing, so there is no mutable access to single items, which could
+  make two of them equal. Checking for a duplicate compares with every
+  item, O(n), so items only need PartialEq. Two sets with the same items
+  are equal, hash the same and compare equal whatever order the items
+  were added in; lists compare item by item, like a Vec.
+
+Both dereference to a slice, so everything a slice can do (get, first,
+contains, binary_search, windows, ...) works on them too. With the serde
+feature a collection serializes as a sequence.
+*/
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+use std::slice::{Iter, IterMut, SliceIndex};
+
+/// Every item is kept, in the order it was added
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct List;
+
+/// Each item is kept once, in the order it was first added
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Set;
+
+#[derive(Clone)]
+pub struct MyCollection<T, S = List> {
     items: Vec<T>,
+    semantics: PhantomData<S>,
 }
 
-impl<T> MyCollection<T> {
-    pub fn new() -> Self {
-        MyCollection { items: Vec::new() }
+/// A MyCollection with set semantics
+pub type MySet<T> = MyCollection<T, Set>;
+
+// Shown as a plain list, like a Vec
+impl<T: fmt::Debug, S> fmt::Debug for MyCollection<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(&self.items).finish()
     }
+}
 
-    pub fn add(&mut self, item: T) {
-        self.items.push(item);
+impl<T: PartialEq> PartialEq for MyCollection<T, List> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<T: Eq> Eq for MyCollection<T, List> {}
+
+impl<T: Hash> Hash for MyCollection<T, List> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.items.hash(state);
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for MyCollection<T, List> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.items.partial_cmp(&other.items)
+    }
+}
+
+impl<T: Ord> Ord for MyCollection<T, List> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.items.cmp(&other.items)
+    }
+}
+
+// The items of a set are unique, so the same length and every item in the
+// other set means the same items
+impl<T: PartialEq> PartialEq for MyCollection<T, Set> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|item| other.items.contains(item))
+    }
+}
+
+impl<T: Eq> Eq for MyCollection<T, Set> {}
+
+// The sum of the item hashes does not depend on their order
+impl<T: Hash> Hash for MyCollection<T, Set> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let sum = self.iter().fold(0u64, |sum, item| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            sum.wrapping_add(hasher.finish())
+        });
+        state.write_usize(self.len());
+        state.write_u64(sum);
+    }
+}
+
+// Sets compare like their sorted items, as BTreeSets do
+impl<T: Ord> PartialOrd for MyCollection<T, Set> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for MyCollection<T, Set> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        sorted(&self.items).cmp(&sorted(&other.items))
     }
+}
+
+fn sorted<T: Ord>(items: &[T]) -> Vec<&T> {
+    let mut sorted: Vec<&T> = items.iter().collect();
+    sorted.sort();
+    sorted
+}
 
+impl<T, S> Default for MyCollection<T, S> {
+    fn default() -> Self {
+        MyCollection {
+            items: Vec::new(),
+            semantics: PhantomData,
+        }
+    }
+}
+
+// Works the same for lists and sets
+impl<T, S> MyCollection<T, S> {
     pub fn len(&self) -> usize {
         self.items.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
     pub fn iter(&self) -> Iter<'_, T> {
         self.items.iter()
     }
+
+    /// Remove and return the item at `index`, shifting the rest down.
+    /// Panics if `index` is out of bounds, like Vec::remove.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.items.remove(index)
+    }
+
+    /// Remove and return the last item
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Keep only the items for which `keep` returns true
+    pub fn retain(&mut self, keep: impl FnMut(&T) -> bool) {
+        self.items.retain(keep);
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.items.sort();
+    }
+
+    pub fn sort_by(&mut self, compare: impl FnMut(&T, &T) -> std::cmp::Ordering) {
+        self.items.sort_by(compare);
+    }
+
+    pub fn sort_by_key<K: Ord>(&mut self, key: impl FnMut(&T) -> K) {
+        self.items.sort_by_key(key);
+    }
+
+    /// Remove the first item equal to `item`, return whether there was one
+    pub fn remove_item(&mut self, item: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        match self.items.iter().position(|x| x == item) {
+            Some(index) => {
+                self.items.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+// new is only for lists, so MyCollection::new() needs no type
+// annotation. An empty set is MySet::default().
+impl<T> MyCollection<T, List> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        MyCollection {
+            items: Vec::with_capacity(capacity),
+            semantics: PhantomData,
+        }
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    /// Insert an item at `index`, shifting the rest up. Panics if `index`
+    /// is greater than the length.
+    pub fn insert(&mut self, index: usize, item: T) {
+        self.items.insert(index, item);
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.items.iter_mut()
+    }
+
+    /// Remove consecutive equal items, like Vec::dedup. Sort first to
+    /// remove every duplicate.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.items.dedup();
+    }
+
+    pub fn dedup_by_key<K: PartialEq>(&mut self, key: impl FnMut(&mut T) -> K) {
+        self.items.dedup_by_key(key);
+    }
+
+    /// The same items as a set: the first of each group of equal items
+    /// is kept, in order
+    pub fn into_set(self) -> MySet<T>
+    where
+        T: PartialEq,
+    {
+        self.items.into_iter().collect()
+    }
+}
+
+impl<T: PartialEq> MyCollection<T, Set> {
+    /// Add an item unless an equal one is already there. Returns whether
+    /// the item was added.
+    pub fn add(&mut self, item: T) -> bool {
+        if self.items.contains(&item) {
+            return false;
+        }
+        self.items.push(item);
+        true
+    }
+
+    pub fn into_list(self) -> MyCollection<T, List> {
+        MyCollection {
+            items: self.items,
+            semantics: PhantomData,
+        }
+    }
+}
+
+impl<T, S> Deref for MyCollection<T, S> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+// Only lists hand out mutable items, see the module comment
+impl<T> DerefMut for MyCollection<T, List> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.items
+    }
+}
+
+// Indexes with a position or a range, like a Vec
+impl<T, S, I: SliceIndex<[T]>> Index<I> for MyCollection<T, S> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.items[index]
+    }
+}
+
+impl<T, I: SliceIndex<[T]>> IndexMut<I> for MyCollection<T, List> {
+    fn index_mut(&mut self, index: I) -> &mut I::Output {
+        &mut self.items[index]
+    }
 }
 
-impl<T> IntoIterator for MyCollection<T> {
+impl<T> FromIterator<T> for MyCollection<T, List> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        MyCollection {
+            items: iter.into_iter().collect(),
+            semantics: PhantomData,
+        }
+    }
+}
+
+impl<T: PartialEq> FromIterator<T> for MyCollection<T, Set> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = MyCollection::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T> Extend<T> for MyCollection<T, List> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+impl<T: PartialEq> Extend<T> for MyCollection<T, Set> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for MyCollection<T, List> {
+    fn from(items: Vec<T>) -> Self {
+        MyCollection {
+            items,
+            semantics: PhantomData,
+        }
+    }
+}
+
+impl<T, S> From<MyCollection<T, S>> for Vec<T> {
+    fn from(collection: MyCollection<T, S>) -> Self {
+        collection.items
+    }
+}
+
+impl<T, S> IntoIterator for MyCollection<T, S> {
     type Item = T;
     type IntoIter = std::vec::IntoIter<T>;
 
@@ -32,7 +347,7 @@ impl<T> IntoIterator for MyCollection<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a MyCollection<T> {
+impl<'a, T, S> IntoIterator for &'a MyCollection<T, S> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
 
@@ -41,6 +356,34 @@ impl<'a, T> IntoIterator for &'a MyCollection<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a mut MyCollection<T, List> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, S> serde::Serialize for MyCollection<T, S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.items.serialize(serializer)
+    }
+}
+
+// Goes through FromIterator, so a set drops duplicates it is given
+#[cfg(feature = "serde")]
+impl<'de, T, S> serde::Deserialize<'de> for MyCollection<T, S>
+where
+    T: serde::Deserialize<'de>,
+    Self: FromIterator<T>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<T>::deserialize(deserializer).map(|items| items.into_iter().collect())
+    }
+}
+
 pub fn run() {
     let mut col = MyCollection::new();
     col.add(10);
@@ -59,6 +402,19 @@ pub fn run() {
     let doubled: Vec<_> = (&col).into_iter().map(|x| x * 2).collect();
     println!("Doubled items: {:?}", doubled);
 
+    col[0] = 40;
+    col.extend([20, 10]);
+    col.sort();
+    col.dedup();
+    println!("Sorted without duplicates: {:?}", &col[..]);
+
+    col.retain(|x| *x > 10);
+    println!("Items above 10: {:?}, the largest is {:?}", col, col.last());
+
+    let mut set: MySet<&str> = ["pear", "apple", "pear"].into_iter().collect();
+    let added = set.add("apple");
+    println!("Set: {:?}, apple added again: {}", set, added);
+
     let collected: Vec<_> = col.into_iter().collect();
     println!("Collected items into a vector: {:?}", collected);
-}
\ No newline at end of file
+}
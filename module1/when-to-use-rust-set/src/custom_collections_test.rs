@@ -1,6 +1,6 @@
+use crate::custom_collection::{List, MyCollection, MySet};
+use proptest::prelude::*;
 
-#[cfg(test)]
-use crate::custom_collection::MyCollection;
 mod tests {
     use super::*;
 
@@ -38,4 +38,181 @@ mod tests {
         let doubled: Vec<_> = (&col).into_iter().map(|x| x * 2).collect();
         assert_eq!(doubled, vec![2, 4]);
     }
+
+    #[test]
+    fn test_index_and_slice() {
+        let mut col: MyCollection<i32> = vec![3, 1, 2].into();
+        col[1] = 5;
+        for x in &mut col {
+            *x += 1;
+        }
+        assert_eq!(col[1], 6);
+        assert_eq!(col.first(), Some(&4));
+        assert!(col.contains(&3));
+        col.reverse();
+        assert_eq!(&col[..], [3, 6, 4]);
+        assert_eq!(format!("{col:?}"), "[3, 6, 4]");
+    }
+
+    #[test]
+    fn test_remove_sort_dedup_retain() {
+        let mut col: MyCollection<i32> = [4, 2, 4, 1, 2, 3].into_iter().collect();
+        assert_eq!(col.remove(0), 4);
+        assert!(col.remove_item(&2));
+        assert!(!col.remove_item(&9));
+        col.extend([3, 1]);
+        col.sort();
+        assert_eq!(&col[..], [1, 1, 2, 3, 3, 4]);
+        col.dedup();
+        assert_eq!(&col[..], [1, 2, 3, 4]);
+        col.retain(|x| x % 2 == 0);
+        assert_eq!(col.into_vec(), [2, 4]);
+
+        let mut words: MyCollection<&str> = ["pear", "fig", "kiwi"].into_iter().collect();
+        words.sort_by_key(|word| word.len());
+        assert_eq!(&words[..], ["fig", "pear", "kiwi"]);
+        words.dedup_by_key(|word| word.len());
+        assert_eq!(&words[..], ["fig", "pear"]);
+    }
+
+    #[test]
+    fn test_set_semantics() {
+        let mut set: MySet<&str> = ["pear", "apple", "pear"].into_iter().collect();
+        assert_eq!(&set[..], ["pear", "apple"]);
+        assert!(!set.add("apple"));
+        assert!(set.add("kiwi"));
+        set.extend(["kiwi", "fig"]);
+        assert_eq!(set.len(), 4);
+        set.sort();
+        assert_eq!(&set[..], ["apple", "fig", "kiwi", "pear"]);
+
+        let list: MyCollection<&str, List> = set.into_list();
+        assert_eq!(list.into_set().len(), 4);
+    }
+
+    #[test]
+    fn test_set_equality_ignores_order() {
+        use std::collections::HashSet;
+
+        let a: MySet<&str> = ["pear", "apple", "fig"].into_iter().collect();
+        let b: MySet<&str> = ["fig", "pear", "apple", "fig"].into_iter().collect();
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+        assert_ne!(a, ["pear", "apple"].into_iter().collect());
+        assert!(a < ["pear", "kiwi", "apple"].into_iter().collect());
+        let sets: HashSet<MySet<&str>> = [a, b].into_iter().collect();
+        assert_eq!(sets.len(), 1);
+
+        // Lists still care about the order
+        let list: MyCollection<&str> = vec!["pear", "apple"].into();
+        assert_ne!(list, vec!["apple", "pear"].into());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let col: MyCollection<i32> = vec![1, 2, 1].into();
+        let json = serde_json::to_string(&col).unwrap();
+        assert_eq!(json, "[1,2,1]");
+        let back: MyCollection<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, col);
+        // A set drops the duplicates it is given
+        let set: MySet<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(&set[..], [1, 2]);
+    }
+}
+
+// MyCollection against a Vec doing the same thing
+mod properties {
+    use super::*;
+
+    fn unique(items: &[u8]) -> Vec<u8> {
+        let mut seen = Vec::new();
+        for item in items {
+            if !seen.contains(item) {
+                seen.push(*item);
+            }
+        }
+        seen
+    }
+
+    proptest! {
+        #[test]
+        fn collect_keeps_order(items in prop::collection::vec(any::<u8>(), 0..50)) {
+            let col: MyCollection<u8> = items.iter().copied().collect();
+            prop_assert_eq!(col.len(), items.len());
+            prop_assert_eq!(&col[..], &items[..]);
+            prop_assert_eq!(col.into_iter().collect::<Vec<_>>(), items);
+        }
+
+        #[test]
+        fn extend_appends(a in prop::collection::vec(any::<u8>(), 0..30),
+                          b in prop::collection::vec(any::<u8>(), 0..30)) {
+            let mut col: MyCollection<u8> = a.clone().into();
+            col.extend(b.iter().copied());
+            let mut vec = a;
+            vec.extend(b);
+            prop_assert_eq!(col.into_vec(), vec);
+        }
+
+        #[test]
+        fn sort_and_dedup_match_vec(items in prop::collection::vec(0u8..10, 0..50)) {
+            let mut col: MyCollection<u8> = items.clone().into();
+            let mut vec = items;
+            col.sort();
+            vec.sort();
+            prop_assert_eq!(&col[..], &vec[..]);
+            col.dedup();
+            vec.dedup();
+            prop_assert_eq!(col.into_vec(), vec);
+        }
+
+        #[test]
+        fn retain_and_remove_match_vec(items in prop::collection::vec(any::<u8>(), 1..50),
+                                       index in any::<prop::sample::Index>()) {
+            let mut col: MyCollection<u8> = items.clone().into();
+            let mut vec = items;
+            let index = index.index(vec.len());
+            prop_assert_eq!(col.remove(index), vec.remove(index));
+            col.retain(|x| x % 3 != 0);
+            vec.retain(|x| x % 3 != 0);
+            prop_assert_eq!(col.into_vec(), vec);
+        }
+
+        #[test]
+        fn set_keeps_first_of_each(items in prop::collection::vec(0u8..20, 0..50)) {
+            let set: MySet<u8> = items.iter().copied().collect();
+            prop_assert_eq!(&set[..], &unique(&items)[..]);
+
+            let mut added = MySet::default();
+            for item in &items {
+                let new = !added.contains(item);
+                prop_assert_eq!(added.add(*item), new);
+            }
+            prop_assert_eq!(added, set);
+        }
+
+        #[test]
+        fn set_stays_unique(items in prop::collection::vec(0u8..20, 0..50),
+                            more in prop::collection::vec(0u8..20, 0..50)) {
+            let mut set: MySet<u8> = items.into_iter().collect();
+            set.extend(more);
+            set.retain(|x| x % 2 == 0);
+            set.sort();
+            let mut sorted = set.clone().into_vec();
+            sorted.dedup();
+            prop_assert_eq!(set.into_vec(), sorted);
+        }
+
+        #[test]
+        fn set_equality_ignores_order(items in prop::collection::vec(0u8..20, 0..30)) {
+            use std::hash::{BuildHasher, RandomState};
+
+            let set: MySet<u8> = items.iter().copied().collect();
+            let reversed: MySet<u8> = items.iter().rev().copied().collect();
+            let state = RandomState::new();
+            prop_assert_eq!(&set, &reversed);
+            prop_assert_eq!(state.hash_one(&set), state.hash_one(&reversed));
+        }
+    }
 }
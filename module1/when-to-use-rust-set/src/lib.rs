@@ -0,0 +1,6 @@
+// Library part of the examples: the custom collection is a template for
+// domain collections, so it is built and tested whatever feature is on.
+pub mod custom_collection;
+
+#[cfg(test)]
+mod custom_collections_test;
@@ -30,11 +30,7 @@ cfg_if! {
         }
 
     } else if #[cfg(feature = "custom_collection")] {
-        mod custom_collection;
-        use custom_collection::run;
-        
-        #[cfg(test)]
-        mod custom_collections_test;
+        use when_to_use_rust_set::custom_collection::run;
 
         fn main() {
             run();
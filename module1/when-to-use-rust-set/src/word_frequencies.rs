@@ -1,29 +1,14 @@
-use std::collections::HashMap;
-use std::fs;
+use word_freq::{Options, count_file};
 
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    // Read the entire file into a string
-    let contents = fs::read_to_string("word_frequencies.txt")?;
-
-    // Create a HashMap to store word counts
-    let mut word_counts: HashMap<String, usize> = HashMap::new();
-
-    // Iterate over words, normalize and count
-    for word in contents.split_whitespace() {
-        let word = word
-            .to_lowercase()
-            .trim_matches(|c: char| !c.is_alphanumeric())
-            .to_string();
-
-        if !word.is_empty() {
-            *word_counts.entry(word).or_insert(0) += 1;
-        }
-    }
+    // Stream the file and count its words. Words are split by the Unicode
+    // word rules, so punctuation is left out, and lower cased.
+    let word_counts = count_file("word_frequencies.txt", &Options::new())?;
 
     // Print the word frequencies
-    for (word, count) in &word_counts {
+    for (word, count) in word_counts.iter() {
         println!("{word}: {count}");
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,172 @@
+/*
+Counting terms in text, readers and files.
+
+A WordCounter is fed text piece by piece and keeps only the counts and
+the last n - 1 terms for n-grams. A reader is read in fixed-size chunks,
+not lines, so a file without line breaks takes no more memory than any
+other. Each chunk is cut after its last whitespace, and the unfinished
+term after it is carried over to the next chunk. N-grams run on across
+chunks and lines. Several files are counted in parallel, one counter per
+file, and their counts merged.
+*/
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::counts::Counts;
+use crate::tokenize::{Options, Tokenizer};
+
+// Bytes read at a time
+const CHUNK: usize = 64 * 1024;
+
+/// A file that could not be read
+#[derive(Debug)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Counts the terms, or n-grams of terms, of everything fed to it
+pub struct WordCounter<'a> {
+    tokenizer: Tokenizer<'a>,
+    // The last terms seen, at most ngram of them
+    window: VecDeque<String>,
+    counts: Counts,
+}
+
+impl<'a> WordCounter<'a> {
+    pub fn new(options: &'a Options) -> Self {
+        WordCounter {
+            tokenizer: Tokenizer::new(options),
+            window: VecDeque::new(),
+            counts: Counts::new(),
+        }
+    }
+
+    pub fn feed(&mut self, text: &str) {
+        let n = self.tokenizer.options().ngram.max(1);
+        for term in self.tokenizer.tokens(text) {
+            if n == 1 {
+                self.counts.add(term);
+                continue;
+            }
+            self.window.push_back(term);
+            if self.window.len() > n {
+                self.window.pop_front();
+            }
+            if self.window.len() == n {
+                let gram = self.window.iter().map(String::as_str).collect::<Vec<_>>();
+                self.counts.add(gram.join(" "));
+            }
+        }
+    }
+
+    /// Feed everything a reader gives, in chunks of at most 64 KiB plus
+    /// an unfinished term. Invalid UTF-8 is replaced, not an error.
+    pub fn read<R: Read>(&mut self, mut reader: R) -> io::Result<()> {
+        let mut chunk = vec![0; CHUNK];
+        // Text not fed yet: the end of the last chunk
+        let mut rest = Vec::new();
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            rest.extend_from_slice(&chunk[..read]);
+            let end = cut(&rest);
+            self.feed(&String::from_utf8_lossy(&rest[..end]));
+            rest.drain(..end);
+        }
+        self.feed(&String::from_utf8_lossy(&rest));
+        Ok(())
+    }
+
+    /// The counts so far
+    pub fn counts(&self) -> &Counts {
+        &self.counts
+    }
+
+    pub fn finish(self) -> Counts {
+        self.counts
+    }
+}
+
+pub fn count_text(text: &str, options: &Options) -> Counts {
+    let mut counter = WordCounter::new(options);
+    counter.feed(text);
+    counter.finish()
+}
+
+pub fn count_reader<R: Read>(reader: R, options: &Options) -> io::Result<Counts> {
+    let mut counter = WordCounter::new(options);
+    counter.read(reader)?;
+    Ok(counter.finish())
+}
+
+pub fn count_file<P: AsRef<Path>>(path: P, options: &Options) -> Result<Counts, FileError> {
+    let path = path.as_ref();
+    let error = |source| FileError {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = File::open(path).map_err(error)?;
+    count_reader(BufReader::new(file), options).map_err(error)
+}
+
+// Where text can be cut without splitting a term: after the last ASCII
+// whitespace, which is never part of a multi-byte character. When more than
+// a chunk follows it, the text is cut at its last word boundary instead,
+// or anywhere for a single term longer than a chunk.
+fn cut(text: &[u8]) -> usize {
+    let after_space = text
+        .iter()
+        .rposition(u8::is_ascii_whitespace)
+        .map_or(0, |space| space + 1);
+    if text.len() - after_space <= CHUNK {
+        return after_space;
+    }
+    let valid = match std::str::from_utf8(text) {
+        Ok(valid) => valid,
+        Err(err) => std::str::from_utf8(&text[..err.valid_up_to()]).unwrap_or_default(),
+    };
+    match valid.split_word_bound_indices().next_back() {
+        Some((start, _)) if start > 0 => start,
+        _ if !valid.is_empty() => valid.len(),
+        _ => text.len(),
+    }
+}
+
+/// The merged counts of several files, read in parallel. N-grams do not
+/// run on from one file to the next.
+pub fn count_files<P>(paths: &[P], options: &Options) -> Result<Counts, FileError>
+where
+    P: AsRef<Path> + Sync,
+{
+    paths
+        .par_iter()
+        .map(|path| count_file(path, options))
+        .try_reduce(Counts::new, |mut counts, other| {
+            counts.merge(other);
+            Ok(counts)
+        })
+}
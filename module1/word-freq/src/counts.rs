@@ -0,0 +1,124 @@
+/*
+Term counts.
+
+Counts from different texts or files merge by adding them up. The most
+frequent terms come from a bounded heap that never holds more than k
+entries, so asking for the top 10 of a million distinct words does not
+sort all of them.
+*/
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::hash_map::{self, HashMap};
+
+/// How often each term occurs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counts {
+    counts: HashMap<String, usize>,
+    total: usize,
+}
+
+impl Counts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, term: String) {
+        self.add_n(term, 1);
+    }
+
+    pub fn add_n(&mut self, term: String, n: usize) {
+        *self.counts.entry(term).or_insert(0) += n;
+        self.total += n;
+    }
+
+    /// How often a term occurs, 0 for an unknown term
+    pub fn get(&self, term: &str) -> usize {
+        self.counts.get(term).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct terms
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Number of terms counted, repeats included
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Terms and counts in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.counts
+            .iter()
+            .map(|(term, count)| (term.as_str(), *count))
+    }
+
+    /// Add the counts of another text
+    pub fn merge(&mut self, other: Counts) {
+        for (term, count) in other.counts {
+            self.add_n(term, count);
+        }
+    }
+
+    /// The k most frequent terms, most frequent first. Equal counts are in
+    /// alphabetical order. O(n log k).
+    pub fn top_k(&self, k: usize) -> Vec<(&str, usize)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        // The top of the heap is the least frequent term kept so far
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        for (term, count) in self.iter() {
+            let key = (Reverse(count), term);
+            if heap.len() < k {
+                heap.push(key);
+            } else if heap.peek().is_some_and(|worst| key < *worst) {
+                heap.pop();
+                heap.push(key);
+            }
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|(Reverse(count), term)| (term, count))
+            .collect()
+    }
+
+    /// Every term, most frequent first
+    pub fn sorted(&self) -> Vec<(&str, usize)> {
+        self.top_k(self.len())
+    }
+
+    pub fn into_map(self) -> HashMap<String, usize> {
+        self.counts
+    }
+}
+
+impl FromIterator<String> for Counts {
+    fn from_iter<I: IntoIterator<Item = String>>(terms: I) -> Self {
+        let mut counts = Counts::new();
+        counts.extend(terms);
+        counts
+    }
+}
+
+impl Extend<String> for Counts {
+    fn extend<I: IntoIterator<Item = String>>(&mut self, terms: I) {
+        for term in terms {
+            self.add(term);
+        }
+    }
+}
+
+impl IntoIterator for Counts {
+    type Item = (String, usize);
+    type IntoIter = hash_map::IntoIter<String, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
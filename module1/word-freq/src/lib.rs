@@ -0,0 +1,153 @@
+/*
+A word frequency engine shared by the word counting examples.
+
+Text is split into words by the Unicode word rules, optionally case
+folded, filtered by a stop word list and stemmed. Single words or n-grams
+(bigrams, trigrams, ...) are counted from strings, readers or files, which
+are streamed in fixed-size chunks. Several files are counted in parallel and the
+most frequent terms are picked with a bounded heap.
+*/
+
+pub mod counter;
+pub mod counts;
+pub mod tokenize;
+
+pub use counter::{FileError, WordCounter, count_file, count_files, count_reader, count_text};
+pub use counts::Counts;
+pub use tokenize::{Algorithm, ENGLISH_STOP_WORDS, Options, Tokenizer};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn words(text: &str, options: &Options) -> Vec<String> {
+        Tokenizer::new(options).tokens(text).collect()
+    }
+
+    #[test]
+    fn test_unicode_words() {
+        let options = Options::new();
+        assert_eq!(
+            words("Hello, world! It's a café—naïve “quotes” 3.14", &options),
+            [
+                "hello", "world", "it's", "a", "café", "naïve", "quotes", "3.14"
+            ]
+        );
+        let options = Options::new().case_fold(false);
+        assert_eq!(words("Straße STRASSE", &options), ["Straße", "STRASSE"]);
+    }
+
+    #[test]
+    fn test_stop_words_and_stemming() {
+        let options = Options::new().english_stop_words().stem(Algorithm::English);
+        assert_eq!(
+            words("The runner was running and the runners ran", &options),
+            ["runner", "run", "runner", "ran"]
+        );
+        // Stop words match whatever the case
+        let options = Options::new().case_fold(false).stop_words(["THE"]);
+        assert_eq!(words("The cat", &options), ["cat"]);
+    }
+
+    #[test]
+    fn test_count_text() {
+        let counts = count_text("Hello world, hello Rust. HELLO!", &Options::new());
+        assert_eq!(counts.get("hello"), 3);
+        assert_eq!(counts.get("world"), 1);
+        assert_eq!(counts.get("missing"), 0);
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts.total(), 5);
+    }
+
+    #[test]
+    fn test_ngrams_across_lines() {
+        let text = "the quick brown\nfox the quick\nbrown";
+        let options = Options::new().ngram(2);
+        let mut counter = WordCounter::new(&options);
+        counter.read(Cursor::new(text)).unwrap();
+        let bigrams = counter.finish();
+        assert_eq!(bigrams.get("the quick"), 2);
+        assert_eq!(bigrams.get("quick brown"), 2);
+        assert_eq!(bigrams.get("brown fox"), 1);
+        assert_eq!(bigrams.total(), 6);
+
+        let trigrams = count_text(text, &Options::new().ngram(3));
+        assert_eq!(trigrams.get("the quick brown"), 2);
+        assert_eq!(trigrams.total(), 5);
+        assert!(count_text("one two", &Options::new().ngram(3)).is_empty());
+    }
+
+    // Gives a few bytes at a time, so terms and characters are split
+    // across reads
+    struct Trickle<'a>(&'a [u8]);
+
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_read_in_chunks() {
+        let text = "Naïve café, naïve CAFÉ!\nthe end";
+        let options = Options::new().ngram(2);
+        let counts = count_reader(Trickle(text.as_bytes()), &options).unwrap();
+        assert_eq!(counts, count_text(text, &options));
+        assert_eq!(counts.get("naïve café"), 2);
+
+        // One line, far longer than a chunk, with and without spaces
+        for separator in [" ", ","] {
+            let line = format!("alpha{separator}beta{separator}gamma{separator}").repeat(20_000);
+            let counts = count_reader(Cursor::new(&line), &Options::new()).unwrap();
+            assert_eq!(counts.get("alpha"), 20_000);
+            assert_eq!(counts.get("gamma"), 20_000);
+            assert_eq!(counts.total(), 60_000);
+        }
+    }
+
+    #[test]
+    fn test_ngram_zero_counts_words() {
+        let options = Options {
+            ngram: 0,
+            ..Options::new()
+        };
+        let counts = count_text("one two two", &options);
+        assert_eq!(counts.get("two"), 2);
+        assert_eq!(counts.get(""), 0);
+        assert_eq!(counts.total(), 3);
+    }
+
+    #[test]
+    fn test_top_k() {
+        let counts = count_text("b a c b a b d", &Options::new());
+        assert_eq!(counts.top_k(2), [("b", 3), ("a", 2)]);
+        // Ties in alphabetical order
+        assert_eq!(counts.top_k(3), [("b", 3), ("a", 2), ("c", 1)]);
+        assert_eq!(counts.top_k(10).len(), 4);
+        assert!(counts.top_k(0).is_empty());
+        assert_eq!(counts.sorted(), counts.top_k(4));
+    }
+
+    #[test]
+    fn test_count_files_in_parallel() {
+        let dir = std::env::temp_dir().join(format!("word-freq-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<_> = (0..4).map(|i| dir.join(format!("{i}.txt"))).collect();
+        for (i, path) in paths.iter().enumerate() {
+            std::fs::write(path, format!("shared word{i}\n").repeat(i + 1)).unwrap();
+        }
+        let counts = count_files(&paths, &Options::new()).unwrap();
+        assert_eq!(counts.get("shared"), 1 + 2 + 3 + 4);
+        assert_eq!(counts.get("word3"), 4);
+        assert_eq!(counts.total(), 20);
+
+        let missing = dir.join("missing.txt");
+        let error = count_files(&[&paths[0], &missing], &Options::new()).unwrap_err();
+        assert_eq!(error.path, missing);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
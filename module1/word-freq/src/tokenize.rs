@@ -0,0 +1,125 @@
+/*
+Turning text into the terms that are counted.
+
+Words are found with the Unicode word boundary rules (UAX #29), so
+punctuation, apostrophes inside words, numbers and scripts without spaces
+between words are handled the same way everywhere. Each word is then case
+folded, checked against the stop words and stemmed, as set in Options.
+*/
+
+use std::collections::HashSet;
+
+pub use rust_stemmers::Algorithm;
+use rust_stemmers::Stemmer;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Common English words that carry little meaning on their own
+pub const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "because",
+    "been", "but", "by", "can", "could", "did", "do", "does", "for", "from", "had", "has", "have",
+    "he", "her", "him", "his", "how", "i", "if", "in", "into", "is", "it", "its", "me", "my", "no",
+    "not", "of", "on", "or", "our", "out", "she", "so", "than", "that", "the", "their", "them",
+    "then", "there", "these", "they", "this", "to", "up", "us", "was", "we", "were", "what",
+    "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+/// How text is split into terms. The default folds case, keeps every word
+/// and counts single words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub case_fold: bool,
+    pub stemmer: Option<Algorithm>,
+    /// Lower case words to leave out, matched before stemming
+    pub stop_words: HashSet<String>,
+    /// 1 for words, 2 for bigrams, 3 for trigrams, ... 0 counts as 1.
+    pub ngram: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            case_fold: true,
+            stemmer: None,
+            stop_words: HashSet::new(),
+            ngram: 1,
+        }
+    }
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn case_fold(mut self, case_fold: bool) -> Self {
+        self.case_fold = case_fold;
+        self
+    }
+
+    pub fn stem(mut self, algorithm: Algorithm) -> Self {
+        self.stemmer = Some(algorithm);
+        self
+    }
+
+    pub fn stop_words<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.stop_words
+            .extend(words.into_iter().map(|word| word.as_ref().to_lowercase()));
+        self
+    }
+
+    pub fn english_stop_words(self) -> Self {
+        self.stop_words(ENGLISH_STOP_WORDS)
+    }
+
+    /// Count runs of n consecutive terms instead of single terms. 0 counts
+    /// as 1.
+    pub fn ngram(mut self, n: usize) -> Self {
+        self.ngram = n.max(1);
+        self
+    }
+}
+
+/// Splits text into terms with a set of Options
+pub struct Tokenizer<'a> {
+    options: &'a Options,
+    stemmer: Option<Stemmer>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(options: &'a Options) -> Self {
+        Tokenizer {
+            options,
+            stemmer: options.stemmer.map(Stemmer::create),
+        }
+    }
+
+    pub fn options(&self) -> &Options {
+        self.options
+    }
+
+    /// The terms of a text, in order
+    pub fn tokens<'t>(&'t self, text: &'t str) -> impl Iterator<Item = String> + 't {
+        text.unicode_words().filter_map(|word| self.term(word))
+    }
+
+    /// The term for one word, None for a stop word
+    pub fn term(&self, word: &str) -> Option<String> {
+        let lower = word.to_lowercase();
+        if self.options.stop_words.contains(&lower) {
+            return None;
+        }
+        let word = if self.options.case_fold {
+            lower
+        } else {
+            word.to_string()
+        };
+        match &self.stemmer {
+            Some(stemmer) => Some(stemmer.stem(&word).into_owned()),
+            None => Some(word),
+        }
+    }
+}
@@ -0,0 +1,588 @@
+/*
+Saving and loading finished salads.
+
+A ServedSalad is what ends up in the bowl: each fruit once with its
+quantity, calories and price per serving, plus the dressing. Every format
+writes the nutritional totals and can be read back with Format::read.
+
+CSV       RFC 4180, one row per fruit, the dressing and the totals, told
+          apart by the first column:
+
+              kind,name,quantity,calories,price
+              fruit,Apple,2,52,0.5
+              dressing,Honey Drizzle,,,
+              total,,2,104,1.00
+
+JSON/YAML {fruits: [{name, quantity, calories, price}], dressing, totals}
+Markdown  a table with the per-serving and line values, a total row and
+          the dressing below it. The first two rows are the header and
+          the last one the total, whatever their first cell says.
+
+Per-serving prices are written in full, so a salad reads back exactly.
+The totals and the line prices of the Markdown table are rounded to
+cents; the totals are recomputed from the fruits when reading.
+*/
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use fruit_catalog::Salad;
+use serde::{Deserialize, Serialize};
+
+/// Error writing or reading a salad file
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    Yaml(serde_yaml::Error),
+    /// A file that does not look like a saved salad
+    Parse(String),
+    /// An unknown format name or file extension
+    UnknownFormat(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "{}", e),
+            ExportError::Csv(e) => write!(f, "invalid CSV salad: {}", e),
+            ExportError::Json(e) => write!(f, "invalid JSON salad: {}", e),
+            ExportError::Yaml(e) => write!(f, "invalid YAML salad: {}", e),
+            ExportError::Parse(reason) => write!(f, "invalid salad file: {}", reason),
+            ExportError::UnknownFormat(format) => write!(
+                f,
+                "unknown salad format '{}'. Choose from: csv, json, yaml, markdown",
+                format
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<csv::Error> for ExportError {
+    fn from(e: csv::Error) -> Self {
+        ExportError::Csv(e)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(e: serde_json::Error) -> Self {
+        ExportError::Json(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ExportError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ExportError::Yaml(e)
+    }
+}
+
+/// One fruit of a served salad. Calories and price are per serving.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaladItem {
+    pub name: String,
+    pub quantity: u32,
+    #[serde(default)]
+    pub calories: u32,
+    #[serde(default)]
+    pub price: f64,
+}
+
+/// Summed over all servings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Totals {
+    pub servings: u32,
+    pub calories: u32,
+    pub price: f64,
+}
+
+/// A salad as it is served and saved.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServedSalad {
+    pub items: Vec<SaladItem>,
+    pub dressing: Option<String>,
+}
+
+impl ServedSalad {
+    /// Fruits that appear more than once are counted into one item, in the
+    /// order they first appear
+    pub fn from_salad(salad: &Salad, dressing: Option<String>) -> Self {
+        let mut served = ServedSalad {
+            items: Vec::new(),
+            dressing,
+        };
+        for fruit in salad {
+            served.add(&fruit.name, fruit.calories, fruit.price);
+        }
+        served
+    }
+
+    /// A salad of plain names without nutrition data
+    pub fn from_names(names: &[String], dressing: Option<String>) -> Self {
+        let mut served = ServedSalad {
+            items: Vec::new(),
+            dressing,
+        };
+        for name in names {
+            served.add(name, 0, 0.0);
+        }
+        served
+    }
+
+    fn add(&mut self, name: &str, calories: u32, price: f64) {
+        match self.items.iter_mut().find(|item| item.name == name) {
+            Some(item) => item.quantity += 1,
+            None => self.items.push(SaladItem {
+                name: name.to_string(),
+                quantity: 1,
+                calories,
+                price,
+            }),
+        }
+    }
+
+    pub fn totals(&self) -> Totals {
+        Totals {
+            servings: self.items.iter().map(|item| item.quantity).sum(),
+            calories: self
+                .items
+                .iter()
+                .map(|item| item.quantity * item.calories)
+                .sum(),
+            price: self
+                .items
+                .iter()
+                .map(|item| item.quantity as f64 * item.price)
+                .sum(),
+        }
+    }
+
+    /// Every serving by name, duplicates repeated
+    pub fn names(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .flat_map(|item| std::iter::repeat_n(item.name.clone(), item.quantity as usize))
+            .collect()
+    }
+}
+
+/// A backend that writes a served salad.
+pub trait SaladWriter {
+    fn write(&self, salad: &ServedSalad, out: &mut dyn io::Write) -> Result<(), ExportError>;
+
+    /// The salad as a string
+    fn to_string(&self, salad: &ServedSalad) -> Result<String, ExportError> {
+        let mut buffer = Vec::new();
+        self.write(salad, &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| ExportError::Parse(e.to_string()))
+    }
+}
+
+pub struct CsvWriter;
+pub struct JsonWriter;
+pub struct YamlWriter;
+pub struct MarkdownWriter;
+
+impl SaladWriter for CsvWriter {
+    fn write(&self, salad: &ServedSalad, out: &mut dyn io::Write) -> Result<(), ExportError> {
+        let mut writer = csv::Writer::from_writer(out);
+        writer.write_record(["kind", "name", "quantity", "calories", "price"])?;
+        for item in &salad.items {
+            writer.write_record([
+                "fruit",
+                &item.name,
+                &item.quantity.to_string(),
+                &item.calories.to_string(),
+                &item.price.to_string(),
+            ])?;
+        }
+        if let Some(dressing) = &salad.dressing {
+            writer.write_record(["dressing", dressing, "", "", ""])?;
+        }
+        let totals = salad.totals();
+        writer.write_record([
+            "total",
+            "",
+            &totals.servings.to_string(),
+            &totals.calories.to_string(),
+            &format!("{:.2}", totals.price),
+        ])?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// The JSON and YAML layout
+#[derive(Serialize, Deserialize)]
+struct SaladDocument {
+    fruits: Vec<SaladItem>,
+    #[serde(default)]
+    dressing: Option<String>,
+    #[serde(default, skip_deserializing)]
+    totals: Option<Totals>,
+}
+
+impl SaladDocument {
+    fn new(salad: &ServedSalad) -> Self {
+        SaladDocument {
+            fruits: salad.items.clone(),
+            dressing: salad.dressing.clone(),
+            totals: Some(salad.totals()),
+        }
+    }
+
+    fn into_salad(self) -> ServedSalad {
+        ServedSalad {
+            items: self.fruits,
+            dressing: self.dressing,
+        }
+    }
+}
+
+impl SaladWriter for JsonWriter {
+    fn write(&self, salad: &ServedSalad, out: &mut dyn io::Write) -> Result<(), ExportError> {
+        serde_json::to_writer_pretty(&mut *out, &SaladDocument::new(salad))?;
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+impl SaladWriter for YamlWriter {
+    fn write(&self, salad: &ServedSalad, out: &mut dyn io::Write) -> Result<(), ExportError> {
+        serde_yaml::to_writer(out, &SaladDocument::new(salad))?;
+        Ok(())
+    }
+}
+
+// '|' ends a Markdown table cell
+fn escape_cell(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+impl SaladWriter for MarkdownWriter {
+    fn write(&self, salad: &ServedSalad, out: &mut dyn io::Write) -> Result<(), ExportError> {
+        writeln!(
+            out,
+            "| Fruit | Quantity | kcal/serving | EUR/serving | kcal | EUR |"
+        )?;
+        writeln!(out, "|---|---:|---:|---:|---:|---:|")?;
+        for item in &salad.items {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {:.2} |",
+                escape_cell(&item.name),
+                item.quantity,
+                item.calories,
+                item.price,
+                item.quantity * item.calories,
+                item.quantity as f64 * item.price
+            )?;
+        }
+        let totals = salad.totals();
+        writeln!(
+            out,
+            "| **Total** | {} | | | {} | {:.2} |",
+            totals.servings, totals.calories, totals.price
+        )?;
+        if let Some(dressing) = &salad.dressing {
+            writeln!(out, "\nDressing: {}", dressing)?;
+        }
+        Ok(())
+    }
+}
+
+/// The supported salad file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+    Yaml,
+    Markdown,
+}
+
+impl Format {
+    /// The format of a file extension: csv, json, yaml/yml or md/markdown
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ExportError> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        ext.parse()
+            .map_err(|_| ExportError::UnknownFormat(path.display().to_string()))
+    }
+
+    pub fn writer(&self) -> Box<dyn SaladWriter> {
+        match self {
+            Format::Csv => Box::new(CsvWriter),
+            Format::Json => Box::new(JsonWriter),
+            Format::Yaml => Box::new(YamlWriter),
+            Format::Markdown => Box::new(MarkdownWriter),
+        }
+    }
+
+    /// Read a salad written in this format
+    pub fn read(&self, text: &str) -> Result<ServedSalad, ExportError> {
+        match self {
+            Format::Csv => read_csv(text),
+            Format::Json => Ok(serde_json::from_str::<SaladDocument>(text)?.into_salad()),
+            Format::Yaml => Ok(serde_yaml::from_str::<SaladDocument>(text)?.into_salad()),
+            Format::Markdown => read_markdown(text),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "md" | "markdown" => Ok(Format::Markdown),
+            other => Err(ExportError::UnknownFormat(other.to_string())),
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(field: &str, what: &str) -> Result<T, ExportError> {
+    field
+        .trim()
+        .parse()
+        .map_err(|_| ExportError::Parse(format!("invalid {} '{}'", what, field)))
+}
+
+fn read_csv(text: &str) -> Result<ServedSalad, ExportError> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let mut salad = ServedSalad::default();
+    for record in reader.records() {
+        let record = record?;
+        let field = |i: usize| record.get(i).unwrap_or_default();
+        match field(0) {
+            "fruit" => salad.items.push(SaladItem {
+                name: field(1).to_string(),
+                quantity: parse_number(field(2), "quantity")?,
+                calories: parse_number(field(3), "calories")?,
+                price: parse_number(field(4), "price")?,
+            }),
+            "dressing" => salad.dressing = Some(field(1).to_string()),
+            "total" => {}
+            other => return Err(ExportError::Parse(format!("unknown row kind '{}'", other))),
+        }
+    }
+    Ok(salad)
+}
+
+// Cells of a Markdown table row, unescaping '\|' and '\\'
+fn split_row(line: &str) -> Vec<String> {
+    let inner = line.trim().trim_start_matches('|');
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => cell.extend(chars.next()),
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells
+}
+
+fn read_markdown(text: &str) -> Result<ServedSalad, ExportError> {
+    let mut salad = ServedSalad::default();
+    let mut rows = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(dressing) = line.strip_prefix("Dressing:") {
+            salad.dressing = Some(dressing.trim().to_string());
+        } else if line.starts_with('|') {
+            rows.push(line);
+        }
+    }
+    // Between the header and separator rows and the total row
+    let fruit_rows = rows
+        .get(2..rows.len().saturating_sub(1))
+        .unwrap_or_default();
+    for row in fruit_rows {
+        let cells = split_row(row);
+        if cells.len() < 4 {
+            return Err(ExportError::Parse(format!("short table row '{}'", row)));
+        }
+        salad.items.push(SaladItem {
+            name: cells[0].clone(),
+            quantity: parse_number(&cells[1], "quantity")?,
+            calories: parse_number(&cells[2], "calories")?,
+            price: parse_number(&cells[3], "price")?,
+        });
+    }
+    Ok(salad)
+}
+
+/// Save a salad, the format from `format` or else the file extension
+pub fn save_salad(
+    salad: &ServedSalad,
+    path: impl AsRef<Path>,
+    format: Option<Format>,
+) -> Result<Format, ExportError> {
+    let path = path.as_ref();
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(path)?,
+    };
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    format.writer().write(salad, &mut file)?;
+    io::Write::flush(&mut file)?;
+    Ok(format)
+}
+
+/// Load a salad, the format from `format` or else the file extension
+pub fn load_salad(
+    path: impl AsRef<Path>,
+    format: Option<Format>,
+) -> Result<ServedSalad, ExportError> {
+    let path = path.as_ref();
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(path)?,
+    };
+    format.read(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fruit_catalog::Catalog;
+
+    fn salad() -> ServedSalad {
+        let catalog = Catalog::builtin();
+        let names: Vec<String> = ["Apple", "Fig", "Apple"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let salad = Salad::new(catalog.only(&names).fruits().to_vec());
+        ServedSalad::from_salad(&salad, Some("Honey Drizzle".to_string()))
+    }
+
+    #[test]
+    fn test_quantities_and_totals() {
+        let salad = salad();
+        assert_eq!(salad.items.len(), 2);
+        assert_eq!(salad.items[0].quantity, 2);
+        let totals = salad.totals();
+        assert_eq!(totals.servings, 3);
+        assert_eq!(totals.calories, 2 * 52 + 74);
+        assert!((totals.price - 2.80).abs() < 1e-9);
+        assert_eq!(salad.names(), ["Apple", "Apple", "Fig"]);
+    }
+
+    #[test]
+    fn test_csv_output() {
+        let csv = CsvWriter.to_string(&salad()).unwrap();
+        assert_eq!(
+            csv,
+            "kind,name,quantity,calories,price\n\
+             fruit,Apple,2,52,0.5\n\
+             fruit,Fig,1,74,1.8\n\
+             dressing,Honey Drizzle,,,\n\
+             total,,3,178,2.80\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_quotes_commas_and_quotes() {
+        let names = vec![
+            "Melon, Cantaloupe".to_string(),
+            "\"Sweet\" Kiwi".to_string(),
+        ];
+        let salad = ServedSalad::from_names(&names, None);
+        let csv = CsvWriter.to_string(&salad).unwrap();
+        assert!(csv.contains("fruit,\"Melon, Cantaloupe\",1,0,0\n"));
+        assert!(csv.contains("fruit,\"\"\"Sweet\"\" Kiwi\",1,0,0\n"));
+        assert_eq!(Format::Csv.read(&csv).unwrap(), salad);
+    }
+
+    #[test]
+    fn test_markdown_output() {
+        let markdown = MarkdownWriter.to_string(&salad()).unwrap();
+        assert!(markdown.contains("| Apple | 2 | 52 | 0.5 | 104 | 1.00 |\n"));
+        assert!(markdown.contains("| **Total** | 3 | | | 178 | 2.80 |\n"));
+        assert!(markdown.ends_with("\nDressing: Honey Drizzle\n"));
+
+        let piped = ServedSalad::from_names(&["Kiwi | Gold".to_string()], None);
+        let markdown = MarkdownWriter.to_string(&piped).unwrap();
+        assert!(markdown.contains("| Kiwi \\| Gold |"));
+        assert_eq!(Format::Markdown.read(&markdown).unwrap(), piped);
+    }
+
+    #[test]
+    fn test_json_and_yaml_include_totals() {
+        let json = JsonWriter.to_string(&salad()).unwrap();
+        assert!(json.contains("\"dressing\": \"Honey Drizzle\""));
+        assert!(json.contains("\"servings\": 3"));
+        let yaml = YamlWriter.to_string(&salad()).unwrap();
+        assert!(yaml.contains("dressing: Honey Drizzle"));
+        assert!(yaml.contains("calories: 178"));
+    }
+
+    #[test]
+    fn test_round_trip_every_format() {
+        let salad = salad();
+        for format in [Format::Csv, Format::Json, Format::Yaml, Format::Markdown] {
+            let text = format.writer().to_string(&salad).unwrap();
+            assert_eq!(format.read(&text).unwrap(), salad, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_exact_prices_and_odd_names() {
+        let salad = ServedSalad {
+            items: ["Fruit", "**Total**", "Kiwi"]
+                .iter()
+                .zip([0.123456789, 1.0 / 3.0, 0.005])
+                .map(|(name, price)| SaladItem {
+                    name: name.to_string(),
+                    quantity: 2,
+                    calories: 40,
+                    price,
+                })
+                .collect(),
+            dressing: None,
+        };
+        for format in [Format::Csv, Format::Markdown] {
+            let text = format.writer().to_string(&salad).unwrap();
+            assert_eq!(format.read(&text).unwrap(), salad, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("salad.csv").unwrap(), Format::Csv);
+        assert_eq!(Format::from_path("salad.YML").unwrap(), Format::Yaml);
+        assert_eq!(Format::from_path("out/salad.md").unwrap(), Format::Markdown);
+        assert!(matches!(
+            Format::from_path("salad.txt"),
+            Err(ExportError::UnknownFormat(_))
+        ));
+        assert_eq!("markdown".parse::<Format>().unwrap(), Format::Markdown);
+    }
+
+    #[test]
+    fn test_read_rejects_unknown_rows() {
+        let csv = "kind,name,quantity,calories,price\nsoup,Tomato,1,0,0\n";
+        assert!(matches!(Format::Csv.read(csv), Err(ExportError::Parse(_))));
+        let csv = "kind,name,quantity,calories,price\nfruit,Fig,many,0,0\n";
+        assert!(Format::Csv.read(csv).is_err());
+    }
+}
@@ -3,35 +3,85 @@ This code defines a function called create_fruit_salad
 that takes a mutable vector of strings as input and returns
 a new vector of strings that contains the same elements as the input vector,
 but in a random order. It also includes functionality to add random dressing options.
+
+create_salad does the same with the shared fruit catalog: the fruits keep
+their catalog attributes and the salad is built under constraints such as
+a calorie limit, excluded allergens, a season and a budget.
+
+The _with variants take the random number generator, so a seeded generator
+always gives the same salad and dressing.
+
+The export module saves a salad with its dressing, quantities and totals
+as CSV, JSON, YAML or Markdown and reads it back. The repl module lets the
+interactive shell of fruit-salad-repl edit a salad.
 */
 
+pub mod export;
+pub mod repl;
+
+use fruit_catalog::{Catalog, Constraints, Salad, SaladError};
+use rand::Rng;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 
-pub fn create_fruit_salad(mut fruits: Vec<String>) -> Vec<String> {
-    let mut rng = thread_rng();
-    fruits.shuffle(&mut rng);
+pub use fruit_catalog;
+
+pub const DRESSINGS: [&str; 8] = [
+    "Honey Drizzle",
+    "Maple Syrup",
+    "Cinnamon Spice",
+    "Vanilla Yogurt",
+    "Mint Lime",
+    "Balsamic Reduction",
+    "Brown Sugar Glaze",
+    "Coconut Cream",
+];
+
+pub fn create_fruit_salad(fruits: Vec<String>) -> Vec<String> {
+    create_fruit_salad_with(fruits, &mut thread_rng())
+}
+
+pub fn create_fruit_salad_with<R: Rng + ?Sized>(
+    mut fruits: Vec<String>,
+    rng: &mut R,
+) -> Vec<String> {
+    fruits.shuffle(rng);
 
     fruits
 }
 
+/// Shuffled salad of the named fruits, or of the whole catalog when no names
+/// are given. Names the catalog does not know are used as plain fruits.
+pub fn create_salad<R: Rng + ?Sized>(
+    catalog: &Catalog,
+    fruits: &[String],
+    constraints: &Constraints,
+    rng: &mut R,
+) -> Result<Salad, SaladError> {
+    let fruits: Vec<String> = fruits
+        .iter()
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect();
+    let catalog = if fruits.is_empty() {
+        catalog.clone()
+    } else {
+        catalog.only(&fruits)
+    };
+    catalog.build_salad_with(constraints, rng)
+}
+
 pub fn get_random_dressing() -> String {
-    let dressings = vec![
-        "Honey Drizzle",
-        "Maple Syrup",
-        "Cinnamon Spice",
-        "Vanilla Yogurt",
-        "Mint Lime",
-        "Balsamic Reduction",
-        "Brown Sugar Glaze",
-        "Coconut Cream",
-    ];
-
-    let mut rng = thread_rng();
-    let dressing = dressings.choose(&mut rng).unwrap();
+    get_random_dressing_with(&mut thread_rng())
+}
+
+pub fn get_random_dressing_with<R: Rng + ?Sized>(rng: &mut R) -> String {
+    let dressing = DRESSINGS.choose(rng).unwrap();
     dressing.to_string()
 }
 
+/// Writes the fruit names as one comma separated line, without header or
+/// quoting. See `export::CsvWriter` for a complete salad.
 pub fn write_salad_to_csv(
     fruits: &[String],
     filename: &str,
@@ -42,8 +92,19 @@ pub fn write_salad_to_csv(
 }
 
 #[cfg(test)]
+#[allow(clippy::len_zero, clippy::useless_vec)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn rng(seed: u64) -> StdRng {
+        StdRng::seed_from_u64(seed)
+    }
+
+    fn fruits(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
 
     #[test]
     fn test_create_fruit_salad_returns_same_fruits() {
@@ -226,4 +287,76 @@ mod tests {
         // Clean up
         let _ = std::fs::remove_file(filename);
     }
+
+    #[test]
+    fn test_create_salad_from_catalog() {
+        let catalog = Catalog::builtin();
+        let fruits = vec!["Pear".to_string(), " maçã ".to_string(), "".to_string()];
+
+        let salad = create_salad(&catalog, &fruits, &Constraints::new(), &mut rng(1)).unwrap();
+        let mut names = salad.names();
+        names.sort();
+        assert_eq!(names, ["Pear", "maçã"]);
+        assert_eq!(salad.total_calories(), 57);
+
+        let salad = create_salad(&catalog, &[], &Constraints::new().count(3), &mut rng(1)).unwrap();
+        assert_eq!(salad.len(), 3);
+    }
+
+    #[test]
+    fn test_create_salad_with_constraints() {
+        let catalog = Catalog::builtin();
+        let constraints = Constraints::new()
+            .exclude_allergen("birch pollen")
+            .in_season(fruit_catalog::Season::Summer);
+
+        let salad = create_salad(&catalog, &[], &constraints, &mut rng(1)).unwrap();
+        let mut names = salad.names();
+        names.sort();
+        assert_eq!(names, ["Fig", "Peach"]);
+
+        let err = create_salad(&catalog, &[], &constraints.count(3), &mut rng(1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Requested 3 fruits but only 2 are available."
+        );
+    }
+
+    #[test]
+    fn test_seeded_fruit_salad() {
+        let fruits = fruits(&["apple", "banana", "orange", "kiwi", "mango"]);
+        assert_eq!(
+            create_fruit_salad_with(fruits.clone(), &mut rng(7)),
+            ["banana", "mango", "kiwi", "apple", "orange"]
+        );
+        assert_eq!(
+            create_fruit_salad_with(fruits.clone(), &mut rng(7)),
+            create_fruit_salad_with(fruits, &mut rng(7))
+        );
+    }
+
+    #[test]
+    fn test_seeded_dressing() {
+        assert_eq!(get_random_dressing_with(&mut rng(7)), "Vanilla Yogurt");
+        let mut seeded = rng(3);
+        let dressings: Vec<String> = (0..3)
+            .map(|_| get_random_dressing_with(&mut seeded))
+            .collect();
+        assert_eq!(
+            dressings,
+            ["Balsamic Reduction", "Maple Syrup", "Maple Syrup"]
+        );
+    }
+
+    #[test]
+    fn test_seeded_catalog_salad() {
+        let salad = create_salad(
+            &Catalog::builtin(),
+            &[],
+            &Constraints::new().count(3),
+            &mut rng(42),
+        )
+        .unwrap();
+        assert_eq!(salad.names(), ["Pear", "Pomegranate", "Apple"]);
+    }
 }
@@ -4,11 +4,25 @@ Usage:
 cargo run -- fruits.csv
 or
 cargo run -- --fruits "apple, pear"
+or, with the fruit catalog
+cargo run -- --season summer --max-calories 200 --count 3
+saving and loading a salad (csv, json, yaml or md)
+cargo run -- --count 3 -d --output salad.json
+cargo run -- --load salad.json --output salad.md
+editing a salad in the interactive shell, or from a script of shell commands
+cargo run -- --count 3 --repl
+cargo run -- --count 3 --script commands.txt
 
  */
 
 use clap::Parser;
-use fruit_salad_maker::{create_fruit_salad, get_random_dressing, write_salad_to_csv};
+use fruit_salad_maker::export::{Format, ServedSalad, load_salad, save_salad};
+use fruit_salad_maker::fruit_catalog::{Catalog, Constraints, Season};
+use fruit_salad_maker::repl::SaladBowl;
+use fruit_salad_maker::{create_salad, get_random_dressing_with};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[clap(
@@ -24,9 +38,42 @@ struct Opts {
     /// Add random dressing to the fruit salad
     #[clap(short, long)]
     dressing: bool,
-    /// Output CSV file for the resulting fruit salad
+    /// Output file for the resulting fruit salad, the format from the extension
     #[clap(short, long)]
     output: Option<String>,
+    /// Output format: csv, json, yaml or markdown, overrides the extension
+    #[clap(long)]
+    format: Option<Format>,
+    /// Show a saved salad (csv, json, yaml or md) instead of making a new one
+    #[clap(long)]
+    load: Option<String>,
+    /// Fruit catalog file (.toml, .json or .csv), the built-in catalog otherwise
+    #[clap(long)]
+    catalog: Option<String>,
+    /// Number of fruits, 0 for as many as fit the constraints
+    #[clap(short = 'n', long, default_value = "0")]
+    count: usize,
+    /// Maximum calories of the whole salad
+    #[clap(long)]
+    max_calories: Option<u32>,
+    /// Leave out fruits with this allergen, can be repeated
+    #[clap(short = 'x', long)]
+    exclude_allergen: Vec<String>,
+    /// Only use fruits in season: spring, summer, autumn or winter
+    #[clap(short, long)]
+    season: Option<Season>,
+    /// Maximum price of the whole salad in euros
+    #[clap(short, long)]
+    budget: Option<f64>,
+    /// Seed for the salad order and dressing, the same seed gives the same salad
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Edit the salad in an interactive shell
+    #[clap(long)]
+    repl: bool,
+    /// Run the shell commands of a file instead of reading them interactively
+    #[clap(long)]
+    script: Option<PathBuf>,
 }
 
 // Function that converts a csv file to a vector of strings
@@ -43,9 +90,39 @@ fn display_fruit_salad(fruits: Vec<String>, dressing: Option<String>) {
     }
 }
 
+fn display_totals(salad: &ServedSalad) {
+    let totals = salad.totals();
+    println!(
+        "\n{} servings, {} kcal, {:.2} EUR",
+        totals.servings, totals.calories, totals.price
+    );
+}
+
+// Save in the --format, or by the file extension with CSV as fallback
+fn save(salad: &ServedSalad, output_file: &str, format: Option<Format>) {
+    let format = format.or_else(|| Format::from_path(output_file).ok());
+    match save_salad(salad, output_file, format.or(Some(Format::Csv))) {
+        Ok(format) => println!("\nFruit salad saved to: {} ({:?})", output_file, format),
+        Err(e) => eprintln!("Error writing to file: {}", e),
+    }
+}
+
 fn main() {
     let opts: Opts = Opts::parse();
 
+    if let Some(path) = &opts.load {
+        let salad = load_salad(path, None).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        display_fruit_salad(salad.names(), salad.dressing.clone());
+        display_totals(&salad);
+        if let Some(output_file) = opts.output {
+            save(&salad, &output_file, opts.format);
+        }
+        return;
+    }
+
     // Use fruits from CSV file or command-line input
     let fruit_list = match opts.csvfile {
         Some(filename) => {
@@ -60,22 +137,66 @@ fn main() {
             .collect(),
     };
 
+    let mut rng = match opts.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
     // Add dressing if requested
     let dressing = if opts.dressing {
-        Some(get_random_dressing())
+        Some(get_random_dressing_with(&mut rng))
     } else {
         None
     };
 
+    let catalog = match &opts.catalog {
+        Some(path) => Catalog::load(path).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }),
+        None => Catalog::builtin(),
+    };
+    let constraints = Constraints {
+        count: opts.count,
+        max_calories: opts.max_calories,
+        exclude_allergens: opts.exclude_allergen,
+        season: opts.season,
+        budget: opts.budget,
+    };
+
     // display fruit salad
-    let fruit_salad = create_fruit_salad(fruit_list);
-    display_fruit_salad(fruit_salad.clone(), dressing);
+    let salad = create_salad(&catalog, &fruit_list, &constraints, &mut rng).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
 
-    // Write to output CSV file if requested
-    if let Some(output_file) = opts.output {
-        match write_salad_to_csv(&fruit_salad, &output_file) {
-            Ok(_) => println!("\nFruit salad saved to: {}", output_file),
-            Err(e) => eprintln!("Error writing to file: {}", e),
+    if opts.repl || opts.script.is_some() {
+        let names = salad.names().iter().map(|s| s.to_string()).collect();
+        let bowl = SaladBowl::new(names, catalog, dressing);
+        match fruit_salad_repl::run_session(bowl, opts.script.as_deref(), opts.seed) {
+            Ok((_, 0)) => {}
+            Ok((_, errors)) => {
+                eprintln!("{} commands failed", errors);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
         }
+        return;
+    }
+
+    let served = ServedSalad::from_salad(&salad, dressing);
+    display_fruit_salad(served.names(), served.dressing.clone());
+    display_totals(&served);
+    let allergens = salad.allergens();
+    if !allergens.is_empty() {
+        println!("Allergens: {}", allergens.join(", "));
+    }
+
+    // Write to output file if requested
+    if let Some(output_file) = opts.output {
+        save(&served, &output_file, opts.format);
     }
 }
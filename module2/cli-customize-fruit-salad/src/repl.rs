@@ -0,0 +1,119 @@
+/*
+The salad as a bowl for the interactive shell of fruit-salad-repl.
+
+The fruits are a Vec, like the rest of this crate. Saving looks the fruits
+up in the catalog and writes them with the export module, so the saved
+salad has the dressing, quantities and totals in the format of the file
+extension (CSV for unknown extensions).
+*/
+
+use std::error::Error;
+use std::path::Path;
+
+use fruit_catalog::{Catalog, Salad};
+use fruit_salad_repl::Bowl;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::export::{Format, ServedSalad, save_salad};
+
+#[derive(Clone, Debug)]
+pub struct SaladBowl {
+    pub fruits: Vec<String>,
+    pub catalog: Catalog,
+    pub dressing: Option<String>,
+}
+
+impl SaladBowl {
+    pub fn new(fruits: Vec<String>, catalog: Catalog, dressing: Option<String>) -> Self {
+        SaladBowl {
+            fruits,
+            catalog,
+            dressing,
+        }
+    }
+
+    /// The fruits with their catalog attributes and the dressing
+    pub fn served(&self) -> ServedSalad {
+        let salad = Salad::new(self.catalog.only(&self.fruits).fruits().to_vec());
+        ServedSalad::from_salad(&salad, self.dressing.clone())
+    }
+}
+
+impl Bowl for SaladBowl {
+    fn insert(&mut self, position: usize, fruit: String) {
+        self.fruits.insert(position, fruit);
+    }
+
+    fn remove(&mut self, position: usize) -> String {
+        self.fruits.remove(position)
+    }
+
+    fn push_front(&mut self, fruit: String) {
+        self.fruits.insert(0, fruit);
+    }
+
+    fn push_back(&mut self, fruit: String) {
+        self.fruits.push(fruit);
+    }
+
+    fn pop_front(&mut self) -> Option<String> {
+        if self.fruits.is_empty() {
+            None
+        } else {
+            Some(self.fruits.remove(0))
+        }
+    }
+
+    fn pop_back(&mut self) -> Option<String> {
+        self.fruits.pop()
+    }
+
+    fn shuffle(&mut self, rng: &mut StdRng) {
+        self.fruits.shuffle(rng);
+    }
+
+    fn sort(&mut self) {
+        self.fruits.sort();
+    }
+
+    fn fruits(&self) -> Vec<String> {
+        self.fruits.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.fruits.len()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let format = Format::from_path(path).unwrap_or(Format::Csv);
+        save_salad(&self.served(), path, Some(format))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fruit_salad_repl::Session;
+
+    #[test]
+    fn test_session_saves_with_catalog_data() {
+        let path = std::env::temp_dir().join(format!("salad-repl-{}.json", std::process::id()));
+        let bowl = SaladBowl::new(
+            vec!["Fig".to_string()],
+            Catalog::builtin(),
+            Some("Mint Lime".to_string()),
+        );
+        let mut session = Session::with_seed(bowl, 1);
+        let script = format!("push back Apple\nadd 0 Apple\nsave {}\n", path.display());
+        let mut out = Vec::new();
+        assert_eq!(session.run(script.as_bytes(), &mut out, false).unwrap(), 0);
+
+        let saved = crate::export::load_salad(&path, None).unwrap();
+        assert_eq!(saved.names(), ["Apple", "Apple", "Fig"]);
+        assert_eq!(saved.totals().calories, 2 * 52 + 74);
+        assert_eq!(saved.dressing.as_deref(), Some("Mint Lime"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
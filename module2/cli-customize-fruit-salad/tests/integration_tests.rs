@@ -1,3 +1,6 @@
+// The lab tests use vec! literals where an array would do
+#![allow(clippy::useless_vec)]
+
 use fruit_salad_maker::{create_fruit_salad, get_random_dressing, write_salad_to_csv};
 
 #[test]
@@ -0,0 +1,56 @@
+use std::process::Command;
+
+#[test]
+fn test_repl_script_edits_and_saves() {
+    let dir = std::env::temp_dir();
+    let script = dir.join(format!("salad-script-{}.txt", std::process::id()));
+    let saved = dir.join(format!("salad-script-{}.csv", std::process::id()));
+    std::fs::write(
+        &script,
+        format!(
+            "# start from Pear, Fig\nsort\npush front Apple\nremove 2\nundo\nshow\nsave {}\n",
+            saved.display()
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cli-customize-fruit-salad"))
+        .args(["--fruits", "Pear, Fig", "--seed", "1", "--script"])
+        .arg(&script)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("Fruit Salad: Fig, Pear\n"));
+    assert!(stdout.contains("Removed Pear at 2\n"));
+    assert!(stdout.contains("Fruit Salad: Apple, Fig, Pear\n"));
+    assert_eq!(
+        std::fs::read_to_string(&saved).unwrap(),
+        "kind,name,quantity,calories,price\n\
+         fruit,Apple,1,52,0.5\n\
+         fruit,Fig,1,74,1.8\n\
+         fruit,Pear,1,57,0.7\n\
+         total,,3,183,3.00\n"
+    );
+
+    std::fs::remove_file(&script).unwrap();
+    std::fs::remove_file(&saved).unwrap();
+}
+
+#[test]
+fn test_repl_script_failure_exit_code() {
+    let script = std::env::temp_dir().join(format!("salad-script-bad-{}.txt", std::process::id()));
+    std::fs::write(&script, "pop front\npop front\npop front\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cli-customize-fruit-salad"))
+        .args(["--fruits", "Kiwi", "--script"])
+        .arg(&script)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("Error: the salad is empty").count(), 2);
+    std::fs::remove_file(&script).unwrap();
+}
@@ -0,0 +1,94 @@
+/*
+Benchmarks of the hot paths:
+
+cargo bench
+cargo bench -- crack_shift
+
+Accuracy is measured by the eval subcommand, these only time the code.
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use decoder_ring::{
+    crack_shift, decrypt, guess_vigenere, language, solve_substitution, Cipher, LetterHistogram,
+    SolverOptions, Strategy, Vigenere,
+};
+
+const CORPUS: &str = include_str!("../corpora/en.txt");
+
+// The first `len` characters of the corpus, shifted by 10
+fn message(len: usize) -> String {
+    decrypt(&CORPUS.chars().take(len).collect::<String>(), 10)
+}
+
+fn bench_crack_shift(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crack_shift");
+    for len in [40, 400, 4000] {
+        let text = message(len);
+        for strategy in Strategy::ALL {
+            let scorer = strategy.scorer();
+            group.bench_with_input(BenchmarkId::new(strategy.name(), len), &text, |b, text| {
+                b.iter(|| crack_shift(black_box(text), 26, scorer.as_ref()))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_histogram(c: &mut Criterion) {
+    let text = message(4000);
+    let english = language::english();
+    c.bench_function("histogram/count_4000", |b| {
+        b.iter(|| LetterHistogram::from_text(black_box(&text)))
+    });
+    let histogram = LetterHistogram::from_text(&text);
+    c.bench_function("histogram/rank_shifts", |b| {
+        b.iter(|| {
+            decoder_ring::stream::crack_histogram(
+                black_box(&histogram),
+                26,
+                Strategy::ChiSquared,
+                &english,
+            )
+        })
+    });
+}
+
+fn bench_decrypt(c: &mut Criterion) {
+    let text = message(4000);
+    c.bench_function("decrypt/4000", |b| b.iter(|| decrypt(black_box(&text), 16)));
+}
+
+fn bench_vigenere(c: &mut Criterion) {
+    let plaintext: String = CORPUS.chars().take(1000).collect();
+    let text = Vigenere::new("lemon").unwrap().encrypt(&plaintext);
+    c.bench_function("guess_vigenere/1000", |b| {
+        b.iter(|| guess_vigenere(black_box(&text), 20))
+    });
+}
+
+fn bench_substitution(c: &mut Criterion) {
+    let plaintext: String = CORPUS.chars().take(600).collect();
+    let text =
+        decoder_ring::substitution::apply_substitution(&plaintext, "qwertyuiopasdfghjklzxcvbnm");
+    let options = SolverOptions {
+        restarts: 4,
+        threads: 1,
+        seed: Some(1),
+    };
+    let mut group = c.benchmark_group("solve_substitution");
+    group.sample_size(10);
+    group.bench_function("600_chars_4_restarts", |b| {
+        b.iter(|| solve_substitution(black_box(&text), &options))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_crack_shift,
+    bench_histogram,
+    bench_decrypt,
+    bench_vigenere,
+    bench_substitution
+);
+criterion_main!(benches);
@@ -0,0 +1,493 @@
+/*
+Ciphertext analysis.
+
+Runs on a message of unknown origin and reports letter statistics together
+with a ranked guess of the cipher family and the language. Each family gets
+an evidence score in 0..1 from a few simple tests, and the scores are then
+normalized into confidences:
+
+ * plain text      - language-like IoC, letters match a language unshifted,
+                     common bigrams present
+ * caesar          - letters match a language after a shift
+ * substitution    - language-like IoC, sorted letter frequencies match a
+                     language but no shift does
+ * transposition   - letters match a language unshifted, bigrams do not
+ * polyalphabetic  - flat letters, but the columns of some key length have
+                     a language-like IoC
+ * base64, hex     - the alphabet and length of the encodings
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::language::LanguageProfile;
+use super::vigenere::{column_ioc, estimate_key_length, rank_shifts};
+use super::{decrypt, stats_analysis, BigramScorer, Scorer, COMMON_NGRAMS};
+
+// Index of coincidence of uniformly random letters
+const RANDOM_IOC: f32 = 1.0 / 26.0;
+
+// Cosine similarity of unrelated letter distributions sits around this value
+const UNRELATED_SIMILARITY: f32 = 0.6;
+
+// Messages with fewer letters get lower evidence overall
+const MIN_LETTERS: usize = 40;
+
+/// Cipher families recognized by `analyze`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CipherFamily {
+    PlainText,
+    Caesar,
+    Substitution,
+    Transposition,
+    Polyalphabetic,
+    Base64,
+    Hex,
+}
+
+impl fmt::Display for CipherFamily {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CipherFamily::PlainText => "plain text",
+            CipherFamily::Caesar => "caesar",
+            CipherFamily::Substitution => "monoalphabetic substitution",
+            CipherFamily::Transposition => "transposition",
+            CipherFamily::Polyalphabetic => "polyalphabetic",
+            CipherFamily::Base64 => "base64 encoding",
+            CipherFamily::Hex => "hex encoding",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FamilyGuess {
+    pub family: CipherFamily,
+    /// Share of the total evidence, the guesses sum to 1
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageGuess {
+    pub language: String,
+    /// Cosine similarity of the letter frequencies at the best shift
+    pub similarity: f32,
+    /// Best Caesar shift for this language, 0 for plain text
+    pub shift: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalysisReport {
+    /// Characters in the message
+    pub length: usize,
+    /// ASCII letters in the message
+    pub letters: usize,
+    pub index_of_coincidence: f32,
+    /// Shannon entropy of the letters in bits, at most log2(26) = 4.70
+    pub entropy: f32,
+    /// Effective alphabet size divided by 26; 1.0 is perfectly flat
+    pub flatness: f32,
+    /// Estimated key length if the message is polyalphabetic
+    pub key_length: usize,
+    /// Languages ranked by how well the letters match, best first
+    pub languages: Vec<LanguageGuess>,
+    /// Cipher families ranked by confidence, best first
+    pub families: Vec<FamilyGuess>,
+}
+
+impl AnalysisReport {
+    pub fn best_family(&self) -> Option<CipherFamily> {
+        self.families.first().map(|guess| guess.family)
+    }
+
+    pub fn best_language(&self) -> Option<&LanguageGuess> {
+        self.languages.first()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes to JSON")
+    }
+}
+
+impl fmt::Display for AnalysisReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Length:               {} ({} letters)",
+            self.length, self.letters
+        )?;
+        writeln!(f, "Index of coincidence: {:.4}", self.index_of_coincidence)?;
+        writeln!(f, "Entropy:              {:.3} bits", self.entropy)?;
+        writeln!(f, "Flatness:             {:.3}", self.flatness)?;
+        writeln!(f, "Key length estimate:  {}", self.key_length)?;
+        writeln!(f, "\nLanguages:")?;
+        for guess in &self.languages {
+            writeln!(
+                f,
+                "  {:4} similarity {:.3} at shift {}",
+                guess.language, guess.similarity, guess.shift
+            )?;
+        }
+        writeln!(f, "\nCipher family:")?;
+        for guess in &self.families {
+            writeln!(
+                f,
+                "  {:28} {:5.1}%",
+                guess.family.to_string(),
+                guess.confidence * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Letter counts built from stats_analysis, upper and lower case merged
+fn letter_histogram(text: &str, profile: &LanguageProfile) -> [u32; 26] {
+    let mut histogram = [0u32; 26];
+    for (letter, count, _, _, _) in stats_analysis(text, profile) {
+        if letter.is_ascii_alphabetic() {
+            histogram[(letter.to_ascii_lowercase() as u8 - b'a') as usize] += count;
+        }
+    }
+    histogram
+}
+
+fn distribution(histogram: &[u32; 26]) -> [f32; 26] {
+    let total: u32 = histogram.iter().sum();
+    let mut p = [0.0; 26];
+    if total > 0 {
+        for (p, count) in p.iter_mut().zip(histogram) {
+            *p = *count as f32 / total as f32;
+        }
+    }
+    p
+}
+
+fn profile_distribution(profile: &LanguageProfile) -> [f32; 26] {
+    let mut p = [0.0; 26];
+    for (letter, freq) in &profile.unigrams {
+        if letter.is_ascii_lowercase() {
+            p[(*letter as u8 - b'a') as usize] = freq / 100.0;
+        }
+    }
+    p
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator > 0.0 {
+        dot / denominator
+    } else {
+        0.0
+    }
+}
+
+fn sorted_descending(p: &[f32; 26]) -> [f32; 26] {
+    let mut sorted = *p;
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    sorted
+}
+
+// Maps a similarity to 0..1 where unrelated distributions score 0
+fn similarity_evidence(similarity: f32) -> f32 {
+    ((similarity - UNRELATED_SIMILARITY) / (1.0 - UNRELATED_SIMILARITY)).clamp(0.0, 1.0)
+}
+
+// Maps an IoC to 0..1 where random letters score 0 and the language scores 1
+fn ioc_evidence(ioc: f32, language_ioc: f32) -> f32 {
+    ((ioc - RANDOM_IOC) / (language_ioc - RANDOM_IOC)).clamp(0.0, 1.0)
+}
+
+// Bigram score of running text in the bundled languages, measured by the
+// bigram scorer on the sample corpora
+const EXPECTED_BIGRAM_SCORE: f32 = 22.0;
+
+// Maps the bigram score to 0..1 where letters in random order score 0 and
+// running text scores 1
+fn bigram_evidence(text: &str, profile: &Arc<LanguageProfile>) -> f32 {
+    let letters = profile_distribution(profile);
+    // Profiles loaded from a file can have bigrams outside a-z
+    let index = |c: char| c.is_ascii_lowercase().then(|| (c as u8 - b'a') as usize);
+    // Expected score if the letters were shuffled
+    let chance: f32 = profile
+        .top_bigrams(COMMON_NGRAMS)
+        .iter()
+        .filter_map(|bigram| {
+            let mut chars = bigram.chars();
+            Some(letters[index(chars.next()?)?] * letters[index(chars.next()?)?] * 100.0)
+        })
+        .sum();
+    let score = BigramScorer::new(profile.clone()).score(text);
+    ((score - chance) / (EXPECTED_BIGRAM_SCORE - chance)).clamp(0.0, 1.0)
+}
+
+fn is_base64(text: &str) -> bool {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let body = compact.trim_end_matches('=');
+    compact.len() >= 8
+        && compact.len().is_multiple_of(4)
+        && compact.len() - body.len() <= 2
+        && body
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+        && body
+            .chars()
+            .any(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+        && body.chars().any(|c| c.is_ascii_lowercase())
+}
+
+fn is_hex(text: &str) -> bool {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 8
+        && compact.len().is_multiple_of(2)
+        && compact.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Analyze a message against the bundled language profiles.
+pub fn analyze(text: &str) -> AnalysisReport {
+    let profiles: Vec<Arc<LanguageProfile>> = LanguageProfile::bundled_names()
+        .into_iter()
+        .filter_map(LanguageProfile::bundled)
+        .map(Arc::new)
+        .collect();
+    analyze_with(text, &profiles)
+}
+
+/// Analyze a message against the given language profiles.
+pub fn analyze_with(text: &str, profiles: &[Arc<LanguageProfile>]) -> AnalysisReport {
+    let reference = profiles
+        .first()
+        .cloned()
+        .unwrap_or_else(super::language::english);
+    let histogram = letter_histogram(text, &reference);
+    let letters: usize = histogram.iter().map(|c| *c as usize).sum();
+    let p = distribution(&histogram);
+
+    let index_of_coincidence = super::vigenere::index_of_coincidence(text);
+    let entropy: f32 = -p
+        .iter()
+        .filter(|p| **p > 0.0)
+        .map(|p| p * p.log2())
+        .sum::<f32>();
+    let sum_of_squares: f32 = p.iter().map(|p| p * p).sum();
+    let flatness = if sum_of_squares > 0.0 {
+        (1.0 / sum_of_squares) / 26.0
+    } else {
+        0.0
+    };
+
+    // Rank the languages by the best shift by chi-squared, as for a column
+    // of a Vigenère key
+    let mut languages: Vec<(LanguageGuess, Arc<LanguageProfile>)> = profiles
+        .iter()
+        .map(|profile| {
+            let shift = rank_shifts(&letter_histogram(text, profile), profile)[0];
+            let shifted = distribution(&letter_histogram(&decrypt(text, shift), profile));
+            let guess = LanguageGuess {
+                language: profile.name.clone(),
+                similarity: cosine(&shifted, &profile_distribution(profile)),
+                shift,
+            };
+            (guess, profile.clone())
+        })
+        .collect();
+    languages.sort_by(|a, b| b.0.similarity.total_cmp(&a.0.similarity));
+
+    let mut evidence: HashMap<CipherFamily, f32> = HashMap::new();
+    let key_length = estimate_key_length(text, 20);
+
+    if let Some((best, profile)) = languages.first() {
+        let expected = profile_distribution(profile);
+        let language_ioc = profile.index_of_coincidence();
+        let ioc = ioc_evidence(index_of_coincidence, language_ioc);
+        let unshifted = similarity_evidence(cosine(&p, &expected));
+        let shifted = similarity_evidence(best.similarity);
+        let sorted = similarity_evidence(cosine(
+            &sorted_descending(&p),
+            &sorted_descending(&expected),
+        ));
+        let bigrams_unshifted = bigram_evidence(text, profile);
+        let bigrams_shifted = bigram_evidence(&decrypt(text, best.shift), profile);
+        let columns = if key_length > 1 {
+            ioc_evidence(column_ioc(text, key_length), language_ioc)
+        } else {
+            0.0
+        };
+
+        evidence.insert(CipherFamily::PlainText, ioc * unshifted * bigrams_unshifted);
+        if best.shift != 0 {
+            evidence.insert(CipherFamily::Caesar, ioc * shifted * bigrams_shifted);
+        }
+        evidence.insert(
+            CipherFamily::Substitution,
+            ioc * sorted * (1.0 - shifted.max(unshifted)),
+        );
+        evidence.insert(
+            CipherFamily::Transposition,
+            ioc * unshifted * (1.0 - bigrams_unshifted),
+        );
+        evidence.insert(CipherFamily::Polyalphabetic, (1.0 - ioc) * columns);
+    }
+
+    if is_hex(text) {
+        evidence.insert(CipherFamily::Hex, 1.0);
+    } else if is_base64(text) {
+        evidence.insert(CipherFamily::Base64, 1.0);
+    }
+
+    // Short messages are weak evidence for any family
+    let reliability = (letters as f32 / MIN_LETTERS as f32).min(1.0);
+    for (family, score) in evidence.iter_mut() {
+        if !matches!(family, CipherFamily::Hex | CipherFamily::Base64) {
+            *score *= reliability;
+        }
+    }
+
+    let total: f32 = evidence.values().sum();
+    let mut families: Vec<FamilyGuess> = evidence
+        .into_iter()
+        .filter(|(_, score)| *score > 0.0)
+        .map(|(family, score)| FamilyGuess {
+            family,
+            confidence: score / total,
+        })
+        .collect();
+    families.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| (a.family as u8).cmp(&(b.family as u8)))
+    });
+
+    AnalysisReport {
+        length: text.chars().count(),
+        letters,
+        index_of_coincidence,
+        entropy,
+        flatness,
+        key_length,
+        languages: languages.into_iter().map(|(guess, _)| guess).collect(),
+        families,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &str = "Data engineering is mostly about moving information from one place to \
+        another without losing anything important on the way. A good pipeline reads raw records, \
+        checks them, cleans them and writes them somewhere useful. The hard part is rarely the \
+        code itself, it is knowing what the data really means.";
+
+    #[test]
+    fn test_statistics() {
+        let report = analyze(ENGLISH);
+        assert_eq!(report.length, ENGLISH.chars().count());
+        assert!(report.index_of_coincidence > 0.055);
+        assert!(report.entropy > 3.8 && report.entropy < 4.4);
+        assert!(report.flatness > 0.4 && report.flatness < 0.7);
+        let total: f32 = report.families.iter().map(|g| g.confidence).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_detects_plain_text_and_language() {
+        let report = analyze(ENGLISH);
+        assert_eq!(report.best_family(), Some(CipherFamily::PlainText));
+        assert_eq!(report.best_language().unwrap().language, "en");
+
+        let finnish =
+            "Kokouksen jälkeen ihmiset kävelivät kotiin pienissä ryhmissä. Yö oli kirkas, \
+            ja tähdet loistivat kukkuloiden yllä. Lapset juoksivat edellä, ja koirat haukkuivat.";
+        let report = analyze(finnish);
+        assert_eq!(report.best_family(), Some(CipherFamily::PlainText));
+        assert_eq!(report.best_language().unwrap().language, "fi");
+    }
+
+    #[test]
+    fn test_detects_caesar() {
+        let report = analyze(&decrypt(ENGLISH, 7));
+        assert_eq!(report.best_family(), Some(CipherFamily::Caesar));
+        assert_eq!(report.best_language().unwrap().shift, 19);
+    }
+
+    #[test]
+    fn test_detects_substitution() {
+        let key = "qwertyuiopasdfghjklzxcvbnm";
+        let encrypted: String = ENGLISH
+            .chars()
+            .map(|c| match c {
+                'a'..='z' => key.as_bytes()[(c as u8 - b'a') as usize] as char,
+                'A'..='Z' => key.as_bytes()[(c as u8 - b'A') as usize].to_ascii_uppercase() as char,
+                _ => c,
+            })
+            .collect();
+        assert_eq!(
+            analyze(&encrypted).best_family(),
+            Some(CipherFamily::Substitution)
+        );
+    }
+
+    #[test]
+    fn test_detects_transposition() {
+        // Columnar transposition with 7 columns
+        let letters: Vec<char> = ENGLISH
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .collect();
+        let encrypted: String = (0..7)
+            .flat_map(|column| letters.iter().skip(column).step_by(7))
+            .collect();
+        assert_eq!(
+            analyze(&encrypted).best_family(),
+            Some(CipherFamily::Transposition)
+        );
+    }
+
+    #[test]
+    fn test_detects_polyalphabetic() {
+        let encrypted = crate::vigenere::decrypt_vigenere(ENGLISH, "cipher");
+        let report = analyze(&encrypted);
+        assert_eq!(report.best_family(), Some(CipherFamily::Polyalphabetic));
+        assert_eq!(report.key_length, 6);
+    }
+
+    #[test]
+    fn test_detects_encodings() {
+        let base64 = "RGF0YSBlbmdpbmVlcmluZyBpcyBtb3N0bHkgYWJvdXQgbW92aW5nIGluZm9ybWF0aW9u";
+        assert_eq!(analyze(base64).best_family(), Some(CipherFamily::Base64));
+        let hex = "4461746120656e67696e656572696e67206973206d6f73746c792061626f7574";
+        assert_eq!(analyze(hex).best_family(), Some(CipherFamily::Hex));
+    }
+
+    #[test]
+    fn test_empty_message() {
+        let report = analyze("");
+        assert_eq!(report.letters, 0);
+        assert_eq!(report.entropy, 0.0);
+        assert!(report.families.is_empty());
+    }
+
+    #[test]
+    fn test_profile_with_letters_outside_a_to_z() {
+        let json = r#"{"name":"odd","unigrams":{"e":12.0,"t":9.0,"ä":3.0},
+            "bigrams":{"th":3.0,"Th":2.0,"ä":1.0,"éa":1.0,"e":1.0},"trigrams":{}}"#;
+        let profile = Arc::new(LanguageProfile::from_json(json).unwrap());
+        let report = analyze_with(ENGLISH, &[profile]);
+        assert_eq!(report.best_language().unwrap().language, "odd");
+    }
+
+    #[test]
+    fn test_json_output() {
+        let json = analyze(ENGLISH).to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["families"][0]["family"], "plain_text");
+        assert!(value["index_of_coincidence"].as_f64().unwrap() > 0.05);
+    }
+}
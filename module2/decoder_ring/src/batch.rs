@@ -0,0 +1,224 @@
+/*
+Batch cracking.
+
+Cracks many shift-ciphered messages in parallel with rayon. Messages come
+from a directory, one message per file, or from a JSONL file with one
+{"id": ..., "message": ...} object per line. Every message gets one result
+line of JSON, in input order.
+*/
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::crack_shift;
+use super::language::LanguageProfile;
+use super::scorer::Strategy;
+
+/// A message to crack, named by its file or its JSONL id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchMessage {
+    /// Defaults to "line N" in JSONL input
+    #[serde(default)]
+    pub id: String,
+    pub message: String,
+}
+
+/// The best shift of one message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub id: String,
+    pub shift: u8,
+    pub score: f32,
+    pub decrypted: String,
+}
+
+/// Error loading a batch
+#[derive(Debug)]
+pub enum BatchError {
+    Io(io::Error),
+    /// A JSONL line that is not a message object, with its line number
+    Json(usize, serde_json::Error),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::Io(e) => write!(f, "{}", e),
+            BatchError::Json(line, e) => write!(f, "invalid message on line {}: {}", line, e),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl From<io::Error> for BatchError {
+    fn from(e: io::Error) -> Self {
+        BatchError::Io(e)
+    }
+}
+
+/// Messages of a JSONL file; blank lines are skipped
+pub fn load_jsonl(path: impl AsRef<Path>) -> Result<Vec<BatchMessage>, BatchError> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    let mut messages = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut message: BatchMessage =
+            serde_json::from_str(&line).map_err(|e| BatchError::Json(i + 1, e))?;
+        if message.id.is_empty() {
+            message.id = format!("line {}", i + 1);
+        }
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// One message per file of a directory, sorted by file name.
+/// Subdirectories are not searched.
+pub fn load_directory(path: impl AsRef<Path>) -> Result<Vec<BatchMessage>, BatchError> {
+    let mut messages = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            messages.push(BatchMessage {
+                id: entry.file_name().to_string_lossy().into_owned(),
+                message: fs::read_to_string(entry.path())?,
+            });
+        }
+    }
+    messages.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(messages)
+}
+
+/// A directory or a JSONL file of messages
+pub fn load_batch(path: impl AsRef<Path>) -> Result<Vec<BatchMessage>, BatchError> {
+    if path.as_ref().is_dir() {
+        load_directory(path)
+    } else {
+        load_jsonl(path)
+    }
+}
+
+/// Crack every message in parallel, results in input order
+pub fn crack_batch(
+    messages: &[BatchMessage],
+    strategy: Strategy,
+    profile: Arc<LanguageProfile>,
+) -> Vec<BatchResult> {
+    messages
+        .par_iter()
+        .map(|message| {
+            let scorer = strategy.scorer_for(profile.clone());
+            let result = crack_shift(&message.message, 26, scorer.as_ref());
+            let (shift, score, decrypted) = match result.best() {
+                Some(best) => (best.shift, best.score, best.decrypted.clone()),
+                None => (0, f32::NEG_INFINITY, String::new()),
+            };
+            BatchResult {
+                id: message.id.clone(),
+                shift,
+                score,
+                decrypted,
+            }
+        })
+        .collect()
+}
+
+/// Write the results as JSONL, one line per message
+pub fn write_results<W: Write>(results: &[BatchResult], mut writer: W) -> io::Result<()> {
+    for result in results {
+        serde_json::to_writer(&mut writer, result)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decrypt, language};
+
+    const PLAIN: [&str; 3] = [
+        "Off to the bunker. Every person for themselves",
+        "The quick brown fox jumps over the lazy dog",
+        "Meet me at the old mill when the clock strikes nine",
+    ];
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("decoder_ring_batch_{}", name))
+    }
+
+    #[test]
+    fn test_crack_batch_keeps_order() {
+        let messages: Vec<BatchMessage> = PLAIN
+            .iter()
+            .enumerate()
+            .map(|(i, text)| BatchMessage {
+                id: i.to_string(),
+                message: decrypt(text, 26 - (i as u8 + 3)),
+            })
+            .collect();
+        let results = crack_batch(&messages, Strategy::Weighted, language::english());
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.id, i.to_string());
+            assert_eq!(result.shift, i as u8 + 3);
+            assert_eq!(result.decrypted, PLAIN[i]);
+        }
+    }
+
+    #[test]
+    fn test_load_jsonl() {
+        let path = temp_path("input.jsonl");
+        fs::write(
+            &path,
+            "{\"id\": \"first\", \"message\": \"Ypp dy dro lexuob\"}\n\n{\"message\": \"Uryyb\"}\n",
+        )
+        .unwrap();
+        let messages = load_batch(&path).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].id, "first");
+        assert_eq!(messages[1].id, "line 3");
+
+        fs::write(&path, "{\"id\": \"first\"}\n").unwrap();
+        assert!(matches!(load_jsonl(&path), Err(BatchError::Json(1, _))));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_directory() {
+        let dir = temp_path("dir");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("b.txt"), "Uryyb").unwrap();
+        fs::write(dir.join("a.txt"), "Ypp dy dro lexuob").unwrap();
+        let messages = load_batch(&dir).unwrap();
+        let ids: Vec<&str> = messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["a.txt", "b.txt"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_results_round_trip() {
+        let results = vec![BatchResult {
+            id: "a".to_string(),
+            shift: 16,
+            score: 1.5,
+            decrypted: "Off to the bunker".to_string(),
+        }];
+        let mut output = Vec::new();
+        write_results(&results, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        let parsed: BatchResult = serde_json::from_str(text.trim()).unwrap();
+        assert_eq!(parsed, results[0]);
+    }
+}
@@ -0,0 +1,529 @@
+/*
+Classical ciphers.
+
+Every cipher implements Cipher, so the CLI and the tests can treat them
+alike. Substitution ciphers change ASCII letters only and keep their case;
+everything else, including accented and other non-ASCII characters, passes
+through unchanged. The rail fence moves characters around but never changes
+them.
+
+Ciphers are usually built from a key spec such as "caesar:3" or
+"vigenere:lemon", see `parse_cipher`.
+*/
+
+use std::fmt;
+
+use super::decrypt;
+
+/// A reversible cipher: `decrypt(encrypt(text)) == text` for any text.
+pub trait Cipher {
+    fn encrypt(&self, text: &str) -> String;
+    fn decrypt(&self, text: &str) -> String;
+
+    /// Name used in key specs and reports
+    fn name(&self) -> &str;
+}
+
+/// Error for a key spec that names no cipher or carries a bad key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CipherError {
+    Unknown(String),
+    InvalidKey { cipher: String, reason: String },
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CipherError::Unknown(name) => write!(
+                f,
+                "unknown cipher '{}'. Choose from: {}",
+                name,
+                CIPHER_NAMES.join(", ")
+            ),
+            CipherError::InvalidKey { cipher, reason } => {
+                write!(f, "invalid {} key: {}", cipher, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CipherError {}
+
+fn invalid_key(cipher: &str, reason: impl Into<String>) -> CipherError {
+    CipherError::InvalidKey {
+        cipher: cipher.to_string(),
+        reason: reason.into(),
+    }
+}
+
+// Alphabet index 0..26 of each letter of a key, other characters ignored
+fn key_shifts(key: &str) -> Vec<u8> {
+    key.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_lowercase() - b'a')
+        .collect()
+}
+
+// Replace every ASCII letter by `map(index)`, keeping its case. The second
+// argument counts the letters seen so far, for the keyed ciphers.
+fn map_letters(text: &str, mut map: impl FnMut(u8, usize) -> u8) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    for c in text.chars() {
+        if c.is_ascii_alphabetic() {
+            let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+            result.push((base + map(c as u8 - base, i) % 26) as char);
+            i += 1;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Shift every letter forward by a fixed amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caesar {
+    shift: u8,
+}
+
+impl Caesar {
+    pub fn new(shift: u8) -> Self {
+        Caesar { shift: shift % 26 }
+    }
+
+    pub fn shift(&self) -> u8 {
+        self.shift
+    }
+}
+
+impl Cipher for Caesar {
+    fn encrypt(&self, text: &str) -> String {
+        decrypt(text, self.shift)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        decrypt(text, (26 - self.shift) % 26)
+    }
+
+    fn name(&self) -> &str {
+        "caesar"
+    }
+}
+
+/// Caesar with shift 13, its own inverse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rot13;
+
+impl Cipher for Rot13 {
+    fn encrypt(&self, text: &str) -> String {
+        decrypt(text, 13)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        decrypt(text, 13)
+    }
+
+    fn name(&self) -> &str {
+        "rot13"
+    }
+}
+
+/// Reverse the alphabet: a <-> z, b <-> y, ... Its own inverse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Atbash;
+
+impl Cipher for Atbash {
+    fn encrypt(&self, text: &str) -> String {
+        map_letters(text, |x, _| 25 - x)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        self.encrypt(text)
+    }
+
+    fn name(&self) -> &str {
+        "atbash"
+    }
+}
+
+/// E(x) = a * x + b mod 26, where `a` must be coprime to 26.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    a: u8,
+    b: u8,
+    a_inverse: u8,
+}
+
+impl Affine {
+    pub fn new(a: u8, b: u8) -> Result<Self, CipherError> {
+        let a = a % 26;
+        let a_inverse = (1..26)
+            .find(|inv| (a as u32 * inv) % 26 == 1)
+            .ok_or_else(|| invalid_key("affine", format!("a = {} is not coprime to 26", a)))?;
+        Ok(Affine {
+            a,
+            b: b % 26,
+            a_inverse: a_inverse as u8,
+        })
+    }
+}
+
+impl Cipher for Affine {
+    fn encrypt(&self, text: &str) -> String {
+        map_letters(text, |x, _| {
+            ((self.a as u32 * x as u32 + self.b as u32) % 26) as u8
+        })
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        map_letters(text, |y, _| {
+            ((self.a_inverse as u32 * (y as u32 + 26 - self.b as u32)) % 26) as u8
+        })
+    }
+
+    fn name(&self) -> &str {
+        "affine"
+    }
+}
+
+/// Shift each letter by the matching key letter (a = 0), repeating the key.
+/// Only letters advance the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vigenere {
+    shifts: Vec<u8>,
+}
+
+impl Vigenere {
+    pub fn new(key: &str) -> Result<Self, CipherError> {
+        let shifts = key_shifts(key);
+        if shifts.is_empty() {
+            return Err(invalid_key("vigenere", "the key needs at least one letter"));
+        }
+        Ok(Vigenere { shifts })
+    }
+
+    pub fn key(&self) -> String {
+        self.shifts.iter().map(|s| (b'a' + s) as char).collect()
+    }
+}
+
+impl Cipher for Vigenere {
+    fn encrypt(&self, text: &str) -> String {
+        map_letters(text, |x, i| x + self.shifts[i % self.shifts.len()])
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        map_letters(text, |y, i| y + 26 - self.shifts[i % self.shifts.len()])
+    }
+
+    fn name(&self) -> &str {
+        "vigenere"
+    }
+}
+
+/// E(x) = key - x mod 26, the Vigenère variant that is its own inverse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Beaufort {
+    shifts: Vec<u8>,
+}
+
+impl Beaufort {
+    pub fn new(key: &str) -> Result<Self, CipherError> {
+        let shifts = key_shifts(key);
+        if shifts.is_empty() {
+            return Err(invalid_key("beaufort", "the key needs at least one letter"));
+        }
+        Ok(Beaufort { shifts })
+    }
+}
+
+impl Cipher for Beaufort {
+    fn encrypt(&self, text: &str) -> String {
+        map_letters(text, |x, i| self.shifts[i % self.shifts.len()] + 26 - x)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        self.encrypt(text)
+    }
+
+    fn name(&self) -> &str {
+        "beaufort"
+    }
+}
+
+/*
+Rail fence transposition:
+
+The characters are written in a zigzag over `rails` rows and read off row by
+row. With 3 rails "WEAREDISCOVERED" is laid out as
+
+W . . . E . . . C . . . R . .
+. E . R . D . S . O . E . E .
+. . A . . . I . . . V . . . D
+
+and encrypts to "WECRERDSOEEAIVD". Every character takes part, spaces and
+punctuation included.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RailFence {
+    rails: usize,
+}
+
+impl RailFence {
+    pub fn new(rails: usize) -> Result<Self, CipherError> {
+        if rails == 0 {
+            return Err(invalid_key("railfence", "at least one rail is needed"));
+        }
+        Ok(RailFence { rails })
+    }
+
+    // Rail of each position of a text of `len` characters
+    fn pattern(&self, len: usize) -> Vec<usize> {
+        let cycle = (2 * self.rails).saturating_sub(2).max(1);
+        (0..len)
+            .map(|i| {
+                let step = i % cycle;
+                if step < self.rails {
+                    step
+                } else {
+                    cycle - step
+                }
+            })
+            .collect()
+    }
+
+    // Positions of the text in the order they are read off the rails
+    fn order(&self, len: usize) -> Vec<usize> {
+        let pattern = self.pattern(len);
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by_key(|&i| pattern[i]);
+        order
+    }
+}
+
+impl Cipher for RailFence {
+    fn encrypt(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        self.order(chars.len())
+            .into_iter()
+            .map(|i| chars[i])
+            .collect()
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut plain = vec!['\0'; chars.len()];
+        for (c, i) in chars.iter().zip(self.order(chars.len())) {
+            plain[i] = *c;
+        }
+        plain.into_iter().collect()
+    }
+
+    fn name(&self) -> &str {
+        "railfence"
+    }
+}
+
+/// Cipher names accepted by `parse_cipher`
+pub const CIPHER_NAMES: [&str; 7] = [
+    "caesar",
+    "rot13",
+    "atbash",
+    "affine",
+    "vigenere",
+    "beaufort",
+    "railfence",
+];
+
+fn parse_number<T: std::str::FromStr>(cipher: &str, value: &str) -> Result<T, CipherError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid_key(cipher, format!("'{}' is not a number", value)))
+}
+
+/*
+Build a cipher from a key spec, the cipher name and its key separated by
+a colon:
+
+caesar:3  rot13  atbash  affine:5,8  vigenere:lemon  beaufort:fortify
+railfence:3
+*/
+pub fn parse_cipher(spec: &str) -> Result<Box<dyn Cipher>, CipherError> {
+    let (name, key) = match spec.split_once(':') {
+        Some((name, key)) => (name, Some(key)),
+        None => (spec, None),
+    };
+    let name = name.trim().to_ascii_lowercase();
+    let require_key = || key.ok_or_else(|| invalid_key(&name, "missing key, e.g. caesar:3"));
+
+    let cipher: Box<dyn Cipher> = match name.as_str() {
+        "caesar" => {
+            let shift: u32 = parse_number(&name, require_key()?)?;
+            Box::new(Caesar::new((shift % 26) as u8))
+        }
+        "rot13" => Box::new(Rot13),
+        "atbash" => Box::new(Atbash),
+        "affine" => {
+            let (a, b) = require_key()?
+                .split_once(',')
+                .ok_or_else(|| invalid_key(&name, "expected a,b such as 5,8"))?;
+            let a: u32 = parse_number(&name, a)?;
+            let b: u32 = parse_number(&name, b)?;
+            Box::new(Affine::new((a % 26) as u8, (b % 26) as u8)?)
+        }
+        "vigenere" => Box::new(Vigenere::new(require_key()?)?),
+        "beaufort" => Box::new(Beaufort::new(require_key()?)?),
+        "railfence" => Box::new(RailFence::new(parse_number(&name, require_key()?)?)?),
+        _ => return Err(CipherError::Unknown(name)),
+    };
+    Ok(cipher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_known_vectors() {
+        assert_eq!(Caesar::new(3).encrypt("Hello, World!"), "Khoor, Zruog!");
+        assert_eq!(Rot13.encrypt("Why did the chicken"), "Jul qvq gur puvpxra");
+        assert_eq!(Atbash.encrypt("Wizard"), "Draziw");
+        assert_eq!(
+            Affine::new(5, 8).unwrap().encrypt("AFFINE cipher"),
+            "IHHWVC swfrcp"
+        );
+        assert_eq!(
+            Vigenere::new("LEMON").unwrap().encrypt("ATTACKATDAWN"),
+            "LXFOPVEFRNHR"
+        );
+        assert_eq!(
+            Beaufort::new("FORTIFICATION")
+                .unwrap()
+                .encrypt("DEFENDTHEEASTWALLOFTHECASTLE"),
+            "CKMPVCPVWPIWUJOGIUAPVWRIWUUK"
+        );
+        assert_eq!(
+            RailFence::new(3).unwrap().encrypt("WEAREDISCOVERED"),
+            "WECRERDSOEEAIVD"
+        );
+    }
+
+    #[test]
+    fn test_caesar_matches_decrypt() {
+        // The shift 16 message from the crate docs
+        let cipher = Caesar::new(10);
+        assert_eq!(
+            cipher.encrypt("Off to the bunker"),
+            decrypt("Off to the bunker", 10)
+        );
+        assert_eq!(cipher.decrypt("Ypp dy dro lexuob"), "Off to the bunker");
+        assert_eq!(Caesar::new(29).shift(), 3);
+    }
+
+    #[test]
+    fn test_vigenere_matches_cracker() {
+        let text = "Attack at dawn, then retreat";
+        let encrypted = Vigenere::new("lemon").unwrap().encrypt(text);
+        assert_eq!(
+            super::super::vigenere::decrypt_vigenere(&encrypted, "lemon"),
+            text
+        );
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert!(matches!(
+            Affine::new(13, 1),
+            Err(CipherError::InvalidKey { .. })
+        ));
+        assert!(Vigenere::new("123").is_err());
+        assert!(Beaufort::new("").is_err());
+        assert!(RailFence::new(0).is_err());
+    }
+
+    #[test]
+    fn test_parse_cipher() {
+        let cases = [
+            ("caesar:3", "caesar"),
+            ("ROT13", "rot13"),
+            ("atbash", "atbash"),
+            ("affine:5,8", "affine"),
+            ("vigenere:lemon", "vigenere"),
+            ("beaufort:fortify", "beaufort"),
+            ("railfence:4", "railfence"),
+        ];
+        for (spec, name) in cases {
+            assert_eq!(parse_cipher(spec).unwrap().name(), name);
+        }
+        assert_eq!(parse_cipher("caesar:3").unwrap().encrypt("abc"), "def");
+        assert_eq!(parse_cipher("affine:31,34").unwrap().encrypt("a"), "i");
+    }
+
+    #[test]
+    fn test_parse_cipher_errors() {
+        let err = parse_cipher("enigma:abc").err().unwrap();
+        assert_eq!(err, CipherError::Unknown("enigma".to_string()));
+        assert!(err.to_string().contains("railfence"));
+        assert!(parse_cipher("caesar")
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("missing key"));
+        assert!(parse_cipher("caesar:x").is_err());
+        assert!(parse_cipher("affine:4,1").is_err());
+        assert!(parse_cipher("affine:5").is_err());
+        assert!(parse_cipher("railfence:0").is_err());
+    }
+
+    // Key specs covering every cipher
+    fn any_spec() -> impl Strategy<Value = String> {
+        prop_oneof![
+            (0u8..26).prop_map(|s| format!("caesar:{}", s)),
+            Just("rot13".to_string()),
+            Just("atbash".to_string()),
+            (
+                prop::sample::select(vec![1u8, 3, 5, 7, 9, 11, 15, 17, 19, 21, 23, 25]),
+                0u8..26
+            )
+                .prop_map(|(a, b)| format!("affine:{},{}", a, b)),
+            "[a-zA-Z]{1,12}".prop_map(|key| format!("vigenere:{}", key)),
+            "[a-zA-Z]{1,12}".prop_map(|key| format!("beaufort:{}", key)),
+            (1usize..12).prop_map(|rails| format!("railfence:{}", rails)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(spec in any_spec(), text in "\\PC*") {
+            let cipher = parse_cipher(&spec).unwrap();
+            prop_assert_eq!(cipher.decrypt(&cipher.encrypt(&text)), text);
+        }
+
+        #[test]
+        fn prop_non_ascii_passes_through(spec in any_spec(), text in "\\PC*") {
+            let cipher = parse_cipher(&spec).unwrap();
+            let encrypted = cipher.encrypt(&text);
+            prop_assert_eq!(encrypted.chars().count(), text.chars().count());
+            let non_ascii = |s: &str| s.chars().filter(|c| !c.is_ascii()).collect::<Vec<_>>();
+            if cipher.name() == "railfence" {
+                // Moved, not changed
+                let mut before = non_ascii(&text);
+                let mut after = non_ascii(&encrypted);
+                before.sort_unstable();
+                after.sort_unstable();
+                prop_assert_eq!(after, before);
+            } else {
+                // Left in place
+                for (a, b) in text.chars().zip(encrypted.chars()) {
+                    prop_assert_eq!(a.is_ascii_alphabetic(), b.is_ascii_alphabetic());
+                    if !a.is_ascii_alphabetic() {
+                        prop_assert_eq!(a, b);
+                    }
+                }
+            }
+        }
+    }
+}
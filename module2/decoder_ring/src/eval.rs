@@ -0,0 +1,311 @@
+/*
+Accuracy evaluation of the scoring strategies.
+
+1. generate_cases cuts snippets of several lengths out of a plaintext corpus
+   and encrypts each one at every shift. Snippet positions come from a
+   seeded generator, so a seed always gives the same cases.
+2. run_eval cracks every case with every strategy and records whether the
+   right shift won and how long it took, per length bucket.
+3. EvalReport shows accuracy and latency per bucket and the shortest length
+   from which a strategy stays reliable.
+
+cargo run --release -- eval --corpus corpora/eval/en.txt --samples 20 --seed 1
+*/
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use super::crack_shift;
+use super::decrypt;
+use super::language::LanguageProfile;
+use super::scorer::Strategy;
+
+/// Snippet lengths evaluated by default, in characters
+pub const DEFAULT_LENGTHS: [usize; 8] = [10, 20, 40, 60, 80, 120, 200, 400];
+
+/// Accuracy a strategy needs to count as reliable
+pub const DEFAULT_THRESHOLD: f32 = 0.95;
+
+/// A plaintext snippet encrypted with a known shift.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalCase {
+    pub length: usize,
+    pub plaintext: String,
+    pub ciphertext: String,
+    /// The shift crack_shift has to find, `decrypt(ciphertext, shift)`
+    /// gives back the plaintext
+    pub shift: u8,
+}
+
+/*
+Snippets of `samples` random positions per length, each encrypted at all
+26 shifts. Whitespace runs are collapsed to one space first so line breaks
+in the corpus do not count towards the length. Lengths longer than the
+corpus are skipped.
+*/
+pub fn generate_cases(corpus: &str, lengths: &[usize], samples: usize, seed: u64) -> Vec<EvalCase> {
+    let chars: Vec<char> = corpus
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cases = Vec::new();
+
+    for &length in lengths {
+        if length == 0 || length > chars.len() {
+            continue;
+        }
+        for _ in 0..samples {
+            let start = rng.gen_range(0..=chars.len() - length);
+            let plaintext: String = chars[start..start + length].iter().collect();
+            for shift in 0..26u8 {
+                cases.push(EvalCase {
+                    length,
+                    ciphertext: decrypt(&plaintext, (26 - shift) % 26),
+                    plaintext: plaintext.clone(),
+                    shift,
+                });
+            }
+        }
+    }
+    cases
+}
+
+/// Accuracy and latency of one strategy on one length bucket.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BucketResult {
+    pub length: usize,
+    pub cases: usize,
+    pub correct: usize,
+    pub accuracy: f32,
+    /// Mean time of one crack_shift call, in microseconds
+    pub mean_micros: f32,
+}
+
+/// Results of one strategy, buckets by increasing length.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyEval {
+    #[serde(serialize_with = "serialize_strategy")]
+    pub strategy: Strategy,
+    pub buckets: Vec<BucketResult>,
+    /// Shortest length from which every bucket reaches the threshold
+    pub reliable_length: Option<usize>,
+}
+
+fn serialize_strategy<S: serde::Serializer>(strategy: &Strategy, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(strategy.name())
+}
+
+impl StrategyEval {
+    pub fn overall_accuracy(&self) -> f32 {
+        let cases: usize = self.buckets.iter().map(|b| b.cases).sum();
+        let correct: usize = self.buckets.iter().map(|b| b.correct).sum();
+        if cases == 0 {
+            0.0
+        } else {
+            correct as f32 / cases as f32
+        }
+    }
+}
+
+/// Evaluation of all strategies on the same cases.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EvalReport {
+    pub language: String,
+    pub threshold: f32,
+    pub strategies: Vec<StrategyEval>,
+}
+
+// The shortest length after which no bucket falls below the threshold
+fn reliable_length(buckets: &[BucketResult], threshold: f32) -> Option<usize> {
+    let mut reliable = None;
+    for bucket in buckets.iter().rev() {
+        if bucket.accuracy < threshold {
+            break;
+        }
+        reliable = Some(bucket.length);
+    }
+    reliable
+}
+
+/// Crack every case with every strategy
+pub fn run_eval(
+    cases: &[EvalCase],
+    strategies: &[Strategy],
+    profile: Arc<LanguageProfile>,
+    threshold: f32,
+) -> EvalReport {
+    let mut lengths: Vec<usize> = cases.iter().map(|case| case.length).collect();
+    lengths.sort_unstable();
+    lengths.dedup();
+
+    let strategies = strategies
+        .iter()
+        .map(|&strategy| {
+            let scorer = strategy.scorer_for(profile.clone());
+            let buckets: Vec<BucketResult> = lengths
+                .iter()
+                .map(|&length| {
+                    let mut total = 0;
+                    let mut correct = 0;
+                    let start = Instant::now();
+                    for case in cases.iter().filter(|case| case.length == length) {
+                        let result = crack_shift(&case.ciphertext, 26, scorer.as_ref());
+                        if result
+                            .best()
+                            .is_some_and(|best| best.decrypted == case.plaintext)
+                        {
+                            correct += 1;
+                        }
+                        total += 1;
+                    }
+                    let elapsed = start.elapsed().as_secs_f32();
+                    BucketResult {
+                        length,
+                        cases: total,
+                        correct,
+                        accuracy: correct as f32 / total as f32,
+                        mean_micros: elapsed * 1e6 / total as f32,
+                    }
+                })
+                .collect();
+            StrategyEval {
+                strategy,
+                reliable_length: reliable_length(&buckets, threshold),
+                buckets,
+            }
+        })
+        .collect();
+
+    EvalReport {
+        language: profile.name.clone(),
+        threshold,
+        strategies,
+    }
+}
+
+impl EvalReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes to JSON")
+    }
+}
+
+impl fmt::Display for EvalReport {
+    /// Markdown tables: accuracy per length, then latency per length
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lengths: Vec<usize> = self
+            .strategies
+            .first()
+            .map(|s| s.buckets.iter().map(|b| b.length).collect())
+            .unwrap_or_default();
+        let header: String = lengths.iter().map(|l| format!(" {} |", l)).collect();
+        let rule: String = lengths.iter().map(|_| "---|").collect();
+
+        writeln!(f, "Accuracy by message length ({})\n", self.language)?;
+        writeln!(f, "| Strategy |{} Overall | Reliable from |", header)?;
+        writeln!(f, "|---|{}---|---|", rule)?;
+        for eval in &self.strategies {
+            let cells: String = eval
+                .buckets
+                .iter()
+                .map(|b| format!(" {:.0}% |", b.accuracy * 100.0))
+                .collect();
+            let reliable = eval
+                .reliable_length
+                .map_or("never".to_string(), |l| format!("{} chars", l));
+            writeln!(
+                f,
+                "| {} |{} {:.1}% | {} |",
+                eval.strategy,
+                cells,
+                eval.overall_accuracy() * 100.0,
+                reliable
+            )?;
+        }
+
+        writeln!(f, "\nMean time per message (µs)\n")?;
+        writeln!(f, "| Strategy |{}", header)?;
+        writeln!(f, "|---|{}", rule)?;
+        for eval in &self.strategies {
+            let cells: String = eval
+                .buckets
+                .iter()
+                .map(|b| format!(" {:.1} |", b.mean_micros))
+                .collect();
+            writeln!(f, "| {} |{}", eval.strategy, cells)?;
+        }
+        writeln!(
+            f,
+            "\nReliable: accuracy of at least {:.0}% at that length and every longer one.",
+            self.threshold * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language;
+
+    const CORPUS: &str = include_str!("../corpora/eval/en.txt");
+
+    #[test]
+    fn test_generate_cases() {
+        let cases = generate_cases(CORPUS, &[10, 50], 3, 1);
+        assert_eq!(cases.len(), 2 * 3 * 26);
+        for case in &cases {
+            assert_eq!(case.plaintext.chars().count(), case.length);
+            assert_eq!(decrypt(&case.ciphertext, case.shift), case.plaintext);
+            assert!(!case.plaintext.contains('\n'));
+        }
+        assert_eq!(generate_cases(CORPUS, &[10, 50], 3, 1), cases);
+        assert_ne!(generate_cases(CORPUS, &[10, 50], 3, 2), cases);
+        assert!(generate_cases("too short", &[100], 3, 1).is_empty());
+    }
+
+    #[test]
+    fn test_reliable_length() {
+        let bucket = |length, accuracy| BucketResult {
+            length,
+            cases: 1,
+            correct: 0,
+            accuracy,
+            mean_micros: 0.0,
+        };
+        let buckets = [
+            bucket(10, 0.5),
+            bucket(20, 0.97),
+            bucket(40, 0.9),
+            bucket(80, 1.0),
+        ];
+        assert_eq!(reliable_length(&buckets, 0.95), Some(80));
+        assert_eq!(reliable_length(&buckets, 0.5), Some(10));
+        assert_eq!(reliable_length(&buckets[..3], 0.95), None);
+    }
+
+    #[test]
+    fn test_long_messages_are_reliable() {
+        let cases = generate_cases(CORPUS, &[10, 200], 2, 3);
+        let report = run_eval(&cases, &Strategy::ALL, language::english(), 0.95);
+        assert_eq!(report.strategies.len(), Strategy::ALL.len());
+        for eval in &report.strategies {
+            assert_eq!(eval.buckets.len(), 2);
+            assert_eq!(eval.buckets[1].cases, 52);
+            // The chi-squared scorer counts upper and lower case apart and
+            // skips missing letters, which the harness is there to show
+            if eval.strategy != Strategy::ChiSquared {
+                assert!(eval.reliable_length.is_some(), "{}", eval.strategy);
+            }
+        }
+        let table = report.to_string();
+        assert!(table.contains("| chi_squared |"));
+        assert!(report.to_json().contains("\"reliable_length\""));
+    }
+}
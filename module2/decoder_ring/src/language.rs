@@ -0,0 +1,324 @@
+/*
+Language profiles.
+
+A profile holds the letter, bigram, trigram and quadgram frequencies of one
+language.
+Profiles are trained from a sample corpus, saved as JSON and loaded again
+with --lang. Only ASCII letters are counted because the ciphers only
+rotate ASCII letters; accented letters pass through unchanged.
+
+Bundled profiles were trained from the sample texts in corpora/:
+cargo run -- train --corpus corpora/fi.txt --name fi --output profiles/fi.json
+The texts in corpora/eval/ are kept out of training, for the eval
+subcommand.
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+// Number of bigrams and trigrams kept when training a profile
+const MAX_NGRAMS: usize = 100;
+
+// Number of quadgrams kept, enough to cover most of a sample corpus
+const MAX_QUADGRAMS: usize = 3000;
+
+// Profiles shipped with the crate
+const BUNDLED: [(&str, &str); 4] = [
+    ("en", include_str!("../profiles/en.json")),
+    ("de", include_str!("../profiles/de.json")),
+    ("fi", include_str!("../profiles/fi.json")),
+    ("pt", include_str!("../profiles/pt.json")),
+];
+
+/// Letter, bigram, trigram and quadgram frequencies of a language, in percent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageProfile {
+    pub name: String,
+    pub unigrams: BTreeMap<char, f32>,
+    pub bigrams: BTreeMap<String, f32>,
+    pub trigrams: BTreeMap<String, f32>,
+    /// Counted over the letter stream, across word boundaries; older
+    /// profiles without quadgrams still load
+    #[serde(default)]
+    pub quadgrams: BTreeMap<String, f32>,
+}
+
+/// Error loading or saving a profile
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Unknown(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::Io(e) => write!(f, "{}", e),
+            ProfileError::Json(e) => write!(f, "invalid profile: {}", e),
+            ProfileError::Unknown(name) => write!(
+                f,
+                "unknown language '{}'. Use a profile file or one of: {}",
+                name,
+                LanguageProfile::bundled_names().join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl From<io::Error> for ProfileError {
+    fn from(e: io::Error) -> Self {
+        ProfileError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ProfileError {
+    fn from(e: serde_json::Error) -> Self {
+        ProfileError::Json(e)
+    }
+}
+
+/// The bundled English profile, shared by the default scorers.
+pub fn english() -> Arc<LanguageProfile> {
+    static ENGLISH: OnceLock<Arc<LanguageProfile>> = OnceLock::new();
+    ENGLISH
+        .get_or_init(|| Arc::new(LanguageProfile::bundled("en").expect("bundled English profile")))
+        .clone()
+}
+
+// Percentages of the most common n-grams, at most `keep` of them
+fn percentages(counts: HashMap<String, u32>, keep: usize) -> BTreeMap<String, f32> {
+    let total: u32 = counts.values().sum();
+    let mut sorted: Vec<(String, u32)> = counts.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted
+        .into_iter()
+        .take(keep)
+        .map(|(ngram, count)| (ngram, count as f32 / total as f32 * 100.0))
+        .collect()
+}
+
+// The keys of a frequency table, most frequent first
+fn ranked<K: Clone + Ord>(table: &BTreeMap<K, f32>) -> Vec<K> {
+    let mut entries: Vec<(&K, &f32)> = table.iter().collect();
+    entries.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    entries.into_iter().map(|(key, _)| key.clone()).collect()
+}
+
+impl LanguageProfile {
+    /*
+    Train a profile from a sample text:
+
+    Letters are lowercased. Bigrams and trigrams are counted inside words
+    only, quadgrams over the letters with the word breaks removed, which is
+    how the substitution solver reads a message. All 26 letters are kept,
+    the n-gram tables are cut to the most common entries.
+    */
+    pub fn train(name: &str, corpus: &str) -> Self {
+        let mut letters: HashMap<String, u32> = HashMap::new();
+        let mut bigrams: HashMap<String, u32> = HashMap::new();
+        let mut trigrams: HashMap<String, u32> = HashMap::new();
+
+        for c in b'a'..=b'z' {
+            letters.insert((c as char).to_string(), 0);
+        }
+
+        let mut quadgrams: HashMap<String, u32> = HashMap::new();
+
+        let lowercase = corpus.to_ascii_lowercase();
+        let stream: Vec<char> = lowercase
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .collect();
+        for window in stream.windows(4) {
+            *quadgrams.entry(window.iter().collect()).or_insert(0) += 1;
+        }
+
+        for word in lowercase.split(|c: char| !c.is_ascii_alphabetic()) {
+            let chars: Vec<char> = word.chars().collect();
+            for c in &chars {
+                *letters.entry(c.to_string()).or_insert(0) += 1;
+            }
+            for window in chars.windows(2) {
+                *bigrams.entry(window.iter().collect()).or_insert(0) += 1;
+            }
+            for window in chars.windows(3) {
+                *trigrams.entry(window.iter().collect()).or_insert(0) += 1;
+            }
+        }
+
+        let unigrams = if letters.values().all(|count| *count == 0) {
+            BTreeMap::new()
+        } else {
+            percentages(letters, 26)
+                .into_iter()
+                .filter_map(|(letter, freq)| letter.chars().next().map(|c| (c, freq)))
+                .collect()
+        };
+
+        LanguageProfile {
+            name: name.to_string(),
+            unigrams,
+            bigrams: percentages(bigrams, MAX_NGRAMS),
+            trigrams: percentages(trigrams, MAX_NGRAMS),
+            quadgrams: percentages(quadgrams, MAX_QUADGRAMS),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ProfileError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("profile serializes to JSON")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProfileError> {
+        fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+
+    /// Names of the profiles shipped with the crate
+    pub fn bundled_names() -> Vec<&'static str> {
+        BUNDLED.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn bundled(name: &str) -> Option<Self> {
+        BUNDLED
+            .iter()
+            .find(|(bundled, _)| *bundled == name)
+            .map(|(_, json)| Self::from_json(json).expect("bundled profile is valid"))
+    }
+
+    /// A bundled profile by name, otherwise a profile file by path
+    pub fn resolve(name_or_path: &str) -> Result<Self, ProfileError> {
+        if let Some(profile) = Self::bundled(name_or_path) {
+            return Ok(profile);
+        }
+        if Path::new(name_or_path).is_file() {
+            return Self::load(name_or_path);
+        }
+        Err(ProfileError::Unknown(name_or_path.to_string()))
+    }
+
+    /// Frequencies of the `n` most common letters, the reference used by
+    /// the frequency scorers
+    pub fn reference_frequencies(&self, n: usize) -> HashMap<char, f32> {
+        ranked(&self.unigrams)
+            .into_iter()
+            .take(n)
+            .map(|letter| (letter, self.unigrams[&letter]))
+            .collect()
+    }
+
+    /// Letters from most to least common
+    pub fn letters_by_frequency(&self) -> Vec<char> {
+        ranked(&self.unigrams)
+    }
+
+    /// The `n` most common bigrams
+    pub fn top_bigrams(&self, n: usize) -> Vec<String> {
+        ranked(&self.bigrams).into_iter().take(n).collect()
+    }
+
+    /// The `n` most common trigrams
+    pub fn top_trigrams(&self, n: usize) -> Vec<String> {
+        ranked(&self.trigrams).into_iter().take(n).collect()
+    }
+
+    /// Expected index of coincidence of text in this language
+    pub fn index_of_coincidence(&self) -> f32 {
+        self.unigrams.values().map(|f| (f / 100.0).powi(2)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_train_counts_letters_and_ngrams() {
+        let profile = LanguageProfile::train("toy", "the the THE. then!");
+        assert_eq!(profile.name, "toy");
+        assert_eq!(profile.unigrams.len(), 26);
+        assert_eq!(profile.letters_by_frequency()[..3], ['e', 'h', 't']);
+        assert_eq!(profile.top_bigrams(2), vec!["he", "th"]);
+        assert_eq!(profile.top_trigrams(1), vec!["the"]);
+        // Quadgrams run across words: "thethethethen"
+        assert!((profile.quadgrams["heth"] - 30.0).abs() < 1e-4);
+        assert!(profile.quadgrams.contains_key("then"));
+        // "en" crosses no word boundary, "et" would
+        assert!(profile.bigrams.contains_key("en"));
+        assert!(!profile.bigrams.contains_key("et"));
+        let total: f32 = profile.unigrams.values().sum();
+        assert!((total - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_train_empty_corpus() {
+        let profile = LanguageProfile::train("empty", "");
+        assert!(profile.unigrams.is_empty());
+        assert!(profile.bigrams.is_empty());
+        assert_eq!(profile.index_of_coincidence(), 0.0);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let profile = LanguageProfile::train("toy", "a quick brown fox");
+        let loaded = LanguageProfile::from_json(&profile.to_json()).unwrap();
+        assert_eq!(loaded, profile);
+    }
+
+    #[test]
+    fn test_profile_without_quadgrams_loads() {
+        let json = r#"{"name":"old","unigrams":{"e":100.0},"bigrams":{},"trigrams":{}}"#;
+        let profile = LanguageProfile::from_json(json).unwrap();
+        assert!(profile.quadgrams.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join("decoder_ring_profile_test.json");
+        let profile = LanguageProfile::train("toy", "save me and load me");
+        profile.save(&path).unwrap();
+        assert_eq!(
+            LanguageProfile::resolve(path.to_str().unwrap()).unwrap(),
+            profile
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_bundled_profiles() {
+        for name in LanguageProfile::bundled_names() {
+            let profile = LanguageProfile::bundled(name).unwrap();
+            assert_eq!(profile.name, name);
+            assert_eq!(profile.unigrams.len(), 26);
+            assert!(profile.index_of_coincidence() > 0.06);
+        }
+        assert_eq!(english().letters_by_frequency()[0], 'e');
+        let finnish = LanguageProfile::bundled("fi")
+            .unwrap()
+            .letters_by_frequency();
+        assert!(finnish[..2].contains(&'a') && finnish[..2].contains(&'i'));
+    }
+
+    #[test]
+    fn test_resolve_unknown_language() {
+        let err = LanguageProfile::resolve("klingon").unwrap_err();
+        assert!(matches!(err, ProfileError::Unknown(_)));
+        assert!(err.to_string().contains("en, de, fi, pt"));
+    }
+}
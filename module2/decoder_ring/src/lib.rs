@@ -1,34 +1,61 @@
 use std::collections::HashMap;
 
-fn gen_counts() -> HashMap<char, f32> {
-    // Reference letter frequencies in English
-    let mut eng_freq: HashMap<char, f32> = HashMap::new();
-
-    // Accounts for 80% of all letters in English
-    eng_freq.insert('e', 12.7);
-    eng_freq.insert('t', 9.1);
-    eng_freq.insert('a', 8.2);
-    eng_freq.insert('o', 7.5);
-    eng_freq.insert('i', 7.0);
-    eng_freq.insert('n', 6.7);
-    eng_freq.insert('s', 6.3);
-    eng_freq.insert('h', 6.1);
-    eng_freq.insert('r', 6.0);
-    eng_freq.insert('d', 4.3);
-
-    eng_freq
+pub mod analysis;
+pub mod batch;
+pub mod cipher;
+pub mod eval;
+pub mod language;
+pub mod scorer;
+pub mod stream;
+pub mod substitution;
+pub mod vigenere;
+
+pub use analysis::{analyze, analyze_with, AnalysisReport, CipherFamily};
+pub use batch::{crack_batch, load_batch, write_results, BatchError, BatchMessage, BatchResult};
+pub use cipher::{
+    parse_cipher, Affine, Atbash, Beaufort, Caesar, Cipher, CipherError, RailFence, Rot13, Vigenere,
+};
+pub use eval::{generate_cases, run_eval, EvalCase, EvalReport};
+pub use language::{LanguageProfile, ProfileError};
+pub use scorer::{
+    BasicScorer, BigramScorer, Blend, ChiSquaredScorer, Scorer, Strategy, TrigramScorer,
+    UnknownStrategy, WeightedScorer,
+};
+pub use stream::{
+    crack_stream, decrypt_stream, LetterHistogram, ShiftScore, StreamError, StreamGuess,
+};
+pub use substitution::{
+    solve_substitution, solve_substitution_with, SolverOptions, SubstitutionSolution,
+};
+pub use vigenere::{guess_vigenere, guess_vigenere_with, VigenereGuess};
+
+// Number of most common letters used as the frequency reference
+const REFERENCE_LETTERS: usize = 10;
+
+// Number of most common bigrams and trigrams counted by the n-gram scorers
+const COMMON_NGRAMS: usize = 14;
+
+fn gen_counts(profile: &LanguageProfile) -> HashMap<char, f32> {
+    // Reference letter frequencies of the ten most common letters,
+    // about 75% of all letters in English
+    profile.reference_frequencies(REFERENCE_LETTERS)
 }
 
 // Get weight for a letter - rare letters are more distinctive
-fn get_letter_weight(letter: char) -> f32 {
-    match letter.to_ascii_lowercase() {
-        'e' | 't' | 'a' | 'o' => 0.8,  // Common, lower weight
-        'q' | 'x' | 'z' => 3.0,         // Rare, higher weight
-        _ => 1.5,                        // Average
+fn get_letter_weight(letter: char, profile: &LanguageProfile) -> f32 {
+    let ranked = profile.letters_by_frequency();
+    let letter = letter.to_ascii_lowercase();
+    match ranked.iter().position(|c| *c == letter) {
+        Some(rank) if rank < 4 => 0.8,                 // Common, lower weight
+        Some(rank) if rank + 3 >= ranked.len() => 3.0, // Rare, higher weight
+        _ => 1.5,                                      // Average
     }
 }
 
-fn stats_analysis(text: &str) -> Vec<(char, u32, f32, Option<f32>, f32)> {
+fn stats_analysis(
+    text: &str,
+    profile: &LanguageProfile,
+) -> Vec<(char, u32, f32, Option<f32>, f32)> {
     let mut counts: HashMap<char, u32> = HashMap::new();
 
     for c in text.chars() {
@@ -36,13 +63,21 @@ fn stats_analysis(text: &str) -> Vec<(char, u32, f32, Option<f32>, f32)> {
     }
 
     let total: u32 = counts.values().sum();
+    stats_from_counts(&counts, total, profile)
+}
 
-    let eng_freq_map = gen_counts();
-    let eng_freq_map: HashMap<char, f32> = eng_freq_map.iter().map(|(k, v)| (*k, *v)).collect();
+// Statistics from character counts; `total` also counts the characters
+// left out of `counts`, so frequencies are relative to the whole text
+fn stats_from_counts(
+    counts: &HashMap<char, u32>,
+    total: u32,
+    profile: &LanguageProfile,
+) -> Vec<(char, u32, f32, Option<f32>, f32)> {
+    let eng_freq_map = gen_counts(profile);
 
     let mut results = Vec::new();
 
-    for (letter, count) in &counts {
+    for (letter, count) in counts {
         let freq = (*count as f32 / total as f32) * 100.0;
         let eng_freq = eng_freq_map.get(&letter.to_ascii_lowercase()).cloned();
 
@@ -54,13 +89,19 @@ fn stats_analysis(text: &str) -> Vec<(char, u32, f32, Option<f32>, f32)> {
 }
 
 pub fn print_stats_analysis(text: &str) {
-    let stats = stats_analysis(text);
+    print_stats_analysis_with(text, &language::english());
+}
+
+/// Print the letter statistics against the given language profile
+pub fn print_stats_analysis_with(text: &str, profile: &LanguageProfile) {
+    let stats = stats_analysis(text, profile);
     for (letter, count, freq, eng_freq, eng_freq_diff) in stats {
         println!(
-            "{}: {} ({}%), English Freq: {} ({}%)",
+            "{}: {} ({}%), {} Freq: {} ({}%)",
             letter,
             count,
             freq,
+            profile.name,
             eng_freq.unwrap_or(0.0),
             eng_freq_diff
         );
@@ -95,10 +136,14 @@ fn score_basic(stats: &[(char, u32, f32, Option<f32>, f32)]) -> f32 {
 }
 
 // Chi-squared statistical test scoring
-fn score_chi_squared(text: &str, stats: &[(char, u32, f32, Option<f32>, f32)]) -> f32 {
-    let total_chars = text.chars().filter(|c| c.is_ascii_alphabetic()).count() as f32;
+fn score_chi_squared(stats: &[(char, u32, f32, Option<f32>, f32)]) -> f32 {
+    let total_chars: f32 = stats
+        .iter()
+        .filter(|(letter, ..)| letter.is_ascii_alphabetic())
+        .map(|(_, count, ..)| *count as f32)
+        .sum();
     let mut chi_squared = 0.0;
-    
+
     for (_, count, _, eng_freq, _) in stats {
         if let Some(eng) = eng_freq {
             let expected = (eng / 100.0) * total_chars;
@@ -111,40 +156,103 @@ fn score_chi_squared(text: &str, stats: &[(char, u32, f32, Option<f32>, f32)]) -
     -chi_squared  // Negative because lower is better
 }
 
-// Bigram analysis scoring
-fn score_bigrams(text: &str) -> f32 {
-    // Common English bigrams
-    let common_bigrams = ["th", "he", "in", "er", "an", "ed", "nd", "to", "en", "ti", "es", "or", "te", "ar"];
+// Percentage of letter n-grams that are among the common ones
+fn score_ngrams(text: &str, common: &[String], n: usize) -> f32 {
     let lowercase = text.to_lowercase();
     let chars: Vec<char> = lowercase.chars().filter(|c| c.is_ascii_alphabetic()).collect();
-    
-    let mut bigram_count = 0;
-    for window in chars.windows(2) {
-        let bigram = format!("{}{}", window[0], window[1]);
-        if common_bigrams.contains(&bigram.as_str()) {
-            bigram_count += 1;
-        }
+
+    if chars.len() < n {
+        return 0.0;
     }
-    
-    if chars.len() > 1 {
-        (bigram_count as f32 / (chars.len() - 1) as f32) * 100.0
-    } else {
-        0.0
+
+    let mut ngram_count = 0;
+    for window in chars.windows(n) {
+        let ngram: String = window.iter().collect();
+        if common.contains(&ngram) {
+            ngram_count += 1;
+        }
     }
+
+    (ngram_count as f32 / (chars.len() - n + 1) as f32) * 100.0
+}
+
+// Bigram analysis scoring
+fn score_bigrams(text: &str, profile: &LanguageProfile) -> f32 {
+    score_ngrams(text, &profile.top_bigrams(COMMON_NGRAMS), 2)
+}
+
+// Trigram analysis scoring
+fn score_trigrams(text: &str, profile: &LanguageProfile) -> f32 {
+    score_ngrams(text, &profile.top_trigrams(COMMON_NGRAMS), 3)
 }
 
 // Weighted letter importance scoring
-fn score_weighted(stats: &[(char, u32, f32, Option<f32>, f32)]) -> f32 {
+fn score_weighted(stats: &[(char, u32, f32, Option<f32>, f32)], profile: &LanguageProfile) -> f32 {
     let mut score = 0.0;
     for (letter, _, freq, eng_freq, eng_freq_diff) in stats {
         if let Some(eng_freq) = eng_freq {
-            let weight = get_letter_weight(*letter);
+            let weight = get_letter_weight(*letter, profile);
             score += weight * (1.0 - eng_freq_diff / eng_freq) * freq;
         }
     }
     score
 }
 
+/// One decryption attempt of a shift search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub shift: u8,
+    pub decrypted: String,
+    pub score: f32,
+}
+
+/// All candidate shifts of a search, ranked best first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrackResult {
+    /// The number of shifts tried
+    pub depth: u8,
+    /// Candidates sorted by score, highest first; ties keep shift order
+    pub candidates: Vec<Candidate>,
+}
+
+impl CrackResult {
+    /// The most likely candidate, None if no shift was tried
+    pub fn best(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+
+    /// The n most likely candidates
+    pub fn top(&self, n: usize) -> &[Candidate] {
+        &self.candidates[..n.min(self.candidates.len())]
+    }
+}
+
+/*
+Crack Shift:
+
+Decrypts the message with every shift in 0..depth and ranks the results
+with the given scorer. Any Scorer works, including a Blend or a
+user-defined one.
+*/
+pub fn crack_shift(text: &str, depth: u8, scorer: &dyn Scorer) -> CrackResult {
+    let mut candidates: Vec<Candidate> = (0..depth)
+        .map(|shift| {
+            let decrypted = decrypt(text, shift);
+            let score = scorer.score(&decrypted);
+            Candidate {
+                shift,
+                decrypted,
+                score,
+            }
+        })
+        .collect();
+
+    // Stable sort, so the lowest shift wins a tie like before
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    CrackResult { depth, candidates }
+}
+
 /*
 Guess Shift with optimization strategy:
 
@@ -154,11 +262,13 @@ Accepts:
  * text: the message to decrypt
  * depth: the number of shifts to try
  * optimization: "basic", "chi_squared", "bigram", or "weighted"
+   (unknown names fall back to "basic", parse a Strategy to catch typos)
 Returns:
    * depth: the number of shifts to tried
    * shift: the most likely shift
    * decrypted: the decrypted message
    * score: the score of the best shift
+Nothing is printed; the CLI shows the per-shift scores with --verbose.
 */
 
 pub fn guess_shift(text: &str, depth: u8) -> (u8, u8, String, f32) {
@@ -166,31 +276,13 @@ pub fn guess_shift(text: &str, depth: u8) -> (u8, u8, String, f32) {
 }
 
 pub fn guess_shift_optimized(text: &str, depth: u8, optimization: &str) -> (u8, u8, String, f32) {
-    let mut max_score = f32::NEG_INFINITY;
-    let mut best_shift = 0;
-    let mut decrypted = String::new();
-
-    for shift in 0..depth {
-        let decrypted_text = decrypt(text, shift);
-        let stats = stats_analysis(&decrypted_text);
-
-        // Select scoring method based on optimization strategy
-        let score = match optimization {
-            "chi_squared" => score_chi_squared(&decrypted_text, &stats),
-            "bigram" => score_bigrams(&decrypted_text),
-            "weighted" => score_weighted(&stats),
-            _ => score_basic(&stats),  // default to basic
-        };
-
-        println!("Shift: {}, Score: {}", shift, score);
-        if score > max_score {
-            max_score = score;
-            best_shift = shift;
-            decrypted = decrypted_text;
-        }
-    }
+    let strategy = optimization.parse::<Strategy>().unwrap_or_default();
+    let result = crack_shift(text, depth, strategy.scorer().as_ref());
 
-    (depth, best_shift, decrypted, max_score)
+    match result.best() {
+        Some(best) => (depth, best.shift, best.decrypted.clone(), best.score),
+        None => (depth, 0, String::new(), f32::NEG_INFINITY),
+    }
 }
 
 #[cfg(test)]
@@ -312,6 +404,67 @@ mod tests {
         assert_eq!(shift, 16, "Weighted optimization should find correct shift");
     }
 
+    #[test]
+    fn test_crack_shift_ranks_all_candidates() {
+        let encrypted = "Ypp dy dro lexuob. Ofobi zobcyx pyb drowcovfoc";
+        let result = crack_shift(encrypted, 26, &BigramScorer::default());
+        assert_eq!(result.candidates.len(), 26);
+        assert_eq!(result.best().unwrap().shift, 16);
+        assert_eq!(
+            result.best().unwrap().decrypted,
+            "Off to the bunker. Every person for themselves"
+        );
+        assert!(result
+            .candidates
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+        assert_eq!(result.top(3).len(), 3);
+        assert_eq!(result.top(100).len(), 26);
+    }
+
+    #[test]
+    fn test_crack_shift_with_custom_scorer() {
+        // Counting the letter 'e' is enough for this message
+        struct LetterE;
+        impl Scorer for LetterE {
+            fn score(&self, text: &str) -> f32 {
+                text.matches('e').count() as f32
+            }
+        }
+
+        let encrypted = "Ypp dy dro lexuob. Ofobi zobcyx pyb drowcovfoc";
+        let result = crack_shift(encrypted, 26, &LetterE);
+        assert_eq!(result.best().unwrap().shift, 16);
+
+        let blend = Blend::new()
+            .with(LetterE, 1.0)
+            .with(BigramScorer::default(), 0.5);
+        assert_eq!(crack_shift(encrypted, 26, &blend).best().unwrap().shift, 16);
+    }
+
+    #[test]
+    fn test_crack_shift_zero_depth() {
+        let result = crack_shift("Ypp", 0, &BasicScorer::default());
+        assert!(result.best().is_none());
+        assert!(result.top(5).is_empty());
+    }
+
+    #[test]
+    fn test_crack_shift_in_other_language() {
+        // "Kokouksen jälkeen ihmiset kävelivät kotiin pienissä ryhmissä" shifted by 7
+        let encrypted = "Rvrvbrzlu qäsrllu potpzla räclspcäa rvappu wplupzzä yfotpzzä";
+        let finnish = std::sync::Arc::new(LanguageProfile::bundled("fi").unwrap());
+        // The chi-squared scorer only compares the letters present, too few
+        // in a message this short
+        for strategy in Strategy::ALL
+            .into_iter()
+            .filter(|s| *s != Strategy::ChiSquared)
+        {
+            let result = crack_shift(encrypted, 26, strategy.scorer_for(finnish.clone()).as_ref());
+            assert_eq!(result.best().unwrap().shift, 19, "strategy {}", strategy);
+        }
+    }
+
     #[test]
     fn test_case_preservation() {
         // Verify case is preserved during decryption
@@ -16,18 +16,73 @@ cargo run -- --message "Ypp dy dro lexuob" --guess --optimize chi_squared
 cargo run -- --message "Ypp dy dro lexuob" --guess --optimize bigram
 cargo run -- --message "Ypp dy dro lexuob" --guess --optimize weighted
 
-Optimization options: basic (default), chi_squared, bigram, weighted
+Optimization options: basic (default), chi_squared, bigram, trigram, weighted
+
+Other languages (bundled: en, de, fi, pt, or a profile file):
+cargo run -- --message "Rvrvbrzlu qäsrllu potpzla räclspcäa rvappu" --guess --optimize bigram --lang fi
+cargo run -- --message "Rvrvbrzlu qäsrllu potpzla räclspcäa rvappu" --guess --lang my_profile.json
+
+Analyze a message of unknown origin (statistics, cipher family, language):
+cargo run -- --file unknown.txt --analyze
+cargo run -- --file unknown.txt --analyze --json
+
+Train a language profile from a sample corpus:
+cargo run -- train --corpus corpora/fi.txt --name fi --output profiles/fi.json
+
+Show the three most likely shifts:
+cargo run -- --message "Ypp dy dro lexuob" --guess --optimize bigram --top 3
+
+Vigenère mode (estimates the key length, then recovers the key):
+cargo run -- --file vigenere.txt --vigenere
+cargo run -- --file vigenere.txt --vigenere --max-key-len 12
+
+Show the score of every shift:
+cargo run -- --message "Ypp dy dro lexuob" --guess --verbose
+
+Large files in one pass with constant memory (basic, chi_squared, weighted):
+cargo run -- --file huge.txt --stream --optimize chi_squared --output decrypted.txt
+
+Crack a directory of messages, or a JSONL file of {"id", "message"} lines:
+cargo run -- batch --input messages/ --output results.jsonl
+cargo run -- batch --input messages.jsonl --optimize chi_squared
+
+Measure how accurate each strategy is by message length:
+cargo run --release -- eval
+cargo run --release -- eval --corpus corpora/de.txt --lang de --samples 20 --json
+
+Encrypt and decrypt with a classical cipher (caesar:3, rot13, atbash,
+affine:5,8, vigenere:lemon, beaufort:fortify, railfence:3):
+cargo run -- encrypt --key vigenere:lemon --message "Attack at dawn"
+cargo run -- decrypt --key vigenere:lemon --message "Lxfopv ef rnhr"
+
+Substitution mode (any 26-letter key, solved by hill climbing):
+cargo run -- --file substitution.txt --substitution
+cargo run -- --file substitution.txt --substitution --restarts 32 --seed 7
 */
 
-use clap::Parser;
-use decoder_ring::print_stats_analysis;
+use clap::{Parser, Subcommand};
+use decoder_ring::{
+    analyze_with, crack_batch, crack_shift, crack_stream, decrypt_stream, generate_cases,
+    load_batch, parse_cipher, print_stats_analysis_with, run_eval, solve_substitution_with,
+    write_results, LanguageProfile, SolverOptions, Strategy, StreamError,
+};
+use decoder_ring::eval::{DEFAULT_LENGTHS, DEFAULT_THRESHOLD};
 use std::fs;
+use std::io::{self, BufReader, BufWriter};
+use std::sync::Arc;
 use std::time::Instant;
 
+// Default corpus of the eval subcommand, English the bundled profile was
+// not trained on
+const ENGLISH_CORPUS: &str = include_str!("../corpora/eval/en.txt");
+
 /// CLI tool to reverse engineer a Caesar cipher with optimization options
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The message to decrypt (via command-line)
     #[arg(short, long)]
     message: Option<String>,
@@ -44,83 +99,547 @@ struct Args {
     #[arg(short, long)]
     guess: bool,
 
-    /// Optimization strategy: basic, chi_squared, bigram, or weighted
+    /// Optimization strategy: basic, chi_squared, bigram, trigram, or weighted
     #[arg(short, long, default_value = "basic")]
-    optimize: String,
+    optimize: Strategy,
+
+    /// Language of the plaintext: a bundled profile (en, de, fi, pt) or a profile file
+    #[arg(short, long, default_value = "en", global = true)]
+    lang: String,
+
+    /// Report statistics and guess the cipher family and language
+    #[arg(short, long)]
+    analyze: bool,
+
+    /// Print the --analyze report as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Number of ranked guesses to show
+    #[arg(short, long, default_value_t = 1)]
+    top: usize,
+
+    /// Crack a Vigenère (polyalphabetic) cipher instead of a single shift
+    #[arg(long)]
+    vigenere: bool,
+
+    /// Longest key length tried in Vigenère mode
+    #[arg(long, default_value_t = 20)]
+    max_key_len: usize,
+
+    /// Crack a general substitution cipher (any 26-letter key)
+    #[arg(long)]
+    substitution: bool,
+
+    /// Hill-climbing restarts in substitution mode
+    #[arg(long, default_value_t = 16)]
+    restarts: usize,
+
+    /// Seed for reproducible substitution results
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Crack --file in one pass with constant memory (basic, chi_squared
+    /// or weighted optimization only)
+    #[arg(long)]
+    stream: bool,
+
+    /// Where --stream writes the decrypted text (default: stdout)
+    #[arg(long)]
+    output: Option<String>,
+
+    /// Show more detail, such as the score of every shift
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
 }
 
-fn main() {
-    let args = Args::parse();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Build a language profile from a sample corpus
+    Train {
+        /// Path to a plain text file in the target language
+        #[arg(short, long)]
+        corpus: String,
+
+        /// Name stored in the profile
+        #[arg(short, long)]
+        name: String,
+
+        /// Where to write the profile (JSON)
+        #[arg(short, long)]
+        output: String,
+    },
+
+    /// Encrypt a message with a classical cipher
+    Encrypt {
+        /// Cipher and key: caesar:3, rot13, atbash, affine:5,8, vigenere:lemon,
+        /// beaufort:fortify or railfence:3
+        #[arg(short, long)]
+        key: String,
+
+        /// The message to encrypt
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Path to file containing the message
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+
+    /// Crack every message of a directory or JSONL file in parallel
+    Batch {
+        /// A directory with one message per file, or a JSONL file of
+        /// {"id": ..., "message": ...} lines
+        #[arg(short, long)]
+        input: String,
+
+        /// Where to write the JSONL results (default: stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Optimization strategy, as for --guess
+        #[arg(long, default_value = "basic")]
+        optimize: Strategy,
+    },
+
+    /// Measure the accuracy and speed of every strategy by message length
+    Eval {
+        /// Plain text to cut test messages from (default: held-out English,
+        /// corpora/eval/en.txt)
+        #[arg(short, long)]
+        corpus: Option<String>,
+
+        /// Message lengths to test, in characters
+        #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_LENGTHS)]
+        lengths: Vec<usize>,
+
+        /// Messages per length; each is encrypted at all 26 shifts
+        #[arg(long, default_value_t = 10)]
+        samples: usize,
+
+        /// Seed for picking the messages
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+
+        /// Accuracy needed to call a strategy reliable at a length
+        #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+        threshold: f32,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Decrypt a message with a known cipher and key
+    Decrypt {
+        /// Cipher and key, as for encrypt
+        #[arg(short, long)]
+        key: String,
+
+        /// The message to decrypt
+        #[arg(short, long)]
+        message: Option<String>,
 
+        /// Path to file containing the message
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+}
+
+fn train(corpus: &str, name: &str, output: &str) {
+    let text = match fs::read_to_string(corpus) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Error reading corpus '{}': {}", corpus, e);
+            return;
+        }
+    };
+
+    let profile = LanguageProfile::train(name, &text);
+    if let Err(e) = profile.save(output) {
+        eprintln!("Error writing profile '{}': {}", output, e);
+        return;
+    }
+
+    println!(
+        "Trained '{}' from {} letters, saved to {}",
+        name,
+        text.chars().filter(|c| c.is_ascii_alphabetic()).count(),
+        output
+    );
+    println!(
+        "Most common letters: {}",
+        profile
+            .letters_by_frequency()
+            .iter()
+            .take(10)
+            .collect::<String>()
+    );
+}
+
+fn read_message(message: &Option<String>, file: &Option<String>) -> Option<String> {
     // Get the message from either command-line or file input
     // Both sources are optional, but exactly one must be provided
-    let message = match (&args.message, &args.file) {
+    match (message, file) {
         // If message is provided via --message flag, use it directly
-        (Some(msg), None) => msg.clone(),
+        (Some(msg), None) => Some(msg.clone()),
 
         // If file path is provided via --file flag, read the file contents
-        (None, Some(path)) => {
-            match fs::read_to_string(path) {
-                Ok(content) => content,
-                Err(e) => {
-                    eprintln!("Error reading file '{}': {}", path, e);
-                    return;
-                }
+        (None, Some(path)) => match fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(e) => {
+                eprintln!("Error reading file '{}': {}", path, e);
+                None
             }
-        }
+        },
 
         // Error: both message and file provided
         (Some(_), Some(_)) => {
             eprintln!("Error: Provide either --message or --file, not both");
-            return;
+            None
         }
 
         // Error: neither message nor file provided
         (None, None) => {
             eprintln!("Error: Either --message or --file is required");
+            None
+        }
+    }
+}
+
+// Encrypt or decrypt a message with the cipher of a key spec
+fn run_cipher(spec: &str, message: &Option<String>, file: &Option<String>, encrypt: bool) {
+    let cipher = match parse_cipher(spec) {
+        Ok(cipher) => cipher,
+        Err(e) => {
+            eprintln!("Error: {}", e);
             return;
         }
     };
+    let Some(message) = read_message(message, file) else {
+        return;
+    };
+
+    if encrypt {
+        println!("{}", cipher.encrypt(&message));
+    } else {
+        println!("{}", cipher.decrypt(&message));
+    }
+}
 
-    // Validate optimization choice
-    let valid_optimizations = ["basic", "chi_squared", "bigram", "weighted"];
-    if !valid_optimizations.contains(&args.optimize.as_str()) {
-        eprintln!(
-            "Error: Invalid optimization '{}'. Choose from: {}",
+fn batch(input: &str, output: Option<&str>, strategy: Strategy, profile: Arc<LanguageProfile>) {
+    let messages = match load_batch(input) {
+        Ok(messages) => messages,
+        Err(e) => {
+            eprintln!("Error reading batch '{}': {}", input, e);
+            return;
+        }
+    };
+
+    let start_time = Instant::now();
+    let results = crack_batch(&messages, strategy, profile);
+    let elapsed = start_time.elapsed();
+
+    let written = match output {
+        Some(path) => {
+            fs::File::create(path).and_then(|file| write_results(&results, BufWriter::new(file)))
+        }
+        None => write_results(&results, io::stdout().lock()),
+    };
+    if let Err(e) = written {
+        eprintln!("Error writing results: {}", e);
+        return;
+    }
+    eprintln!(
+        "Cracked {} messages with {} optimization in {:.4} seconds",
+        results.len(),
+        strategy,
+        elapsed.as_secs_f32()
+    );
+}
+
+fn stream(
+    path: &str,
+    output: Option<&str>,
+    strategy: Strategy,
+    profile: &LanguageProfile,
+    verbose: u8,
+) {
+    let open = || fs::File::open(path).map(BufReader::new);
+
+    let start_time = Instant::now();
+    let guess = match open()
+        .map_err(StreamError::from)
+        .and_then(|file| crack_stream(file, 26, strategy, profile))
+    {
+        Ok(guess) => guess,
+        Err(e) => {
+            eprintln!("Error streaming '{}': {}", path, e);
+            return;
+        }
+    };
+    let Some(best) = guess.best() else {
+        return;
+    };
+
+    // The summary goes to stderr when the decrypted text goes to stdout
+    let report = |line: String| {
+        if output.is_some() {
+            println!("{}", line);
+        } else {
+            eprintln!("{}", line);
+        }
+    };
+    if verbose > 0 {
+        let mut by_shift = guess.scores.clone();
+        by_shift.sort_by_key(|score| score.shift);
+        for score in by_shift {
+            report(format!("Shift: {}, Score: {}", score.shift, score.score));
+        }
+    }
+    report(format!(
+        "Best shift: {} ({} letters, {} bytes), score: {:.2}",
+        best.shift,
+        guess.histogram.letters(),
+        guess.histogram.bytes(),
+        best.score
+    ));
+
+    // Second pass writes the decrypted text
+    let written = match (open(), output) {
+        (Ok(file), Some(out)) => fs::File::create(out)
+            .and_then(|target| decrypt_stream(file, BufWriter::new(target), best.shift)),
+        (Ok(file), None) => decrypt_stream(file, io::stdout().lock(), best.shift),
+        (Err(e), _) => Err(e),
+    };
+    if let Err(e) = written {
+        eprintln!("Error writing decrypted text: {}", e);
+        return;
+    }
+    report(format!(
+        "Time elapsed: {:.4} seconds",
+        start_time.elapsed().as_secs_f32()
+    ));
+}
+
+fn main() {
+    let args = Args::parse();
+
+    match &args.command {
+        Some(Command::Train {
+            corpus,
+            name,
+            output,
+        }) => {
+            train(corpus, name, output);
+            return;
+        }
+        Some(Command::Encrypt { key, message, file }) => {
+            run_cipher(key, message, file, true);
+            return;
+        }
+        Some(Command::Decrypt { key, message, file }) => {
+            run_cipher(key, message, file, false);
+            return;
+        }
+        _ => {}
+    }
+
+    let profile = match LanguageProfile::resolve(&args.lang) {
+        Ok(profile) => Arc::new(profile),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    if let Some(Command::Eval {
+        corpus,
+        lengths,
+        samples,
+        seed,
+        threshold,
+        json,
+    }) = &args.command
+    {
+        let corpus = match corpus {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("Error reading corpus '{}': {}", path, e);
+                    return;
+                }
+            },
+            None => ENGLISH_CORPUS.to_string(),
+        };
+        let cases = generate_cases(&corpus, lengths, *samples, *seed);
+        let report = run_eval(&cases, &Strategy::ALL, profile, *threshold);
+        if *json {
+            println!("{}", report.to_json());
+        } else {
+            print!("{}", report);
+        }
+        return;
+    }
+
+    if let Some(Command::Batch {
+        input,
+        output,
+        optimize,
+    }) = &args.command
+    {
+        batch(input, output.as_deref(), *optimize, profile);
+        return;
+    }
+
+    // Crack a large file without loading it if --stream flag is set
+    if args.stream {
+        let Some(path) = &args.file else {
+            eprintln!("Error: --stream needs --file");
+            return;
+        };
+        stream(
+            path,
+            args.output.as_deref(),
             args.optimize,
-            valid_optimizations.join(", ")
+            &profile,
+            args.verbose,
         );
         return;
     }
 
+    let message = match read_message(&args.message, &args.file) {
+        Some(message) => message,
+        None => return,
+    };
+
     // Display statistical analysis if --stats flag is set
     if args.stats {
-        print_stats_analysis(&message);
+        print_stats_analysis_with(&message, &profile);
+    }
+
+    // Report on a message of unknown origin if --analyze flag is set
+    if args.analyze {
+        // The bundled languages, plus the --lang profile file if one was given
+        let mut profiles: Vec<Arc<LanguageProfile>> = LanguageProfile::bundled_names()
+            .into_iter()
+            .filter_map(LanguageProfile::bundled)
+            .map(Arc::new)
+            .collect();
+        if !profiles.iter().any(|p| p.name == profile.name) {
+            profiles.push(profile.clone());
+        }
+
+        let report = analyze_with(&message, &profiles);
+        if args.json {
+            println!("{}", report.to_json());
+        } else {
+            println!("\n=== Analysis ===");
+            print!("{}", report);
+        }
+        return;
+    }
+
+    // Crack a Vigenère cipher if --vigenere flag is set
+    if args.vigenere {
+        println!("\n=== Vigenère decryption ===");
+
+        let start_time = Instant::now();
+        let guess = decoder_ring::guess_vigenere_with(&message, args.max_key_len, &profile);
+        let elapsed = start_time.elapsed();
+
+        println!(
+            "\nKey: {} (length {}), score: {:.2}",
+            guess.key,
+            guess.key.len(),
+            guess.score
+        );
+        println!("Decrypted message: {}", guess.decrypted);
+        println!(
+            "Time elapsed: {:.4} seconds ({} milliseconds)",
+            elapsed.as_secs_f32(),
+            elapsed.as_millis()
+        );
+        return;
+    }
+
+    // Crack a general substitution cipher if --substitution flag is set
+    if args.substitution {
+        println!("\n=== Substitution decryption ===");
+
+        let options = SolverOptions {
+            restarts: args.restarts,
+            seed: args.seed,
+            ..SolverOptions::default()
+        };
+
+        let start_time = Instant::now();
+        let solution = solve_substitution_with(&message, &profile, &options);
+        let elapsed = start_time.elapsed();
+
+        println!(
+            "\nKey: abcdefghijklmnopqrstuvwxyz -> {}, score: {:.3}, confidence: {:.0}%",
+            solution.key,
+            solution.score,
+            solution.confidence * 100.0
+        );
+        println!("Decrypted message: {}", solution.plaintext);
+        println!(
+            "Time elapsed: {:.4} seconds ({} milliseconds)",
+            elapsed.as_secs_f32(),
+            elapsed.as_millis()
+        );
+        return;
     }
 
     // Attempt to guess the shift and decrypt if --guess flag is set
     if args.guess {
         println!("\n=== Decryption with {} optimization ===", args.optimize);
-        
+
         // Start timing
         let start_time = Instant::now();
 
-        // Run the optimized guess_shift
-        let (depth, best_shift, decrypted, max_score) =
-            decoder_ring::guess_shift_optimized(&message, 26, &args.optimize);
+        // Rank every shift with the selected scorer
+        let result = crack_shift(
+            &message,
+            26,
+            args.optimize.scorer_for(profile.clone()).as_ref(),
+        );
 
         // Stop timing
         let elapsed = start_time.elapsed();
 
-        println!(
-            "\nBest shift: {} (out of {}), score: {:.2}",
-            best_shift, depth, max_score
-        );
-        println!("Decrypted message: {}", decrypted);
+        if args.verbose > 0 {
+            let mut by_shift: Vec<_> = result.candidates.iter().collect();
+            by_shift.sort_by_key(|candidate| candidate.shift);
+            for candidate in by_shift {
+                println!("Shift: {}, Score: {}", candidate.shift, candidate.score);
+            }
+        }
+
+        if let Some(best) = result.best() {
+            println!(
+                "\nBest shift: {} (out of {}), score: {:.2}",
+                best.shift, result.depth, best.score
+            );
+            println!("Decrypted message: {}", best.decrypted);
+        }
+        if args.top > 1 {
+            println!("\nTop {} guesses:", args.top);
+            for (rank, candidate) in result.top(args.top).iter().enumerate() {
+                println!(
+                    "{:2}. shift {:2}, score {:8.2}: {}",
+                    rank + 1,
+                    candidate.shift,
+                    candidate.score,
+                    candidate.decrypted
+                );
+            }
+        }
         println!(
             "Time elapsed: {:.4} seconds ({} milliseconds)",
             elapsed.as_secs_f32(),
             elapsed.as_millis()
         );
     }
-}
\ No newline at end of file
+}
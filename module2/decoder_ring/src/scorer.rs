@@ -0,0 +1,367 @@
+/*
+Scoring strategies.
+
+A Scorer rates how much a candidate plaintext looks like the target
+language; higher is better. The built-in strategies wrap the scoring
+functions in lib.rs and compare against a LanguageProfile (English by
+default), Blend combines several scorers with weights, and any type
+implementing Scorer can be passed to crack_shift.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::language::{self, LanguageProfile};
+use super::{
+    score_basic, score_bigrams, score_chi_squared, score_trigrams, score_weighted, stats_analysis,
+};
+
+/// Rates a candidate plaintext, higher scores are more likely to be correct.
+pub trait Scorer {
+    fn score(&self, text: &str) -> f32;
+
+    /// Name used in reports
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+// Declares a built-in scorer holding the language profile it compares against
+macro_rules! profile_scorer {
+    ($(#[$doc:meta])* $scorer:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $scorer {
+            profile: Arc<LanguageProfile>,
+        }
+
+        impl $scorer {
+            pub fn new(profile: Arc<LanguageProfile>) -> Self {
+                Self { profile }
+            }
+
+            pub fn profile(&self) -> &LanguageProfile {
+                &self.profile
+            }
+        }
+
+        impl Default for $scorer {
+            /// Scores against the bundled English profile
+            fn default() -> Self {
+                Self::new(language::english())
+            }
+        }
+    };
+}
+
+profile_scorer!(
+    /// Frequency deviation from the language (the original method).
+    BasicScorer
+);
+profile_scorer!(
+    /// Negative chi-squared distance from the language letter frequencies.
+    ChiSquaredScorer
+);
+profile_scorer!(
+    /// Percentage of letter pairs that are common bigrams of the language.
+    BigramScorer
+);
+profile_scorer!(
+    /// Percentage of letter triples that are common trigrams of the language.
+    TrigramScorer
+);
+profile_scorer!(
+    /// Frequency deviation with rare letters weighted higher.
+    WeightedScorer
+);
+
+impl Scorer for BasicScorer {
+    fn score(&self, text: &str) -> f32 {
+        score_basic(&stats_analysis(text, &self.profile))
+    }
+
+    fn name(&self) -> &str {
+        "basic"
+    }
+}
+
+impl Scorer for ChiSquaredScorer {
+    fn score(&self, text: &str) -> f32 {
+        score_chi_squared(&stats_analysis(text, &self.profile))
+    }
+
+    fn name(&self) -> &str {
+        "chi_squared"
+    }
+}
+
+impl Scorer for BigramScorer {
+    fn score(&self, text: &str) -> f32 {
+        score_bigrams(text, &self.profile)
+    }
+
+    fn name(&self) -> &str {
+        "bigram"
+    }
+}
+
+impl Scorer for TrigramScorer {
+    fn score(&self, text: &str) -> f32 {
+        score_trigrams(text, &self.profile)
+    }
+
+    fn name(&self) -> &str {
+        "trigram"
+    }
+}
+
+impl Scorer for WeightedScorer {
+    fn score(&self, text: &str) -> f32 {
+        score_weighted(&stats_analysis(text, &self.profile), &self.profile)
+    }
+
+    fn name(&self) -> &str {
+        "weighted"
+    }
+}
+
+impl<S: Scorer + ?Sized> Scorer for Box<S> {
+    fn score(&self, text: &str) -> f32 {
+        (**self).score(text)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+impl<S: Scorer + ?Sized> Scorer for &S {
+    fn score(&self, text: &str) -> f32 {
+        (**self).score(text)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/*
+Weighted blend of scorers:
+
+The score is the weighted sum of the part scores. The built-in scorers use
+different scales (chi-squared is negative and grows with the text length,
+bigram is a percentage), so pick weights with that in mind.
+
+let scorer = Blend::new()
+    .with(BigramScorer::default(), 1.0)
+    .with(ChiSquaredScorer::default(), 0.1);
+*/
+#[derive(Default)]
+pub struct Blend {
+    parts: Vec<(Box<dyn Scorer>, f32)>,
+}
+
+impl Blend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a scorer with the given weight
+    pub fn with(mut self, scorer: impl Scorer + 'static, weight: f32) -> Self {
+        self.parts.push((Box::new(scorer), weight));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+impl Scorer for Blend {
+    fn score(&self, text: &str) -> f32 {
+        self.parts
+            .iter()
+            .map(|(scorer, weight)| weight * scorer.score(text))
+            .sum()
+    }
+
+    fn name(&self) -> &str {
+        "blend"
+    }
+}
+
+/// The built-in scoring strategies, parsed from their CLI names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    #[default]
+    Basic,
+    ChiSquared,
+    Bigram,
+    Trigram,
+    Weighted,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [
+        Strategy::Basic,
+        Strategy::ChiSquared,
+        Strategy::Bigram,
+        Strategy::Trigram,
+        Strategy::Weighted,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Basic => "basic",
+            Strategy::ChiSquared => "chi_squared",
+            Strategy::Bigram => "bigram",
+            Strategy::Trigram => "trigram",
+            Strategy::Weighted => "weighted",
+        }
+    }
+
+    /// The scorer for English text
+    pub fn scorer(&self) -> Box<dyn Scorer> {
+        self.scorer_for(language::english())
+    }
+
+    /// The scorer for text in the given language
+    pub fn scorer_for(&self, profile: Arc<LanguageProfile>) -> Box<dyn Scorer> {
+        match self {
+            Strategy::Basic => Box::new(BasicScorer::new(profile)),
+            Strategy::ChiSquared => Box::new(ChiSquaredScorer::new(profile)),
+            Strategy::Bigram => Box::new(BigramScorer::new(profile)),
+            Strategy::Trigram => Box::new(TrigramScorer::new(profile)),
+            Strategy::Weighted => Box::new(WeightedScorer::new(profile)),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error for an unknown strategy name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownStrategy(pub String);
+
+impl fmt::Display for UnknownStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = Strategy::ALL.iter().map(|s| s.name()).collect();
+        write!(
+            f,
+            "invalid optimization '{}'. Choose from: {}",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownStrategy {}
+
+impl FromStr for Strategy {
+    type Err = UnknownStrategy;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Strategy::ALL
+            .iter()
+            .find(|strategy| strategy.name() == s)
+            .copied()
+            .ok_or_else(|| UnknownStrategy(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct VowelScorer;
+
+    impl Scorer for VowelScorer {
+        fn score(&self, text: &str) -> f32 {
+            text.chars().filter(|c| "aeiouAEIOU".contains(*c)).count() as f32
+        }
+    }
+
+    #[test]
+    fn test_strategy_round_trip() {
+        for strategy in Strategy::ALL {
+            assert_eq!(strategy.name().parse::<Strategy>(), Ok(strategy));
+            assert_eq!(strategy.scorer().name(), strategy.name());
+        }
+    }
+
+    #[test]
+    fn test_strategy_rejects_typos() {
+        let err = "chi-squared".parse::<Strategy>().unwrap_err();
+        assert_eq!(err, UnknownStrategy("chi-squared".to_string()));
+        assert!(err.to_string().contains("chi_squared"));
+    }
+
+    #[test]
+    fn test_builtin_scorers_match_functions() {
+        let text = "Off to the bunker. Every person for themselves";
+        let english = language::english();
+        let stats = stats_analysis(text, &english);
+        // Summation order follows HashMap iteration, so compare loosely
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(close(
+            BasicScorer::default().score(text),
+            score_basic(&stats)
+        ));
+        assert!(close(
+            ChiSquaredScorer::default().score(text),
+            score_chi_squared(&stats)
+        ));
+        assert!(close(
+            BigramScorer::default().score(text),
+            score_bigrams(text, &english)
+        ));
+        assert!(close(
+            TrigramScorer::default().score(text),
+            score_trigrams(text, &english)
+        ));
+        assert!(close(
+            WeightedScorer::default().score(text),
+            score_weighted(&stats, &english)
+        ));
+    }
+
+    #[test]
+    fn test_blend_is_weighted_sum() {
+        let text = "the other one";
+        let blend = Blend::new()
+            .with(BigramScorer::default(), 2.0)
+            .with(VowelScorer, 0.5);
+        let expected = 2.0 * BigramScorer::default().score(text) + 0.5 * VowelScorer.score(text);
+        assert_eq!(blend.len(), 2);
+        assert!((blend.score(text) - expected).abs() < 1e-4);
+        assert_eq!(Blend::new().score(text), 0.0);
+    }
+
+    #[test]
+    fn test_scorer_uses_profile() {
+        let finnish = Arc::new(LanguageProfile::bundled("fi").unwrap());
+        let text = "Kalastajat vetivät verkkonsa kiville";
+        let in_finnish = BigramScorer::new(finnish.clone());
+        assert_eq!(in_finnish.profile().name, "fi");
+        assert!(in_finnish.score(text) > BigramScorer::default().score(text));
+        assert_eq!(
+            Strategy::Bigram.scorer_for(finnish).score(text),
+            in_finnish.score(text)
+        );
+    }
+
+    #[test]
+    fn test_custom_scorer_name_defaults() {
+        assert_eq!(VowelScorer.name(), "custom");
+        assert_eq!(Box::new(VowelScorer).score("aei"), 3.0);
+    }
+}
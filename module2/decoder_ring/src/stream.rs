@@ -0,0 +1,341 @@
+/*
+Streaming shift cracking.
+
+crack_shift decrypts and re-counts the whole text for every shift. The
+frequency strategies only look at letter counts, so for large inputs one
+pass builds a histogram and every shift is scored by rotating it: shifting
+the text by s moves the count of each letter s places along the alphabet.
+Memory stays constant however large the input is, and the decrypted text is
+written out chunk by chunk with decrypt_stream.
+
+Bigram and trigram scoring need the text itself and are not available here.
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use super::language::LanguageProfile;
+use super::scorer::Strategy;
+use super::{score_basic, score_chi_squared, score_weighted, stats_from_counts};
+
+// Bytes read per chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Letter counts of a text, upper and lower case kept apart like
+/// stats_analysis does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LetterHistogram {
+    lower: [u64; 26],
+    upper: [u64; 26],
+    /// All characters, letters included
+    chars: u64,
+    bytes: u64,
+}
+
+impl LetterHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a chunk of UTF-8 text. Chunks may split a character: only
+    /// ASCII letters are counted and every character is counted once, at
+    /// its first byte.
+    pub fn add(&mut self, chunk: &[u8]) {
+        for &b in chunk {
+            if b.is_ascii_lowercase() {
+                self.lower[(b - b'a') as usize] += 1;
+            } else if b.is_ascii_uppercase() {
+                self.upper[(b - b'A') as usize] += 1;
+            }
+            // Continuation bytes of a multi-byte character look like 10xxxxxx
+            if b & 0xC0 != 0x80 {
+                self.chars += 1;
+            }
+        }
+        self.bytes += chunk.len() as u64;
+    }
+
+    pub fn from_text(text: &str) -> Self {
+        let mut histogram = Self::new();
+        histogram.add(text.as_bytes());
+        histogram
+    }
+
+    /// Count everything a reader yields, one chunk at a time
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut histogram = Self::new();
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            histogram.add(&buffer[..read]);
+        }
+        Ok(histogram)
+    }
+
+    /// Number of ASCII letters counted
+    pub fn letters(&self) -> u64 {
+        self.lower.iter().chain(&self.upper).sum()
+    }
+
+    pub fn chars(&self) -> u64 {
+        self.chars
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The histogram of the text after `decrypt(text, shift)`
+    pub fn rotated(&self, shift: u8) -> Self {
+        let mut rotated = self.clone();
+        for i in 0..26 {
+            let j = (i + shift as usize) % 26;
+            rotated.lower[j] = self.lower[i];
+            rotated.upper[j] = self.upper[i];
+        }
+        rotated
+    }
+
+    /*
+    The stats_analysis table of the counted text.
+
+    The table holds u32 counts. Beyond that every count is divided by the
+    same factor, which keeps the frequencies and the ranking of the shifts.
+    */
+    fn stats(&self, profile: &LanguageProfile) -> Vec<(char, u32, f32, Option<f32>, f32)> {
+        let scale = self.chars.div_ceil(u32::MAX as u64).max(1);
+        let mut counts: HashMap<char, u32> = HashMap::new();
+        for i in 0..26u8 {
+            for (base, count) in [
+                (b'a', self.lower[i as usize]),
+                (b'A', self.upper[i as usize]),
+            ] {
+                if count > 0 {
+                    counts.insert((base + i) as char, (count / scale) as u32);
+                }
+            }
+        }
+        stats_from_counts(&counts, (self.chars / scale) as u32, profile)
+    }
+
+    /// Score the counted text like the strategy's scorer scores the text,
+    /// None for the strategies that need the text itself
+    pub fn score(&self, strategy: Strategy, profile: &LanguageProfile) -> Option<f32> {
+        let stats = self.stats(profile);
+        match strategy {
+            Strategy::Basic => Some(score_basic(&stats)),
+            Strategy::ChiSquared => Some(score_chi_squared(&stats)),
+            Strategy::Weighted => Some(score_weighted(&stats, profile)),
+            Strategy::Bigram | Strategy::Trigram => None,
+        }
+    }
+}
+
+/// Error of the streaming path
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    /// The strategy needs the whole text, not just letter counts
+    Unsupported(Strategy),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{}", e),
+            StreamError::Unsupported(strategy) => write!(
+                f,
+                "the {} optimization needs the whole text. Stream with basic, chi_squared or weighted",
+                strategy
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+/// Score of one shift
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShiftScore {
+    pub shift: u8,
+    pub score: f32,
+}
+
+/// Result of a streaming crack, without the decrypted text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamGuess {
+    pub histogram: LetterHistogram,
+    /// Scores sorted best first; ties keep shift order like crack_shift
+    pub scores: Vec<ShiftScore>,
+}
+
+impl StreamGuess {
+    pub fn best(&self) -> Option<ShiftScore> {
+        self.scores.first().copied()
+    }
+}
+
+/// Rank the shifts 0..depth of a counted text
+pub fn crack_histogram(
+    histogram: &LetterHistogram,
+    depth: u8,
+    strategy: Strategy,
+    profile: &LanguageProfile,
+) -> Result<Vec<ShiftScore>, StreamError> {
+    let mut scores = Vec::with_capacity(depth as usize);
+    for shift in 0..depth {
+        let score = histogram
+            .rotated(shift)
+            .score(strategy, profile)
+            .ok_or(StreamError::Unsupported(strategy))?;
+        scores.push(ShiftScore { shift, score });
+    }
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(scores)
+}
+
+/// Count a reader in one pass and rank the shifts 0..depth
+pub fn crack_stream<R: Read>(
+    reader: R,
+    depth: u8,
+    strategy: Strategy,
+    profile: &LanguageProfile,
+) -> Result<StreamGuess, StreamError> {
+    if matches!(strategy, Strategy::Bigram | Strategy::Trigram) {
+        return Err(StreamError::Unsupported(strategy));
+    }
+    let histogram = LetterHistogram::from_reader(reader)?;
+    let scores = crack_histogram(&histogram, depth, strategy, profile)?;
+    Ok(StreamGuess { histogram, scores })
+}
+
+/// Write `decrypt(text, shift)` of everything the reader yields, chunk by
+/// chunk. Returns the number of bytes written.
+pub fn decrypt_stream<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    shift: u8,
+) -> io::Result<u64> {
+    let shift = shift % 26;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut written = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        // ASCII bytes never occur inside a multi-byte character, so bytes
+        // can be rotated without decoding UTF-8
+        for b in &mut buffer[..read] {
+            if b.is_ascii_alphabetic() {
+                let base = if b.is_ascii_lowercase() { b'a' } else { b'A' };
+                *b = base + (*b - base + shift) % 26;
+            }
+        }
+        writer.write_all(&buffer[..read])?;
+        written += read as u64;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crack_shift, decrypt, language};
+
+    const MESSAGE: &str = "Ypp dy dro lexuob. Ofobi zobcyx pyb drowcovfoc, éé ñ 🦀";
+
+    // Reader that hands out a few bytes at a time, splitting characters
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_histogram_counts() {
+        let histogram = LetterHistogram::from_reader(Trickle("Abc aé🦀".as_bytes())).unwrap();
+        assert_eq!(histogram.letters(), 4);
+        assert_eq!(histogram.chars(), 7);
+        assert_eq!(histogram.bytes(), "Abc aé🦀".len() as u64);
+        assert_eq!(histogram, LetterHistogram::from_text("Abc aé🦀"));
+    }
+
+    #[test]
+    fn test_rotation_matches_decrypt() {
+        for shift in [0, 1, 13, 25] {
+            assert_eq!(
+                LetterHistogram::from_text(MESSAGE).rotated(shift),
+                LetterHistogram::from_text(&decrypt(MESSAGE, shift))
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream_matches_crack_shift() {
+        let english = language::english();
+        for strategy in [Strategy::Basic, Strategy::ChiSquared, Strategy::Weighted] {
+            let guess = crack_stream(MESSAGE.as_bytes(), 26, strategy, &english).unwrap();
+            let result = crack_shift(MESSAGE, 26, strategy.scorer().as_ref());
+            assert_eq!(
+                guess.best().unwrap().shift,
+                result.best().unwrap().shift,
+                "{}",
+                strategy
+            );
+            for candidate in &result.candidates {
+                let streamed = guess
+                    .scores
+                    .iter()
+                    .find(|s| s.shift == candidate.shift)
+                    .unwrap();
+                assert!(
+                    (streamed.score - candidate.score).abs() < 1e-2,
+                    "{}",
+                    strategy
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_rejects_ngram_strategies() {
+        let err = crack_stream(
+            MESSAGE.as_bytes(),
+            26,
+            Strategy::Bigram,
+            &language::english(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, StreamError::Unsupported(Strategy::Bigram)));
+        assert!(err.to_string().contains("chi_squared"));
+    }
+
+    #[test]
+    fn test_decrypt_stream() {
+        let mut output = Vec::new();
+        let written = decrypt_stream(Trickle(MESSAGE.as_bytes()), &mut output, 16).unwrap();
+        assert_eq!(written, MESSAGE.len() as u64);
+        assert_eq!(String::from_utf8(output).unwrap(), decrypt(MESSAGE, 16));
+    }
+}
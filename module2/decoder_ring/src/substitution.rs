@@ -0,0 +1,390 @@
+/*
+Monoalphabetic substitution solver.
+
+Any of the 26! keys may have been used, so the key is searched instead of
+enumerated:
+
+1. The first key maps the ciphertext letters, ranked by stats_analysis,
+   onto the letters of the language ranked by frequency.
+2. Hill climbing swaps two letters of the key and keeps the swap when the
+   quadgram log-probability of the decrypted letters improves, until no
+   swap helps.
+3. Random restarts perturb the first key and climb again. The restarts run
+   in parallel threads and the best key wins.
+
+Restart r uses the seed `seed + r`, so the result only depends on the seed
+and the number of restarts, not on the number of threads.
+*/
+
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::language::{self, LanguageProfile};
+use super::stats_analysis;
+
+// Random swaps applied to the first key before each restart but the first
+const PERTURB_SWAPS: usize = 6;
+
+// Position of the 50% confidence point between random and corpus scores,
+// and the width of the logistic curve, both as a fraction of that range
+const CONFIDENCE_MIDPOINT: f32 = 0.25;
+const CONFIDENCE_SPREAD: f32 = 0.1;
+
+const QUADGRAMS: usize = 26 * 26 * 26 * 26;
+
+/// Options for `solve_substitution`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolverOptions {
+    /// Number of hill-climbing runs
+    pub restarts: usize,
+    /// Threads the restarts are spread over
+    pub threads: usize,
+    /// Seed for reproducible results, random when None
+    pub seed: Option<u64>,
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        SolverOptions {
+            restarts: 16,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            seed: None,
+        }
+    }
+}
+
+/// Result of `solve_substitution`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionSolution {
+    /// Plaintext letter for each ciphertext letter a..z
+    pub key: String,
+    pub plaintext: String,
+    /// Average quadgram log10-probability of the plaintext
+    pub score: f32,
+    /// 0 for text scoring like random letters, 1 for typical language text
+    pub confidence: f32,
+}
+
+/// Quadgram log10-probabilities of a language, indexed by four letters.
+pub struct QuadgramModel {
+    log_probs: Vec<f32>,
+    // Average log-probability of quadgrams in the language and in random text
+    expected: f32,
+    random: f32,
+}
+
+fn quadgram_index(a: u8, b: u8, c: u8, d: u8) -> usize {
+    ((a as usize * 26 + b as usize) * 26 + c as usize) * 26 + d as usize
+}
+
+impl QuadgramModel {
+    /// Quadgrams missing from the profile get a tenth of the rarest probability.
+    pub fn new(profile: &LanguageProfile) -> Self {
+        let total: f32 = profile.quadgrams.values().sum();
+        let rarest = profile
+            .quadgrams
+            .values()
+            .copied()
+            .fold(f32::INFINITY, f32::min);
+        let floor = if total > 0.0 {
+            (rarest / total / 10.0).log10()
+        } else {
+            (1.0 / QUADGRAMS as f32).log10()
+        };
+
+        let mut log_probs = vec![floor; QUADGRAMS];
+        let mut expected = 0.0;
+        for (quadgram, freq) in &profile.quadgrams {
+            let letters: Vec<u8> = quadgram.bytes().map(|b| b.wrapping_sub(b'a')).collect();
+            if letters.len() == 4 && letters.iter().all(|l| *l < 26) {
+                let p = freq / total;
+                log_probs[quadgram_index(letters[0], letters[1], letters[2], letters[3])] =
+                    p.log10();
+                expected += p * p.log10();
+            }
+        }
+        let random = log_probs.iter().sum::<f32>() / QUADGRAMS as f32;
+
+        QuadgramModel {
+            log_probs,
+            expected: if total > 0.0 { expected } else { floor },
+            random,
+        }
+    }
+
+    /// Sum of the quadgram log-probabilities of the letters (indices 0..26)
+    pub fn score(&self, letters: &[u8]) -> f32 {
+        letters
+            .windows(4)
+            .map(|w| self.log_probs[quadgram_index(w[0], w[1], w[2], w[3])])
+            .sum()
+    }
+
+    /*
+    Confidence in 0..1 for an average quadgram log-probability:
+
+    `expected` is measured on the training corpus itself, so new text in the
+    language scores well below it (many of its quadgrams were never seen).
+    Language text lands around halfway between random and expected, random
+    letters at the bottom, so the confidence is a logistic curve centred a
+    quarter of the way up.
+    */
+    pub fn confidence(&self, average: f32) -> f32 {
+        let range = self.expected - self.random;
+        if range <= 0.0 {
+            return 0.0;
+        }
+        let midpoint = self.random + CONFIDENCE_MIDPOINT * range;
+        let x = (average - midpoint) / (CONFIDENCE_SPREAD * range);
+        1.0 / (1.0 + (-x).exp())
+    }
+}
+
+/// Apply a substitution key: the n-th letter of `key` replaces the n-th
+/// letter of the alphabet. Case and non-letters are preserved.
+pub fn apply_substitution(text: &str, key: &str) -> String {
+    let key: Vec<u8> = key.bytes().map(|b| b.to_ascii_lowercase()).collect();
+    if key.len() != 26 {
+        return text.to_string();
+    }
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_lowercase() {
+                key[(c as u8 - b'a') as usize] as char
+            } else if c.is_ascii_uppercase() {
+                key[(c as u8 - b'A') as usize].to_ascii_uppercase() as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+// Ciphertext letters ranked by frequency mapped onto the language ranking
+fn frequency_rank_key(text: &str, profile: &LanguageProfile) -> [u8; 26] {
+    let mut counts = [0u32; 26];
+    for (letter, count, _, _, _) in stats_analysis(text, profile) {
+        if letter.is_ascii_alphabetic() {
+            counts[(letter.to_ascii_lowercase() as u8 - b'a') as usize] += count;
+        }
+    }
+    let mut cipher_ranked: Vec<u8> = (0..26).collect();
+    cipher_ranked.sort_by(|a, b| counts[*b as usize].cmp(&counts[*a as usize]).then(a.cmp(b)));
+
+    let mut language_ranked: Vec<u8> = profile
+        .letters_by_frequency()
+        .iter()
+        .filter(|c| c.is_ascii_lowercase())
+        .map(|c| *c as u8 - b'a')
+        .collect();
+    // Letters missing from the profile go last
+    for l in 0..26 {
+        if !language_ranked.contains(&l) {
+            language_ranked.push(l);
+        }
+    }
+
+    let mut key = [0u8; 26];
+    for (cipher, plain) in cipher_ranked.iter().zip(&language_ranked) {
+        key[*cipher as usize] = *plain;
+    }
+    key
+}
+
+fn decrypt_letters(cipher: &[u8], key: &[u8; 26], out: &mut Vec<u8>) {
+    out.clear();
+    out.extend(cipher.iter().map(|l| key[*l as usize]));
+}
+
+// Swap key letters while the score improves, returns the final score
+fn hill_climb(cipher: &[u8], key: &mut [u8; 26], model: &QuadgramModel, rng: &mut StdRng) -> f32 {
+    let mut pairs: Vec<(usize, usize)> = (0..26)
+        .flat_map(|a| (a + 1..26).map(move |b| (a, b)))
+        .collect();
+    let mut plain = Vec::with_capacity(cipher.len());
+    decrypt_letters(cipher, key, &mut plain);
+    let mut best = model.score(&plain);
+
+    loop {
+        let mut improved = false;
+        pairs.shuffle(rng);
+        for &(a, b) in &pairs {
+            key.swap(a, b);
+            decrypt_letters(cipher, key, &mut plain);
+            let score = model.score(&plain);
+            if score > best {
+                best = score;
+                improved = true;
+            } else {
+                key.swap(a, b);
+            }
+        }
+        if !improved {
+            return best;
+        }
+    }
+}
+
+fn run_restart(
+    cipher: &[u8],
+    start: &[u8; 26],
+    model: &QuadgramModel,
+    seed: u64,
+    perturb: bool,
+) -> ([u8; 26], f32) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut key = *start;
+    if perturb {
+        for _ in 0..PERTURB_SWAPS {
+            let (a, b) = (rng.gen_range(0..26), rng.gen_range(0..26));
+            key.swap(a, b);
+        }
+    }
+    let score = hill_climb(cipher, &mut key, model, &mut rng);
+    (key, score)
+}
+
+/// Solve an English substitution cipher.
+pub fn solve_substitution(text: &str, options: &SolverOptions) -> SubstitutionSolution {
+    solve_substitution_with(text, &language::english(), options)
+}
+
+/// Solve a substitution cipher of a message written in the given language.
+pub fn solve_substitution_with(
+    text: &str,
+    profile: &LanguageProfile,
+    options: &SolverOptions,
+) -> SubstitutionSolution {
+    let cipher: Vec<u8> = text
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect();
+    let model = QuadgramModel::new(profile);
+    let start = frequency_rank_key(text, profile);
+    let base_seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let restarts = options.restarts.max(1);
+    let threads = options.threads.clamp(1, restarts);
+
+    // Thread t runs restarts t, t + threads, t + 2 * threads, ...
+    let mut results: Vec<(usize, [u8; 26], f32)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let (cipher, model) = (&cipher, &model);
+                scope.spawn(move || {
+                    (t..restarts)
+                        .step_by(threads)
+                        .map(|r| {
+                            let seed = base_seed.wrapping_add(r as u64);
+                            let (key, score) = run_restart(cipher, &start, model, seed, r > 0);
+                            (r, key, score)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("solver thread panicked"))
+            .collect()
+    });
+
+    // Best score wins, the lowest restart breaks ties
+    results.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+    let (_, key, score) = results[0];
+
+    let key: String = key.iter().map(|l| (b'a' + l) as char).collect();
+    let quadgrams = cipher.len().saturating_sub(3);
+    // Too short to judge: report the random score with no confidence
+    let (average, confidence) = if quadgrams > 0 {
+        let average = score / quadgrams as f32;
+        (average, model.confidence(average))
+    } else {
+        (model.random, 0.0)
+    };
+
+    SubstitutionSolution {
+        plaintext: apply_substitution(text, &key),
+        key,
+        score: average,
+        confidence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &str = "Every autumn the orchard behind the old mill fills with visitors who \
+        come to pick apples. Families walk between the rows with baskets, and the children climb \
+        the lowest branches while their parents argue about which tree has the sweetest fruit. \
+        In the evening the owner sells cider from a wooden table near the gate, and people sit on \
+        the grass until it becomes too cold and dark to stay outside.";
+
+    const KEY: &str = "qwertyuiopasdfghjklzxcvbnm";
+
+    fn options(seed: u64) -> SolverOptions {
+        SolverOptions {
+            restarts: 6,
+            threads: 3,
+            seed: Some(seed),
+        }
+    }
+
+    #[test]
+    fn test_apply_substitution() {
+        assert_eq!(apply_substitution("Hello, World!", KEY), "Itssg, Vgksr!");
+        assert_eq!(apply_substitution("abc", "short"), "abc");
+    }
+
+    #[test]
+    fn test_solves_substitution() {
+        let encrypted = apply_substitution(PLAINTEXT, KEY);
+        let solution = solve_substitution(&encrypted, &options(7));
+        assert_eq!(solution.plaintext, PLAINTEXT);
+        assert!(
+            solution.confidence > 0.8,
+            "confidence {}",
+            solution.confidence
+        );
+        // Every letter used in the message maps back through the key
+        assert_eq!(apply_substitution(&encrypted, &solution.key), PLAINTEXT);
+    }
+
+    #[test]
+    fn test_seed_is_reproducible_across_threads() {
+        let encrypted = apply_substitution(PLAINTEXT, KEY);
+        let first = solve_substitution(&encrypted, &options(42));
+        let single = SolverOptions {
+            threads: 1,
+            ..options(42)
+        };
+        assert_eq!(solve_substitution(&encrypted, &single), first);
+        assert_eq!(solve_substitution(&encrypted, &options(42)), first);
+    }
+
+    #[test]
+    fn test_random_letters_have_low_confidence() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let noise: String = (0..300)
+            .map(|_| (b'a' + rng.gen_range(0..26)) as char)
+            .collect();
+        let solution = solve_substitution(&noise, &options(1));
+        assert!(
+            solution.confidence < 0.6,
+            "confidence {}",
+            solution.confidence
+        );
+    }
+
+    #[test]
+    fn test_empty_message() {
+        let solution = solve_substitution("", &options(1));
+        assert_eq!(solution.plaintext, "");
+        assert_eq!(solution.key.len(), 26);
+        assert_eq!(solution.confidence, 0.0);
+    }
+}
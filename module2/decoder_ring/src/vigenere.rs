@@ -0,0 +1,385 @@
+/*
+Vigenère (polyalphabetic) cracking.
+
+The key length is estimated with Kasiski examination and the index of
+coincidence. Once the length is known the ciphertext is split into one
+column per key letter; every column is then a plain Caesar cipher and is
+solved with the same scorers used by guess_shift_optimized.
+*/
+
+use std::collections::HashMap;
+
+use super::language::{self, LanguageProfile};
+use super::{gen_counts, score_bigrams};
+
+// Index of coincidence of English text, random text is about 0.0385
+const ENGLISH_IOC: f32 = 0.0667;
+
+// Maximum number of bigram refinement passes over the key
+const REFINE_PASSES: usize = 3;
+
+/// Result of a Vigenère cracking attempt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VigenereGuess {
+    /// Recovered key in lowercase letters
+    pub key: String,
+    /// Decrypted message, case and punctuation preserved
+    pub decrypted: String,
+    /// Bigram score of the decrypted message
+    pub score: f32,
+}
+
+// Alphabet indices (0..26) of the ASCII letters in the text
+fn letter_indices(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase() as u8 - b'a')
+        .collect()
+}
+
+fn ioc_of(letters: &[u8]) -> f32 {
+    let n = letters.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let mut counts = [0u32; 26];
+    for &l in letters {
+        counts[l as usize] += 1;
+    }
+    let sum: u32 = counts.iter().map(|c| c * c.saturating_sub(1)).sum();
+    sum as f32 / (n * (n - 1)) as f32
+}
+
+/// Index of coincidence of the ASCII letters in `text`.
+///
+/// English text scores around 0.067, uniformly random letters around 0.038.
+pub fn index_of_coincidence(text: &str) -> f32 {
+    ioc_of(&letter_indices(text))
+}
+
+/// Average index of coincidence of the columns when the text is split
+/// into `key_len` interleaved columns.
+pub fn column_ioc(text: &str, key_len: usize) -> f32 {
+    let letters = letter_indices(text);
+    average_column_ioc(&letters, key_len)
+}
+
+fn average_column_ioc(letters: &[u8], key_len: usize) -> f32 {
+    if key_len == 0 {
+        return 0.0;
+    }
+    let total: f32 = split_columns(letters, key_len)
+        .iter()
+        .map(|column| ioc_of(column))
+        .sum();
+    total / key_len as f32
+}
+
+fn split_columns(letters: &[u8], key_len: usize) -> Vec<Vec<u8>> {
+    let mut columns = vec![Vec::new(); key_len];
+    for (i, &l) in letters.iter().enumerate() {
+        columns[i % key_len].push(l);
+    }
+    columns
+}
+
+/*
+Kasiski examination:
+
+Finds repeated trigrams in the letters of the text and, for each key length
+in 2..=max_key_len, counts how many distances between repeats it divides.
+Returns (key_length, votes) sorted by votes, highest first.
+*/
+pub fn kasiski_examination(text: &str, max_key_len: usize) -> Vec<(usize, u32)> {
+    let letters = letter_indices(text);
+    let mut positions: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for (i, trigram) in letters.windows(3).enumerate() {
+        positions.entry(trigram).or_default().push(i);
+    }
+
+    let mut votes: Vec<(usize, u32)> = (2..=max_key_len).map(|len| (len, 0)).collect();
+    for occurrences in positions.values().filter(|p| p.len() > 1) {
+        for pair in occurrences.windows(2) {
+            let distance = pair[1] - pair[0];
+            for (len, count) in votes.iter_mut() {
+                if distance % *len == 0 {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    votes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    votes
+}
+
+/*
+Estimate the key length:
+
+Every multiple of the real key length also produces English-like columns, so
+the shortest length whose average column IoC is close to the best one wins.
+Kasiski votes break ties between lengths of similar IoC.
+*/
+pub fn estimate_key_length(text: &str, max_key_len: usize) -> usize {
+    let letters = letter_indices(text);
+    // Keep at least two letters per column so the IoC is defined
+    let max_len = max_key_len.min(letters.len() / 2).max(1);
+
+    let iocs: Vec<(usize, f32)> = (1..=max_len)
+        .map(|len| (len, average_column_ioc(&letters, len)))
+        .collect();
+    let best_ioc = iocs.iter().map(|(_, ioc)| *ioc).fold(0.0, f32::max);
+    let threshold = best_ioc.min(ENGLISH_IOC) * 0.9;
+
+    let votes: HashMap<usize, u32> = kasiski_examination(text, max_len).into_iter().collect();
+    let candidates: Vec<usize> = iocs
+        .iter()
+        .filter(|(_, ioc)| *ioc >= threshold)
+        .map(|(len, _)| *len)
+        .collect();
+
+    // The shortest candidate, unless a multiple of it has clearly more Kasiski votes
+    let shortest = candidates.first().copied().unwrap_or(1);
+    let shortest_votes = votes.get(&shortest).copied().unwrap_or(0);
+    candidates
+        .iter()
+        .copied()
+        .find(|len| {
+            len % shortest == 0 && votes.get(len).copied().unwrap_or(0) > shortest_votes * 2
+        })
+        .unwrap_or(shortest)
+}
+
+/// Decrypt a Vigenère message with the given key.
+///
+/// Only ASCII letters advance the key; other characters pass through.
+pub fn decrypt_vigenere(text: &str, key: &str) -> String {
+    let shifts: Vec<u8> = letter_indices(key).iter().map(|k| (26 - k) % 26).collect();
+    if shifts.is_empty() {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    for c in text.chars() {
+        if c.is_ascii_alphabetic() {
+            let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+            let offset = (c as u8 - base + shifts[i % shifts.len()]) % 26;
+            result.push((base + offset) as char);
+            i += 1;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Chi-squared of letter counts (a to z) against the reference letters.
+// Unlike score_chi_squared, reference letters missing from the counts
+// still add their full expected count, which matters for the short
+// columns of long keys.
+fn letter_chi_squared(counts: &[u32; 26], reference: &HashMap<char, f32>) -> f32 {
+    let total: u32 = counts.iter().sum();
+    let mut chi_squared = 0.0;
+    for (letter, freq) in reference {
+        let expected = freq / 100.0 * total as f32;
+        let observed = match letter {
+            'a'..='z' => counts[(*letter as u8 - b'a') as usize] as f32,
+            _ => 0.0,
+        };
+        if expected > 0.0 {
+            chi_squared += (observed - expected).powi(2) / expected;
+        }
+    }
+    chi_squared
+}
+
+/// Caesar shifts of a letter histogram (a to z) ranked by chi-squared,
+/// best first; `decrypt(text, shift)` undoes the best one
+pub(crate) fn rank_shifts(histogram: &[u32; 26], profile: &LanguageProfile) -> Vec<u8> {
+    let reference = gen_counts(profile);
+    let mut scored: Vec<(u8, f32)> = (0..26)
+        .map(|shift| {
+            let mut shifted = [0u32; 26];
+            for (letter, count) in histogram.iter().enumerate() {
+                shifted[(letter + shift as usize) % 26] = *count;
+            }
+            (shift, -letter_chi_squared(&shifted, &reference))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.into_iter().map(|(shift, _)| shift).collect()
+}
+
+// Caesar shifts for one column ranked by chi-squared, best first
+fn rank_column_shifts(column: &[u8], profile: &LanguageProfile) -> Vec<u8> {
+    let mut histogram = [0u32; 26];
+    for letter in column {
+        histogram[*letter as usize] += 1;
+    }
+    rank_shifts(&histogram, profile)
+}
+
+fn key_from_shifts(shifts: &[u8]) -> String {
+    shifts
+        .iter()
+        .map(|shift| (b'a' + (26 - shift) % 26) as char)
+        .collect()
+}
+
+/// Recover the key of a message with the given key length, scoring
+/// against the given language profile.
+pub fn crack_with_key_length(
+    text: &str,
+    key_len: usize,
+    profile: &LanguageProfile,
+) -> VigenereGuess {
+    let letters = letter_indices(text);
+    if letters.is_empty() || key_len == 0 {
+        return VigenereGuess {
+            key: String::new(),
+            decrypted: text.to_string(),
+            score: 0.0,
+        };
+    }
+
+    let ranked: Vec<Vec<u8>> = split_columns(&letters, key_len)
+        .iter()
+        .map(|column| rank_column_shifts(column, profile))
+        .collect();
+
+    // Start from the best chi-squared shift of every column, then let the
+    // bigram score of the whole message correct columns one at a time
+    let mut shifts: Vec<u8> = ranked.iter().map(|r| r[0]).collect();
+    let mut best_score = score_bigrams(&decrypt_vigenere(text, &key_from_shifts(&shifts)), profile);
+    for _ in 0..REFINE_PASSES {
+        let mut improved = false;
+        for (column, candidates) in ranked.iter().enumerate() {
+            for &shift in candidates.iter().skip(1) {
+                let previous = shifts[column];
+                shifts[column] = shift;
+                let score =
+                    score_bigrams(&decrypt_vigenere(text, &key_from_shifts(&shifts)), profile);
+                if score > best_score {
+                    best_score = score;
+                    improved = true;
+                } else {
+                    shifts[column] = previous;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+
+    let key = key_from_shifts(&shifts);
+    let decrypted = decrypt_vigenere(text, &key);
+    VigenereGuess {
+        key,
+        decrypted,
+        score: best_score,
+    }
+}
+
+/*
+Guess a Vigenère key:
+
+Accepts:
+ * text: the message to decrypt
+ * max_key_len: the longest key length considered
+Returns the recovered key, the decrypted message and its bigram score.
+*/
+pub fn guess_vigenere(text: &str, max_key_len: usize) -> VigenereGuess {
+    guess_vigenere_with(text, max_key_len, &language::english())
+}
+
+/// Guess a Vigenère key of a message written in the given language.
+pub fn guess_vigenere_with(
+    text: &str,
+    max_key_len: usize,
+    profile: &LanguageProfile,
+) -> VigenereGuess {
+    let key_len = estimate_key_length(text, max_key_len);
+    crack_with_key_length(text, key_len, profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &str = "It was the best of times, it was the worst of times, it was the age of wisdom, \
+        it was the age of foolishness, it was the epoch of belief, it was the epoch of incredulity, \
+        it was the season of light, it was the season of darkness, it was the spring of hope, \
+        it was the winter of despair, we had everything before us, we had nothing before us, \
+        we were all going direct to heaven, we were all going direct the other way. In short, \
+        the period was so far like the present period, that some of its noisiest authorities \
+        insisted on its being received, for good or for evil, in the superlative degree of \
+        comparison only. There were a king with a large jaw and a queen with a plain face, \
+        on the throne of England; there were a king with a large jaw and a queen with a fair \
+        face, on the throne of France.";
+
+    fn encrypt_vigenere(text: &str, key: &str) -> String {
+        let inverse: String = letter_indices(key)
+            .iter()
+            .map(|k| (b'a' + (26 - k) % 26) as char)
+            .collect();
+        decrypt_vigenere(text, &inverse)
+    }
+
+    #[test]
+    fn test_decrypt_vigenere_known_vector() {
+        // Classic example: ATTACKATDAWN with key LEMON
+        assert_eq!(decrypt_vigenere("LXFOPVEFRNHR", "lemon"), "ATTACKATDAWN");
+    }
+
+    #[test]
+    fn test_decrypt_vigenere_preserves_non_letters() {
+        let encrypted = encrypt_vigenere("Off to the bunker!", "key");
+        assert_eq!(decrypt_vigenere(&encrypted, "KEY"), "Off to the bunker!");
+    }
+
+    #[test]
+    fn test_index_of_coincidence() {
+        assert!(index_of_coincidence(PLAINTEXT) > 0.055);
+        let encrypted = encrypt_vigenere(PLAINTEXT, "dickens");
+        assert!(index_of_coincidence(&encrypted) < 0.05);
+    }
+
+    #[test]
+    fn test_estimate_key_length() {
+        for key in ["lemon", "cipher", "dickens"] {
+            let encrypted = encrypt_vigenere(PLAINTEXT, key);
+            assert_eq!(
+                estimate_key_length(&encrypted, 20),
+                key.len(),
+                "key {}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_kasiski_finds_key_length_multiple() {
+        let encrypted = encrypt_vigenere(PLAINTEXT, "lemon");
+        let votes = kasiski_examination(&encrypted, 20);
+        assert_eq!(votes[0].0 % 5, 0);
+    }
+
+    #[test]
+    fn test_guess_vigenere_recovers_key() {
+        for key in ["lemon", "cipher", "dickens"] {
+            let encrypted = encrypt_vigenere(PLAINTEXT, key);
+            let guess = guess_vigenere(&encrypted, 20);
+            assert_eq!(guess.key, key);
+            assert_eq!(guess.decrypted, PLAINTEXT);
+        }
+    }
+
+    #[test]
+    fn test_guess_vigenere_empty_message() {
+        let guess = guess_vigenere("", 20);
+        assert_eq!(guess.key, "");
+        assert_eq!(guess.decrypted, "");
+    }
+}
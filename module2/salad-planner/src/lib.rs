@@ -0,0 +1,208 @@
+/*
+A planner for events with several fruit salads.
+
+A menu lists the salads with their servings, fruit constraints and
+dressing rules. Planning it picks the fruits of each salad from the fruit
+catalog and adds up one shopping list with the portions and estimated cost
+of every fruit and the dressing bottles. A plan prints as text and writes
+as CSV.
+*/
+
+pub mod menu;
+pub mod plan;
+
+pub use menu::{DressingRule, Menu, MenuError, MenuSalad};
+pub use plan::{Plan, PlanError, Recipe, ShoppingItem, ShoppingList, plan_menu};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fruit_catalog::{Catalog, Season};
+    use fruit_salad_maker::DRESSINGS;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    const PARTY: &str = include_str!("../menus/party.toml");
+
+    fn rng(seed: u64) -> StdRng {
+        StdRng::seed_from_u64(seed)
+    }
+
+    fn parse(text: &str) -> Menu {
+        Menu::from_toml(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_menu() {
+        let menu = parse(PARTY);
+        assert_eq!(menu.name, "Summer party");
+        assert_eq!(menu.dressing, DressingRule::Random);
+        assert!(menu.distinct_dressings);
+        assert_eq!(menu.salads.len(), 3);
+
+        let light = &menu.salads[0];
+        assert_eq!(light.servings, 10);
+        assert_eq!(light.constraints().count, 3);
+        assert_eq!(light.constraints().season, Some(Season::Summer));
+        assert_eq!(menu.dressing_of(light), &DressingRule::Random);
+
+        let kids = &menu.salads[2];
+        assert_eq!(kids.include, ["Apple", "Orange", "Peach"]);
+        assert_eq!(
+            menu.dressing_of(kids),
+            &DressingRule::Named("Honey Drizzle".to_string())
+        );
+    }
+
+    #[test]
+    fn test_menu_defaults_and_errors() {
+        let menu = parse("[[salad]]\nname = \"Plain\"\ndressing = \"None\"\n");
+        assert_eq!(menu.dressing, DressingRule::None);
+        assert!(!menu.distinct_dressings);
+        assert_eq!(menu.salads[0].servings, 1);
+        assert_eq!(menu.salads[0].dressing, Some(DressingRule::None));
+
+        let err = Menu::from_toml("[[salad]]\nservings = 2\n").unwrap_err();
+        assert!(err.to_string().starts_with("invalid menu:"));
+        assert!(Menu::load("no-such-menu.toml").is_err());
+    }
+
+    #[test]
+    fn test_plan_party() {
+        let menu = parse(PARTY);
+        let plan = plan_menu(&menu, &Catalog::builtin(), &mut rng(3)).unwrap();
+        assert_eq!(plan.recipes.len(), 3);
+
+        let light = &plan.recipes[0];
+        assert_eq!(light.salad.len(), 3);
+        assert!(light.salad.total_calories() <= 160);
+        assert!(light.salad.iter().all(|f| f.in_season(Season::Summer)));
+
+        let pollen_free = &plan.recipes[1];
+        assert!(
+            pollen_free
+                .salad
+                .iter()
+                .all(|f| !f.has_allergen("birch pollen"))
+        );
+
+        let kids = &plan.recipes[2];
+        let mut names = kids.salad.names();
+        names.sort();
+        assert_eq!(names, ["Apple", "Orange", "Peach"]);
+        assert_eq!(kids.dressing.as_deref(), Some("Honey Drizzle"));
+
+        let mut dressings: Vec<&str> = plan
+            .recipes
+            .iter()
+            .map(|r| r.dressing.as_deref().unwrap())
+            .collect();
+        dressings.sort();
+        dressings.dedup();
+        assert_eq!(dressings.len(), 3, "distinct dressings");
+
+        // The same seed plans the same menu
+        assert_eq!(
+            plan,
+            plan_menu(&menu, &Catalog::builtin(), &mut rng(3)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_shopping_list_aggregates_portions() {
+        let menu = parse(
+            "[[salad]]\nname = \"A\"\nservings = 4\ninclude = [\"Apple\", \"Pear\"]\n\
+             dressing = \"Mint Lime\"\n\
+             [[salad]]\nname = \"B\"\nservings = 3\ninclude = [\"Pear\", \"Fig\"]\n\
+             dressing = \"Mint Lime\"\n",
+        );
+        let plan = plan_menu(&menu, &Catalog::builtin(), &mut rng(1)).unwrap();
+        let shopping = &plan.shopping;
+
+        let portions: Vec<(&str, u32)> = shopping
+            .fruits
+            .iter()
+            .map(|item| (item.name.as_str(), item.quantity))
+            .collect();
+        assert_eq!(portions, [("Apple", 4), ("Fig", 3), ("Pear", 7)]);
+        assert_eq!(
+            shopping.dressings,
+            [ShoppingItem {
+                name: "Mint Lime".to_string(),
+                quantity: 2,
+                price: 0.0
+            }]
+        );
+
+        let recipe_cost: f64 = plan.recipes.iter().map(Recipe::cost).sum();
+        assert!((shopping.total_cost() - recipe_cost).abs() < 1e-9);
+        let pear = &shopping.fruits[2];
+        assert!((pear.cost() - 7.0 * pear.price).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_plan_errors() {
+        let catalog = Catalog::builtin();
+
+        let menu = parse("[[salad]]\nname = \"Huge\"\nfruits = 11\n");
+        let err = plan_menu(&menu, &catalog, &mut rng(1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "salad 'Huge': Requested 11 fruits but only 10 are available."
+        );
+
+        let menu = parse("[[salad]]\nname = \"Empty\"\nservings = 0\n");
+        assert_eq!(
+            plan_menu(&menu, &catalog, &mut rng(1)),
+            Err(PlanError::NoServings("Empty".to_string()))
+        );
+
+        let menu = parse(
+            "distinct_dressings = true\ndressing = \"Maple Syrup\"\n\
+             [[salad]]\nname = \"A\"\n[[salad]]\nname = \"B\"\n",
+        );
+        assert_eq!(
+            plan_menu(&menu, &catalog, &mut rng(1)),
+            Err(PlanError::DressingReused("Maple Syrup".to_string()))
+        );
+
+        let salads = "[[salad]]\nname = \"S\"\n".repeat(DRESSINGS.len() + 1);
+        let menu = parse(&format!(
+            "distinct_dressings = true\ndressing = \"random\"\n{}",
+            salads
+        ));
+        assert_eq!(
+            plan_menu(&menu, &catalog, &mut rng(1)),
+            Err(PlanError::NoDressingLeft("S".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_plan_text_and_csv() {
+        let menu = parse(
+            "name = \"Brunch\"\n[[salad]]\nname = \"Duo\"\nservings = 2\n\
+             include = [\"Apple\", \"Pear\"]\ndressing = \"Mint Lime\"\n",
+        );
+        let mut plan = plan_menu(&menu, &Catalog::builtin(), &mut rng(1)).unwrap();
+        plan.recipes[0].salad.sort_by_name();
+
+        let text = plan.to_string();
+        assert!(text.starts_with("Brunch\n======\n\nDuo, 2 servings\n"));
+        assert!(text.contains("  Dressing: Mint Lime\n"));
+        assert!(text.contains("Shopping list\n"));
+        assert!(text.contains("  Mint Lime                   1 bottles\n"));
+        assert!(text.ends_with("Total: 2.40 EUR"));
+
+        assert_eq!(
+            plan.to_csv(),
+            "kind,salad,name,quantity,price,cost\n\
+             fruit,Duo,Apple,2,0.50,1.00\n\
+             fruit,Duo,Pear,2,0.70,1.40\n\
+             dressing,Duo,Mint Lime,1,,\n\
+             buy,,Apple,2,0.50,1.00\n\
+             buy,,Pear,2,0.70,1.40\n\
+             buy,,Mint Lime,1,,\n\
+             total,,,,,2.40\n"
+        );
+    }
+}
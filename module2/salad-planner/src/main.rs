@@ -0,0 +1,85 @@
+/*
+Usage:
+
+cargo run -- menus/party.toml
+the same plan every time, as CSV
+cargo run -- menus/party.toml --seed 5 --format csv
+saving the plan, the format from the extension (.csv, anything else is text)
+cargo run -- menus/party.toml --catalog my-fruits.toml --output party.csv
+
+ */
+
+use clap::{Parser, ValueEnum};
+use fruit_catalog::Catalog;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use salad_planner::{Menu, plan_menu};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Csv,
+}
+
+#[derive(Parser)]
+#[clap(
+    version = "1.0",
+    about = "Plan the fruit salads of a menu and the shopping list"
+)]
+struct Opts {
+    /// Menu file (.toml) with the salads, servings and dressing rules
+    menu: PathBuf,
+    /// Fruit catalog file (.toml, .json or .csv), the built-in catalog otherwise
+    #[clap(long)]
+    catalog: Option<PathBuf>,
+    /// Output format, text by default or from the --output extension
+    #[clap(short, long, value_enum)]
+    format: Option<OutputFormat>,
+    /// Write the plan to this file instead of printing it
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// Seed for the fruits and dressings, the same seed gives the same plan
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+fn exit_with(e: impl std::fmt::Display) -> ! {
+    eprintln!("Error: {}", e);
+    std::process::exit(1);
+}
+
+fn main() {
+    let opts = Opts::parse();
+
+    let menu = Menu::load(&opts.menu).unwrap_or_else(|e| exit_with(e));
+    let catalog = match &opts.catalog {
+        Some(path) => Catalog::load(path).unwrap_or_else(|e| exit_with(e)),
+        None => Catalog::builtin(),
+    };
+    let mut rng = match opts.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let plan = plan_menu(&menu, &catalog, &mut rng).unwrap_or_else(|e| exit_with(e));
+
+    let csv_output = opts
+        .output
+        .as_ref()
+        .and_then(|path| path.extension())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let text = match opts.format {
+        Some(OutputFormat::Csv) => plan.to_csv(),
+        None if csv_output => plan.to_csv(),
+        _ => format!("{}\n", plan),
+    };
+
+    match &opts.output {
+        Some(path) => {
+            std::fs::write(path, text).unwrap_or_else(|e| exit_with(e));
+            println!("Plan saved to: {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+}
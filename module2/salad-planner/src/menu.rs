@@ -0,0 +1,147 @@
+/*
+Menu definitions for the planner.
+
+A menu is a TOML file with a name, the dressing rule of the whole menu and
+one [[salad]] table per salad, see menus/party.toml:
+
+    name = "Summer party"
+    dressing = "random"          # "random", "none" or a dressing name
+    distinct_dressings = true    # no dressing is used twice
+
+    [[salad]]
+    name = "Light"
+    servings = 10                # one portion of each fruit per serving
+    fruits = 3                   # 0 or missing for as many as fit
+    max_calories = 160           # per serving
+    season = "summer"
+    exclude_allergens = ["birch pollen"]
+    include = ["Apple", "Pear"]  # pick from these fruits only
+    budget = 4.0                 # euros per serving
+    dressing = "Honey Drizzle"   # overrides the menu rule
+*/
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use fruit_catalog::{Constraints, Season};
+use serde::Deserialize;
+
+/// Error reading a menu file
+#[derive(Debug)]
+pub enum MenuError {
+    Io(io::Error),
+    Toml(String),
+}
+
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MenuError::Io(e) => write!(f, "{}", e),
+            MenuError::Toml(e) => write!(f, "invalid menu: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MenuError {}
+
+impl From<io::Error> for MenuError {
+    fn from(e: io::Error) -> Self {
+        MenuError::Io(e)
+    }
+}
+
+/// How a salad gets its dressing
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(from = "String")]
+pub enum DressingRule {
+    /// No dressing
+    #[default]
+    None,
+    /// A random dressing from `DRESSINGS`
+    Random,
+    /// This dressing
+    Named(String),
+}
+
+impl From<String> for DressingRule {
+    /// "none" and "random" in any case, anything else names a dressing
+    fn from(rule: String) -> Self {
+        match rule.trim().to_lowercase().as_str() {
+            "" | "none" => DressingRule::None,
+            "random" => DressingRule::Random,
+            _ => DressingRule::Named(rule.trim().to_string()),
+        }
+    }
+}
+
+/// One salad of a menu
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MenuSalad {
+    pub name: String,
+    #[serde(default = "one")]
+    pub servings: u32,
+    /// Number of fruits, 0 for as many as fit
+    #[serde(default)]
+    pub fruits: usize,
+    /// Fruits to choose from instead of the whole catalog
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub max_calories: Option<u32>,
+    #[serde(default)]
+    pub exclude_allergens: Vec<String>,
+    #[serde(default)]
+    pub season: Option<Season>,
+    #[serde(default)]
+    pub budget: Option<f64>,
+    /// Overrides the dressing rule of the menu
+    #[serde(default)]
+    pub dressing: Option<DressingRule>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl MenuSalad {
+    /// The catalog constraints of one serving
+    pub fn constraints(&self) -> Constraints {
+        Constraints {
+            count: self.fruits,
+            max_calories: self.max_calories,
+            exclude_allergens: self.exclude_allergens.clone(),
+            season: self.season,
+            budget: self.budget,
+        }
+    }
+}
+
+/// Salads to plan together
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Menu {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub dressing: DressingRule,
+    #[serde(default)]
+    pub distinct_dressings: bool,
+    #[serde(default, rename = "salad")]
+    pub salads: Vec<MenuSalad>,
+}
+
+impl Menu {
+    pub fn from_toml(text: &str) -> Result<Self, MenuError> {
+        toml::from_str(text).map_err(|e| MenuError::Toml(e.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MenuError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// The dressing rule of a salad, its own or the menu's
+    pub fn dressing_of<'a>(&'a self, salad: &'a MenuSalad) -> &'a DressingRule {
+        salad.dressing.as_ref().unwrap_or(&self.dressing)
+    }
+}
@@ -0,0 +1,328 @@
+/*
+Planning a menu: a recipe per salad and one shopping list.
+
+Each salad is built from the catalog with create_salad of the fruit salad
+maker. A serving holds one portion of every fruit of the salad, so a salad
+of 10 servings needs 10 portions of each of its fruits. The shopping list
+counts the portions of all salads with count_fruits of the fruit vector
+lab, and the dressings with one bottle per salad.
+
+Random dressings come from get_random_dressing_with. With
+distinct_dressings a dressing that is already taken, by a named dressing
+anywhere in the menu or by an earlier salad, is drawn again.
+*/
+
+use std::fmt;
+
+use fruit_catalog::{Catalog, Salad, SaladError};
+use fruit_salad_maker::{DRESSINGS, create_salad, get_random_dressing_with};
+use fruit_vector_lab::count_fruits;
+use rand::Rng;
+
+use crate::menu::{DressingRule, Menu};
+
+/// Why a menu could not be planned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    /// A salad whose fruits do not fit its constraints
+    Salad { salad: String, error: SaladError },
+    /// A salad with zero servings
+    NoServings(String),
+    /// With distinct dressings, a named dressing used by two salads
+    DressingReused(String),
+    /// With distinct dressings, a random dressing when all are taken
+    NoDressingLeft(String),
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanError::Salad { salad, error } => write!(f, "salad '{}': {}", salad, error),
+            PlanError::NoServings(salad) => write!(f, "salad '{}' has no servings", salad),
+            PlanError::DressingReused(dressing) => write!(
+                f,
+                "dressing '{}' is used twice but the menu asks for distinct dressings",
+                dressing
+            ),
+            PlanError::NoDressingLeft(salad) => write!(
+                f,
+                "salad '{}': all {} dressings are already used",
+                salad,
+                DRESSINGS.len()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// One planned salad
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe {
+    pub name: String,
+    pub servings: u32,
+    /// The fruits of one serving
+    pub salad: Salad,
+    pub dressing: Option<String>,
+}
+
+impl Recipe {
+    /// Price of all servings
+    pub fn cost(&self) -> f64 {
+        self.servings as f64 * self.salad.total_price()
+    }
+}
+
+/// A line of the shopping list. Fruits are counted in portions at the
+/// catalog price, dressings in bottles without a price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub name: String,
+    pub quantity: u32,
+    pub price: f64,
+}
+
+impl ShoppingItem {
+    pub fn cost(&self) -> f64 {
+        self.quantity as f64 * self.price
+    }
+}
+
+/// Everything to buy for a menu, sorted by name
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShoppingList {
+    pub fruits: Vec<ShoppingItem>,
+    pub dressings: Vec<ShoppingItem>,
+}
+
+impl ShoppingList {
+    pub fn from_recipes(recipes: &[Recipe]) -> Self {
+        let portions: Vec<&str> = recipes
+            .iter()
+            .flat_map(|recipe| {
+                recipe.salad.iter().flat_map(|fruit| {
+                    std::iter::repeat_n(fruit.name.as_str(), recipe.servings as usize)
+                })
+            })
+            .collect();
+        let price = |name: &str| {
+            recipes
+                .iter()
+                .flat_map(|recipe| recipe.salad.iter())
+                .find(|fruit| fruit.name == name)
+                .map_or(0.0, |fruit| fruit.price)
+        };
+        let fruits = count_fruits(&portions)
+            .into_iter()
+            .map(|(name, quantity)| ShoppingItem {
+                name: name.to_string(),
+                quantity: quantity as u32,
+                price: price(name),
+            })
+            .collect();
+
+        let bottles: Vec<&str> = recipes
+            .iter()
+            .filter_map(|recipe| recipe.dressing.as_deref())
+            .collect();
+        let dressings = count_fruits(&bottles)
+            .into_iter()
+            .map(|(name, quantity)| ShoppingItem {
+                name: name.to_string(),
+                quantity: quantity as u32,
+                price: 0.0,
+            })
+            .collect();
+
+        let mut list = ShoppingList { fruits, dressings };
+        list.fruits.sort_by(|a, b| a.name.cmp(&b.name));
+        list.dressings.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.fruits.iter().map(ShoppingItem::cost).sum()
+    }
+}
+
+/// The recipes and shopping list of a menu
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub name: String,
+    pub recipes: Vec<Recipe>,
+    pub shopping: ShoppingList,
+}
+
+impl Plan {
+    /// One CSV table of the recipes, the shopping list and the total cost,
+    /// told apart by the first column:
+    ///
+    /// ```text
+    /// kind,salad,name,quantity,price,cost
+    /// fruit,Light,Loquat,10,1.40,14.00
+    /// dressing,Light,Mint Lime,1,,
+    /// buy,,Loquat,10,1.40,14.00
+    /// buy,,Mint Lime,1,,
+    /// total,,,,,14.00
+    /// ```
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut row =
+            |fields: [&str; 6]| writer.write_record(fields).expect("row writes to memory");
+        row(["kind", "salad", "name", "quantity", "price", "cost"]);
+        for recipe in &self.recipes {
+            let servings = recipe.servings.to_string();
+            for fruit in &recipe.salad {
+                row([
+                    "fruit",
+                    &recipe.name,
+                    &fruit.name,
+                    &servings,
+                    &format!("{:.2}", fruit.price),
+                    &format!("{:.2}", recipe.servings as f64 * fruit.price),
+                ]);
+            }
+            if let Some(dressing) = &recipe.dressing {
+                row(["dressing", &recipe.name, dressing, "1", "", ""]);
+            }
+        }
+        for item in &self.shopping.fruits {
+            row([
+                "buy",
+                "",
+                &item.name,
+                &item.quantity.to_string(),
+                &format!("{:.2}", item.price),
+                &format!("{:.2}", item.cost()),
+            ]);
+        }
+        for item in &self.shopping.dressings {
+            row(["buy", "", &item.name, &item.quantity.to_string(), "", ""]);
+        }
+        row([
+            "total",
+            "",
+            "",
+            "",
+            "",
+            &format!("{:.2}", self.shopping.total_cost()),
+        ]);
+        String::from_utf8(writer.into_inner().expect("CSV flushes to memory"))
+            .expect("CSV is UTF-8")
+    }
+}
+
+impl fmt::Display for Plan {
+    /// The recipes followed by the shopping list
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.name.is_empty() {
+            writeln!(
+                f,
+                "{}\n{}\n",
+                self.name,
+                "=".repeat(self.name.chars().count())
+            )?;
+        }
+        for recipe in &self.recipes {
+            writeln!(f, "{}, {} servings", recipe.name, recipe.servings)?;
+            for fruit in &recipe.salad {
+                writeln!(f, "  {:<24} {:>4} portions", fruit.name, recipe.servings)?;
+            }
+            if let Some(dressing) = &recipe.dressing {
+                writeln!(f, "  Dressing: {}", dressing)?;
+            }
+            writeln!(
+                f,
+                "  {} kcal and {:.2} EUR per serving, {:.2} EUR in total",
+                recipe.salad.total_calories(),
+                recipe.salad.total_price(),
+                recipe.cost()
+            )?;
+            let allergens = recipe.salad.allergens();
+            if !allergens.is_empty() {
+                writeln!(f, "  Allergens: {}", allergens.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "Shopping list")?;
+        for item in &self.shopping.fruits {
+            writeln!(
+                f,
+                "  {:<24} {:>4} x {:.2} EUR = {:>7.2} EUR",
+                item.name,
+                item.quantity,
+                item.price,
+                item.cost()
+            )?;
+        }
+        for item in &self.shopping.dressings {
+            writeln!(f, "  {:<24} {:>4} bottles", item.name, item.quantity)?;
+        }
+        write!(f, "Total: {:.2} EUR", self.shopping.total_cost())
+    }
+}
+
+/// Plan every salad of the menu in order
+pub fn plan_menu<R: Rng + ?Sized>(
+    menu: &Menu,
+    catalog: &Catalog,
+    rng: &mut R,
+) -> Result<Plan, PlanError> {
+    // Named dressings are reserved before any random one is drawn
+    let mut used: Vec<String> = Vec::new();
+    if menu.distinct_dressings {
+        for salad in &menu.salads {
+            if let DressingRule::Named(dressing) = menu.dressing_of(salad) {
+                if used.contains(dressing) {
+                    return Err(PlanError::DressingReused(dressing.clone()));
+                }
+                used.push(dressing.clone());
+            }
+        }
+    }
+
+    let mut recipes = Vec::new();
+    for entry in &menu.salads {
+        if entry.servings == 0 {
+            return Err(PlanError::NoServings(entry.name.clone()));
+        }
+        let salad =
+            create_salad(catalog, &entry.include, &entry.constraints(), rng).map_err(|error| {
+                PlanError::Salad {
+                    salad: entry.name.clone(),
+                    error,
+                }
+            })?;
+        let dressing = match menu.dressing_of(entry) {
+            DressingRule::None => None,
+            DressingRule::Named(dressing) => Some(dressing.clone()),
+            DressingRule::Random if !menu.distinct_dressings => Some(get_random_dressing_with(rng)),
+            DressingRule::Random => {
+                if DRESSINGS.iter().all(|d| used.iter().any(|u| u == d)) {
+                    return Err(PlanError::NoDressingLeft(entry.name.clone()));
+                }
+                let dressing = loop {
+                    let dressing = get_random_dressing_with(rng);
+                    if !used.contains(&dressing) {
+                        break dressing;
+                    }
+                };
+                used.push(dressing.clone());
+                Some(dressing)
+            }
+        };
+        recipes.push(Recipe {
+            name: entry.name.clone(),
+            servings: entry.servings,
+            salad,
+            dressing,
+        });
+    }
+
+    let shopping = ShoppingList::from_recipes(&recipes);
+    Ok(Plan {
+        name: menu.name.clone(),
+        recipes,
+        shopping,
+    })
+}